@@ -7,6 +7,14 @@ use std::sync::Mutex;
 pub const TB_FAVOURATES_MUSIC: &str = "favourates_music";
 pub const TB_FAVOURATES_PLAYLIST: &str = "favourates_playlist";
 pub const TB_FAVOURATES_ARTIST: &str = "favourates_artist";
+pub const TB_QUEUE: &str = "play_queue";
+pub const TB_BOOKMARKS: &str = "bookmarks";
+pub const TB_HISTORY: &str = "play_history";
+pub const TB_RECENTS: &str = "recently_played";
+pub const TB_SETTINGS: &str = "settings";
+pub const TB_SEARCHES: &str = "search_history";
+pub const TB_SCROBBLES: &str = "scrobble_queue";
+pub const TB_RESPONSES: &str = "response_cache";
 
 compute_static! {
     pub static ref CONFIG: Config = {
@@ -0,0 +1,380 @@
+use super::ShortcutsKeys;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+const CTRL_PREFIX: &str = "<CTRL>+";
+const ALT_PREFIX: &str = "<ALT>+";
+
+// Key along with the modifiers it is pressed with. Written in config as the key alone ("d") or
+// with modifiers before it ("<CTRL>+d", "<ALT>+p", "<CTRL>+<ALT>+c") same as shown in help
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub key: char,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyCombo {
+    pub fn plain(key: char) -> Self {
+        KeyCombo {
+            key,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    pub fn with_ctrl(key: char) -> Self {
+        KeyCombo {
+            ctrl: true,
+            ..Self::plain(key)
+        }
+    }
+
+    pub fn with_alt(key: char) -> Self {
+        KeyCombo {
+            alt: true,
+            ..Self::plain(key)
+        }
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            f.write_str(CTRL_PREFIX)?;
+        }
+        if self.alt {
+            f.write_str(ALT_PREFIX)?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = String;
+
+    // Modifiers may be written in any case and order. Whatever is left after them should be a
+    // single character
+    fn from_str(combo: &str) -> Result<Self, Self::Err> {
+        let mut rest = combo;
+        let (mut ctrl, mut alt) = (false, false);
+        loop {
+            let upper = rest.to_ascii_uppercase();
+            if upper.starts_with(CTRL_PREFIX) && rest.len() > CTRL_PREFIX.len() {
+                ctrl = true;
+                rest = &rest[CTRL_PREFIX.len()..];
+            } else if upper.starts_with(ALT_PREFIX) && rest.len() > ALT_PREFIX.len() {
+                alt = true;
+                rest = &rest[ALT_PREFIX.len()..];
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(key), None) => Ok(KeyCombo { key, ctrl, alt }),
+            _ => Err(format!("{:?} is not a single key with modifiers", combo)),
+        }
+    }
+}
+
+impl Serialize for KeyCombo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyCombo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let combo = String::deserialize(deserializer)?;
+        combo.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// Everything that can be bound to a key. Name of each action is same as its field in
+// ShortcutKeys. Actions that are done by pressing key of another shortcut with a modifier have
+// their own name. eg: play_next is the next key with <CTRL>. Actions are listed in help in the
+// order they are declared here
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Help,
+    StartSearch,
+    EditSearch,
+    SearchScope,
+    SearchFocused,
+    TogglePlay,
+    Next,
+    Prev,
+    PlayNext,
+    PlayPrev,
+    FirstPage,
+    Forward,
+    Backward,
+    Repeat,
+    Suffle,
+    AutoAdvance,
+    ConsumeQueue,
+    VolIncrease,
+    VolDecrease,
+    Mute,
+    SpeedUp,
+    SpeedDown,
+    SpeedReset,
+    CycleAudio,
+    AudioDevice,
+    SleepTimer,
+    FavouratesAdd,
+    FavouratesRemove,
+    SavePlaylist,
+    View,
+    OpenBrowser,
+    Refresh,
+    ClearQueue,
+    ClearHistory,
+    ClearResults,
+    ToggleMusicbar,
+    TogglePlaylistbar,
+    ToggleArtistbar,
+    FocusSidebar,
+    FocusMusicbar,
+    FocusPlaylistbar,
+    FocusArtistbar,
+    FocusSearchbar,
+    GrowPane,
+    ShrinkPane,
+    SortList,
+    Download,
+    PauseDownloads,
+    PlayNow,
+    Enqueue,
+    PlayAll,
+    ContextMenu,
+    Mark,
+    GotoPlaying,
+    GotoArtist,
+    Radio,
+    ExportList,
+    ImportList,
+    Lyrics,
+    RestoreQueue,
+    ShowQueue,
+    Filter,
+    FuzzyFilter,
+    AddBookmark,
+    Bookmarks,
+    PlayOnSelect,
+    StayOnSource,
+    ToggleIds,
+    SwitchProfile,
+    CacheInfo,
+    Quit,
+    ForceQuit,
+    ListTop,
+    ListBottom,
+    ListDown,
+    ListUp,
+    ListDelete,
+}
+
+impl Action {
+    // Action of given name as written in config. None if no action has that name
+    pub fn named(name: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+
+    // Name of action as written in config. eg: play_next
+    pub fn name(self) -> String {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => name,
+            _ => format!("{:?}", self),
+        }
+    }
+
+    // ShortcutKeys only has the key of each shortcut. Modifier it is pressed with is fixed by
+    // the shortcut itself, same as it always was
+    fn shortcut_combo(self, key: char) -> KeyCombo {
+        match self {
+            Action::Download | Action::Quit => KeyCombo::with_ctrl(key),
+            Action::FocusSidebar
+            | Action::FocusMusicbar
+            | Action::FocusPlaylistbar
+            | Action::FocusArtistbar
+            | Action::FocusSearchbar => KeyCombo::with_alt(key),
+            _ => KeyCombo::plain(key),
+        }
+    }
+}
+
+// Keys bound by action name in KeyBindings of config. These take place of the key of same
+// action in every profile and can be pressed with any modifier. eg: { "play_next": "<CTRL>+l" }.
+// Value is kept as written so that wrong name or key is only warned about and not a hard error
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct KeyBindings(pub BTreeMap<String, String>);
+
+impl KeyBindings {
+    // Every binding that names an action and a valid key
+    fn parsed(&self) -> impl Iterator<Item = (Action, KeyCombo)> + '_ {
+        self.0
+            .iter()
+            .filter_map(|(name, combo)| Some((Action::named(name)?, combo.parse().ok()?)))
+    }
+
+    // Description of every binding that is ignored because of unknown action or invalid key
+    pub fn invalid(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .filter_map(
+                |(name, combo)| match (Action::named(name), combo.parse::<KeyCombo>()) {
+                    (None, _) => Some(format!("Unknown action {:?}", name)),
+                    (Some(_), Err(err)) => Some(format!("Key of {:?}: {}", name, err)),
+                    (Some(_), Ok(_)) => None,
+                },
+            )
+            .collect()
+    }
+}
+
+// Key of every action in a profile. Event handler looks up the pressed key here to know which
+// action to do
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMap(Vec<(Action, KeyCombo)>);
+
+impl KeyMap {
+    pub fn new(keys: &ShortcutsKeys, bindings: &KeyBindings) -> Self {
+        let mut map = keys
+            .bindings()
+            .into_iter()
+            .filter_map(|(name, key)| {
+                let action = Action::named(&name)?;
+                Some((action, action.shortcut_combo(key)))
+            })
+            .collect::<Vec<_>>();
+        map.extend([
+            (Action::PlayNext, KeyCombo::with_ctrl(keys.next)),
+            (Action::PlayPrev, KeyCombo::with_ctrl(keys.prev)),
+            (Action::FirstPage, KeyCombo::with_alt(keys.prev)),
+            (
+                Action::ForceQuit,
+                KeyCombo {
+                    alt: true,
+                    ..KeyCombo::with_ctrl(keys.quit)
+                },
+            ),
+        ]);
+
+        for (action, combo) in bindings.parsed() {
+            match map.iter_mut().find(|(bound, _)| *bound == action) {
+                Some((_, bound_combo)) => *bound_combo = combo,
+                None => map.push((action, combo)),
+            }
+        }
+        map.sort_by_key(|(action, _)| *action);
+        KeyMap(map)
+    }
+
+    // Action bound to given key. Key pressed with modifiers that no action is bound to does the
+    // action of key alone so that eg: <CTRL>+> still seeks. Action earlier in the list wins if
+    // more than one is bound to same key. See `KeyMap::conflicts`
+    pub fn action(&self, combo: KeyCombo) -> Option<Action> {
+        let bound_to = |combo: KeyCombo| {
+            self.0
+                .iter()
+                .find(|(_, bound)| *bound == combo)
+                .map(|(action, _)| *action)
+        };
+        bound_to(combo).or_else(|| bound_to(KeyCombo::plain(combo.key)))
+    }
+
+    pub fn combo(&self, action: Action) -> Option<KeyCombo> {
+        self.0
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, combo)| *combo)
+    }
+
+    // Key of given action as shown to user. eg: <CTRL>+d
+    pub fn describe(&self, action: Action) -> String {
+        self.combo(action)
+            .map(|combo| combo.to_string())
+            .unwrap_or_default()
+    }
+
+    // Every action with its key in the order of `Action`
+    pub fn entries(&self) -> &[(Action, KeyCombo)] {
+        &self.0
+    }
+
+    // Keys that are bound to more than one action along with those actions. Only the first of
+    // them is ever done
+    pub fn conflicts(&self) -> Vec<(KeyCombo, Vec<Action>)> {
+        let mut conflicts: Vec<(KeyCombo, Vec<Action>)> = Vec::new();
+        for (action, combo) in &self.0 {
+            match conflicts.iter_mut().find(|(bound, _)| bound == combo) {
+                Some((_, actions)) => actions.push(*action),
+                None => conflicts.push((*combo, vec![*action])),
+            }
+        }
+        conflicts.retain(|(_, actions)| actions.len() > 1);
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combo_read_back() {
+        let combo = "<ctrl>+<ALT>+c".parse::<KeyCombo>().unwrap();
+        assert_eq!(
+            combo,
+            KeyCombo {
+                key: 'c',
+                ctrl: true,
+                alt: true
+            }
+        );
+        assert_eq!(combo.to_string(), "<CTRL>+<ALT>+c");
+        // Key that is itself same as a prefix is still a key
+        assert_eq!("+".parse::<KeyCombo>(), Ok(KeyCombo::plain('+')));
+        assert!("ab".parse::<KeyCombo>().is_err());
+    }
+
+    #[test]
+    fn every_shortcut_is_an_action() {
+        let keys = ShortcutsKeys::default();
+        for (name, _) in keys.bindings() {
+            assert!(Action::named(&name).is_some(), "{} has no action", name);
+        }
+
+        let map = KeyMap::new(&keys, &KeyBindings::default());
+        assert!(map.conflicts().is_empty());
+        assert_eq!(map.action(KeyCombo::with_ctrl('d')), Some(Action::Download));
+        assert_eq!(map.action(KeyCombo::plain('d')), Some(Action::ListDelete));
+        // Modifier not bound to anything falls back to key alone
+        assert_eq!(map.action(KeyCombo::with_ctrl('>')), Some(Action::Forward));
+    }
+
+    #[test]
+    fn binding_replaces_shortcut() {
+        let bindings = KeyBindings(BTreeMap::from([
+            ("play_next".to_string(), "<CTRL>+l".to_string()),
+            ("nxet".to_string(), "m".to_string()),
+            ("mute".to_string(), "<CTRL>+".to_string()),
+            ("cache_info".to_string(), "n".to_string()),
+        ]));
+        let map = KeyMap::new(&ShortcutsKeys::default(), &bindings);
+        assert_eq!(map.action(KeyCombo::with_ctrl('l')), Some(Action::PlayNext));
+        assert_eq!(map.action(KeyCombo::with_ctrl('n')), Some(Action::Next));
+        assert_eq!(bindings.invalid().len(), 2);
+        assert_eq!(
+            map.conflicts(),
+            vec![(KeyCombo::plain('n'), vec![Action::Next, Action::CacheInfo])]
+        );
+    }
+}
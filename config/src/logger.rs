@@ -0,0 +1,117 @@
+use super::{ConfigContainer, LOG_FILE_NAME};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Environment variable whose level takes place of log_level from config
+const LOG_LEVEL_VAR_KEY: &str = "RUST_LOG";
+// Log file bigger than this is emptied at startup so that it do not keep growing
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+
+// Writes every message to log file. Nothing is ever written to terminal as that would mess the
+// ui drawn there
+struct FileLogger {
+    file: Mutex<std::fs::File>,
+    level: LevelFilter,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let line = format!(
+            "{secs}.{millis:03} {level:<5} {target}: {message}\n",
+            secs = since_epoch.as_secs(),
+            millis = since_epoch.subsec_millis(),
+            level = record.level(),
+            target = record.target(),
+            message = record.args()
+        );
+        if let Ok(mut file) = self.file.lock() {
+            file.write_all(line.as_bytes()).ok();
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            file.flush().ok();
+        }
+    }
+}
+
+// Level to log at. Level set in environment is used if it is valid, then the one from config.
+// Only plain level name is understood in environment, per module filter (eg: fetcher=debug) is
+// not supported and is ignored
+pub fn log_level(from_env: Option<&str>, from_config: &str) -> LevelFilter {
+    from_env
+        .and_then(|level| level.parse().ok())
+        .or_else(|| from_config.parse().ok())
+        .unwrap_or(LevelFilter::Warn)
+}
+
+// Start writing log to LOG_FILE_NAME in config directory and log the version and config in use.
+// Nothing is logged if level is off or log file cannot be opened
+pub fn init(level: &str, version: &str) {
+    let level = log_level(std::env::var(LOG_LEVEL_VAR_KEY).ok().as_deref(), level);
+    if level == LevelFilter::Off {
+        return;
+    }
+    let log_path = match ConfigContainer::get_config_dir() {
+        Some(config_dir) => config_dir.join(LOG_FILE_NAME),
+        None => return,
+    };
+    let too_big = std::fs::metadata(&log_path).is_ok_and(|meta| meta.len() > MAX_LOG_SIZE);
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(!too_big)
+        .write(true)
+        .truncate(too_big)
+        .open(&log_path);
+    let file = match file {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!(
+                "Cannot open log file {path:?}. Error: {err}",
+                path = log_path,
+                err = err
+            );
+            return;
+        }
+    };
+
+    let logger = FileLogger {
+        file: Mutex::new(file),
+        level,
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_err() {
+        return;
+    }
+    log::set_max_level(level);
+    log::info!(
+        "ytui-music {version} started. Config: {config:?}",
+        version = version,
+        config = ConfigContainer::get_config_path().unwrap_or_default()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_level_comes_first() {
+        assert_eq!(log_level(Some("debug"), "warn"), LevelFilter::Debug);
+        assert_eq!(log_level(Some("fetcher=debug"), "info"), LevelFilter::Info);
+        assert_eq!(log_level(None, "OFF"), LevelFilter::Off);
+        assert_eq!(log_level(None, "loud"), LevelFilter::Warn);
+    }
+}
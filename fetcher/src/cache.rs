@@ -0,0 +1,278 @@
+use config::initilize::{STORAGE, TB_RESPONSES};
+use rusqlite::Connection;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Path, body and unix time in seconds at which the response was cached
+type Entry = (String, String, u64);
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+// Stores the raw response body of recent requests so that revisiting same query/page do not
+// have to send web request again. Entries are keyed by request path (without server url) so the
+// response is reused no matter which server has served it.
+// Only `limit` entries are kept. When full, the entry that was used least recently is removed.
+// Entry older than `ttl` seconds is taken as stale and is fetched again.
+// If `on_disk` is set, entries are also written to storage so that they outlive the app. Entry
+// that is not in memory is then looked for in storage. Storage also removes the entry used least
+// recently once it holds more than `limit` entries, and use of entry is recorded there too
+pub struct ResponseCache {
+    // Least recently used at front and most recently used at back.
+    // Number of entries are small (default limit is 50) so linear search is good enough here
+    entries: VecDeque<Entry>,
+    // Maximum number of entries to keep. 0 means caching is disabled
+    limit: usize,
+    // Seconds for which entry stays fresh. 0 means entry is kept until evicted
+    ttl: u64,
+    // Storage entries are written to. None if they are only kept in memory
+    storage: Option<&'static Mutex<Connection>>,
+}
+
+impl ResponseCache {
+    pub fn new(limit: usize, ttl: u64, on_disk: bool) -> Self {
+        Self::with_storage(limit, ttl, on_disk.then(|| &*STORAGE))
+    }
+
+    fn with_storage(limit: usize, ttl: u64, storage: Option<&'static Mutex<Connection>>) -> Self {
+        ResponseCache {
+            entries: VecDeque::with_capacity(limit),
+            limit,
+            ttl,
+            storage,
+        }
+    }
+
+    // Return the cached response for path and mark it as most recently used
+    pub fn get(&mut self, path: &str) -> Option<&str> {
+        self.get_at(path, now())
+    }
+
+    fn get_at(&mut self, path: &str, now: u64) -> Option<&str> {
+        let entry = match self.entries.iter().position(|(key, ..)| key == path) {
+            Some(index) => self.entries.remove(index)?,
+            None => self.read_disk(path)?,
+        };
+        if self.ttl != 0 && now.saturating_sub(entry.2) >= self.ttl {
+            self.remove(path);
+            return None;
+        }
+        self.touch_disk(path, now);
+        self.push(entry);
+        self.entries.back().map(|(_, body, _)| body.as_str())
+    }
+
+    pub fn insert(&mut self, path: String, body: String) {
+        self.insert_at(path, body, now());
+    }
+
+    fn insert_at(&mut self, path: String, body: String, now: u64) {
+        if self.limit == 0 {
+            return;
+        }
+
+        if let Some(index) = self.entries.iter().position(|(key, ..)| *key == path) {
+            self.entries.remove(index);
+        }
+        self.write_disk(&path, &body, now);
+        self.push((path, body, now));
+    }
+
+    fn push(&mut self, entry: Entry) {
+        while self.entries.len() >= self.limit {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    // Evict the response of given path. Returns false if nothing was cached for that path
+    pub fn remove(&mut self, path: &str) -> bool {
+        self.remove_disk(Some(path));
+        match self.entries.iter().position(|(key, ..)| key == path) {
+            Some(index) => self.entries.remove(index).is_some(),
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.remove_disk(None);
+        self.entries.clear();
+    }
+
+    fn read_disk(&self, path: &str) -> Option<Entry> {
+        let query = format!(
+            "SELECT body, cached_at FROM {tb_name} WHERE path = :path",
+            tb_name = TB_RESPONSES
+        );
+        let args = [(":path", &path)];
+        self.storage?
+            .lock()
+            .unwrap()
+            .query_row(&query, &args, |row| {
+                Ok((path.to_string(), row.get(0)?, row.get::<_, i64>(1)? as u64))
+            })
+            .ok()
+    }
+
+    // Storage holds as many entries as memory does. Entries beyond the limit are the ones used
+    // least recently, same as in memory, and are removed once new one is written
+    fn write_disk(&self, path: &str, body: &str, now: u64) {
+        let storage = match self.storage {
+            Some(storage) => storage,
+            None => return,
+        };
+        let query = format!(
+            "
+            INSERT OR REPLACE INTO {tb_name}
+            (path, body, cached_at, used_at)
+            VALUES (:path, :body, :cached_at, :cached_at);
+        ",
+            tb_name = TB_RESPONSES
+        );
+        let cached_at = now.to_string();
+        let args = [
+            (":path", &path),
+            (":body", &body),
+            (":cached_at", &cached_at.as_str()),
+        ];
+        let prune = format!(
+            "
+            DELETE FROM {tb_name}
+            WHERE path NOT IN (SELECT path FROM {tb_name} ORDER BY used_at DESC LIMIT {limit})
+        ",
+            tb_name = TB_RESPONSES,
+            limit = self.limit
+        );
+
+        let storage = storage.lock().unwrap();
+        let res = storage
+            .execute(&query, &args)
+            .and_then(|_| storage.execute_batch(&prune));
+        if let Err(err) = res {
+            log::warn!("Cannot write response to cache. Error: {err}", err = err);
+        }
+    }
+
+    // Record that entry of given path was used at given time
+    fn touch_disk(&self, path: &str, now: u64) {
+        let storage = match self.storage {
+            Some(storage) => storage,
+            None => return,
+        };
+        let query = format!(
+            "UPDATE {tb_name} SET used_at = :used_at WHERE path = :path",
+            tb_name = TB_RESPONSES
+        );
+        let used_at = now.to_string();
+        let args = [(":used_at", &used_at.as_str()), (":path", &path)];
+        if let Err(err) = storage.lock().unwrap().execute(&query, &args) {
+            log::warn!("Cannot update response in cache. Error: {err}", err = err);
+        }
+    }
+
+    // Remove the entry of given path from storage, or every entry if path is None
+    fn remove_disk(&self, path: Option<&str>) {
+        let storage = match self.storage {
+            Some(storage) => storage,
+            None => return,
+        };
+        let res = match path {
+            Some(path) => {
+                let query = format!(
+                    "DELETE FROM {tb_name} WHERE path = :path",
+                    tb_name = TB_RESPONSES
+                );
+                storage.lock().unwrap().execute(&query, &[(":path", &path)])
+            }
+            None => {
+                let query = format!("DELETE FROM {tb_name}", tb_name = TB_RESPONSES);
+                storage.lock().unwrap().execute(&query, [])
+            }
+        };
+        if let Err(err) = res {
+            log::warn!("Cannot remove response from cache. Error: {err}", err = err);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evict_least_recently_used() {
+        let mut cache = ResponseCache::new(2, 0, false);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        // Using "a" makes "b" the least recently used one
+        assert_eq!(cache.get("a"), Some("1"));
+        cache.insert("c".to_string(), "3".to_string());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some("1"));
+        assert_eq!(cache.get("c"), Some("3"));
+    }
+
+    #[test]
+    fn zero_limit_disable_cache() {
+        let mut cache = ResponseCache::new(0, 0, false);
+        cache.insert("a".to_string(), "1".to_string());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn stale_entry_is_evicted() {
+        let mut cache = ResponseCache::new(2, 60, false);
+        cache.insert_at("a".to_string(), "1".to_string(), 100);
+        assert_eq!(cache.get_at("a", 159), Some("1"));
+        assert_eq!(cache.get_at("a", 160), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn disk_keeps_recently_used() {
+        let connection = Connection::open_in_memory().unwrap();
+        let table = format!(
+            "CREATE TABLE {} (path TEXT PRIMARY KEY, body TEXT, cached_at INTEGER, used_at INTEGER)",
+            TB_RESPONSES
+        );
+        connection.execute_batch(&table).unwrap();
+        let storage = Box::leak(Box::new(Mutex::new(connection)));
+
+        let mut cache = ResponseCache::with_storage(2, 0, Some(storage));
+        cache.insert_at("a".to_string(), "1".to_string(), 100);
+        cache.insert_at("b".to_string(), "2".to_string(), 101);
+        // Using "a" makes "b" the least recently used one in storage too
+        assert_eq!(cache.get_at("a", 102), Some("1"));
+        cache.insert_at("c".to_string(), "3".to_string(), 103);
+
+        // Next run starts with nothing in memory and reads what storage has kept
+        let mut cache = ResponseCache::with_storage(2, 0, Some(storage));
+        assert_eq!(cache.get_at("a", 104), Some("1"));
+        assert_eq!(cache.get_at("c", 104), Some("3"));
+        assert_eq!(cache.get_at("b", 104), None);
+
+        cache.remove("a");
+        let mut cache = ResponseCache::with_storage(2, 0, Some(storage));
+        assert_eq!(cache.get_at("a", 105), None);
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{self, Deserialize, Serialize};
+pub mod cache;
+pub mod lyrics;
 pub mod utils;
 use std::time::Duration;
 
@@ -17,6 +19,44 @@ where
     Ok(res)
 }
 
+// Short form of count as shown in list. eg: 1234 => 1.2K, 45_600_000 => 46M
+pub fn short_count(count: u64) -> String {
+    const UNITS: [(u64, &str); 3] = [(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
+    for (size, unit) in UNITS {
+        if count >= size {
+            let value = count as f64 / size as f64;
+            return if value < 10.0 {
+                format!("{:.1}{}", value, unit)
+            } else {
+                format!("{:.0}{}", value, unit)
+            };
+        }
+    }
+    count.to_string()
+}
+
+// Count back from its short form. This is only as accurate as the short form. Text that is not
+// a count is taken as 0
+pub fn count_from_str(text: &str) -> u64 {
+    let (number, size) = match text.chars().last() {
+        Some('K') => (&text[..text.len() - 1], 1_000.0),
+        Some('M') => (&text[..text.len() - 1], 1_000_000.0),
+        Some('B') => (&text[..text.len() - 1], 1_000_000_000.0),
+        _ => (text, 1.0),
+    };
+    number
+        .parse::<f64>()
+        .map_or(0, |number| (number * size) as u64)
+}
+
+fn count_to_str<'de, D>(input: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let count: u64 = Deserialize::deserialize(input)?;
+    Ok(short_count(count))
+}
+
 fn seconds_to_str<'de, D>(input: D) -> Result<String, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -38,6 +78,14 @@ struct FetchPlaylistContentRes {
     videos: Vec<MusicUnit>,
 }
 
+// Same as FetchPlaylistContentRes but for music related to a video. Endpoint /videos/:id returns
+// them as "recommendedVideos": [ { <Fields of MusicUnit> } ]
+#[derive(Deserialize, Clone, PartialEq)]
+struct FetchRelatedRes {
+    #[serde(alias = "recommendedVideos")]
+    videos: Vec<MusicUnit>,
+}
+
 // Serve same purpose as described in struct FetchPlaylistContentRes but
 // to convert to Vec<PlaylistUnit>
 #[derive(Deserialize, Clone, PartialEq)]
@@ -48,11 +96,16 @@ struct FetchArtistPlaylist {
 // Represent the single playable music item.
 #[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
 pub struct MusicUnit {
-    // uniquely identifiable id of the youtube channel that represent the publisher of this unit
-    // This field exist to make it possible to navigate to the artist channel from the song alone
+    // Name of the youtube channel that represent the publisher of this unit
     // server return this field as `author`
     #[serde(alias = "author")]
     pub artist: String,
+    // uniquely identifiable id of the same channel. This field exist to make it possible to
+    // navigate to the artist channel from the song alone. Music read from database or cache
+    // stored before this field may not have it, so it is empty then
+    // server return this field as `authorId`
+    #[serde(alias = "authorId", default)]
+    pub artist_id: String,
     // The name of the music unit itself. This may also contains the unicode or
     // any unprintable character.
     // This field simply serves as the music name to be displayed in list
@@ -64,6 +117,11 @@ pub struct MusicUnit {
     pub duration: String,
     #[serde(alias = "videoId")]
     pub id: String,
+    // Number of views in short form. See `short_count`. Music read from database and playlist
+    // content from some servers do not have it, so it is empty then
+    // server return this field as `viewCount`
+    #[serde(alias = "viewCount", deserialize_with = "count_to_str", default)]
+    pub views: String,
 }
 
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
@@ -130,6 +188,9 @@ pub enum ReturnAction {
     // At this condition the fetch has technicallt suceed and also tells that
     // another retry on same query will return EOR again and again until new fetch is to be made
     EOR,
+    // Server told that requested item (eg: playlist) does not exist. This may happen when saved
+    // item was removed from youtube since. Retrying will not help
+    NotFound,
 }
 
 pub struct Fetcher {
@@ -141,6 +202,8 @@ pub struct Fetcher {
     // This may bring little delay when user explore for first time in a session but after that everything
     // will be in memory making it smooth.
     trending_now: Option<Vec<MusicUnit>>,
+    // Same as trending_now but holds the popular videos shown in youtube community section
+    popular_now: Option<Vec<MusicUnit>>,
 
     //playlist_content stores collection of music contained in a playlist
     // first field: (String) holds the unique if of playlist that is being read.
@@ -191,8 +254,16 @@ pub struct Fetcher {
     // storing the index and hence preventing accidintal out-of-index access
     active_server_index: usize,
 
-    // copy of constants.item_per_list
-    item_per_page: usize,
+    // Response of recently sent request. See cache.rs for detail
+    // Limit, freshness and storage of this cache are read from constants.result_cache_*
+    cache: cache::ResponseCache,
+
+    // Path of every request whose response is kept in the results above, separately for music,
+    // playlist and artist (same order as FILTER_TYPE). See `Fetcher::forget_results`
+    served: [Vec<String>; 3],
+
+    // copy of constants.page_size. Indexed in same order as FILTER_TYPE
+    item_per_page: [usize; 3],
     // reference to constants.region in config file
     region: &'static str,
 }
@@ -0,0 +1,95 @@
+use crate::{Fetcher, ReturnAction};
+use serde::Deserialize;
+
+// Lyrics are fetched from lyrics.ovh. Artist and title are appended to this url as path segments
+const LYRICS_API: &str = "https://api.lyrics.ovh/v1";
+
+#[derive(Deserialize)]
+struct LyricsRes {
+    lyrics: String,
+}
+
+// Guess the artist and title to search lyrics with from youtube title and channel name. Title
+// like "Artist - Title (Official Video)" is split into artist and title. Otherwise channel name
+// without suffix like " - Topic" or "VEVO" is taken as the artist
+pub fn lyrics_query(artist: &str, title: &str) -> (String, String) {
+    // Remove everything inside () and [] as these are mostly about video, not about the song
+    let mut cleaned = String::with_capacity(title.len());
+    let mut depth = 0;
+    for ch in title.chars() {
+        match ch {
+            '(' | '[' => depth += 1,
+            ')' | ']' if depth > 0 => depth -= 1,
+            _ if depth == 0 => cleaned.push(ch),
+            _ => {}
+        }
+    }
+
+    // Removed part may leave extra spaces behind
+    let squeeze = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if let Some((artist, title)) = cleaned.split_once(" - ") {
+        return (squeeze(artist), squeeze(title));
+    }
+    let artist = artist.trim_end_matches(" - Topic").trim_end_matches("VEVO");
+    (squeeze(artist), squeeze(&cleaned))
+}
+
+impl Fetcher {
+    // Lyrics of music of given title from given artist. EOR is returned if there are no lyrics of
+    // the music. Artist and title are first cleaned with `lyrics_query`
+    pub async fn get_lyrics(&mut self, artist: &str, title: &str) -> Result<String, ReturnAction> {
+        let (artist, title) = lyrics_query(artist, title);
+        if artist.is_empty() || title.is_empty() {
+            return Err(ReturnAction::EOR);
+        }
+
+        let mut url = reqwest::Url::parse(LYRICS_API).map_err(|_| ReturnAction::Failed)?;
+        url.path_segments_mut()
+            .map_err(|_| ReturnAction::Failed)?
+            .push(&artist)
+            .push(&title);
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|_| ReturnAction::Failed)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ReturnAction::EOR);
+        }
+        let res = response
+            .json::<LyricsRes>()
+            .await
+            .map_err(|_| ReturnAction::Failed)?;
+
+        let lyrics = res.lyrics.trim();
+        if lyrics.is_empty() {
+            Err(ReturnAction::EOR)
+        } else {
+            Ok(lyrics.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_from_youtube_title() {
+        assert_eq!(
+            lyrics_query("SomeVEVO", "Some Artist - Song Name (Official Video) [HD]"),
+            ("Some Artist".to_string(), "Song Name".to_string())
+        );
+        assert_eq!(
+            lyrics_query("Some Artist - Topic", "Song Name"),
+            ("Some Artist".to_string(), "Song Name".to_string())
+        );
+        assert_eq!(
+            lyrics_query("ArtistVEVO", "Song (feat. Other) Name"),
+            ("Artist".to_string(), "Song Name".to_string())
+        );
+    }
+}
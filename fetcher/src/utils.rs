@@ -1,6 +1,7 @@
 use crate::{Fetcher, ReturnAction};
 use config::initilize::{
-    CONFIG, STORAGE, TB_FAVOURATES_ARTIST, TB_FAVOURATES_MUSIC, TB_FAVOURATES_PLAYLIST,
+    CONFIG, STORAGE, TB_FAVOURATES_ARTIST, TB_FAVOURATES_MUSIC, TB_FAVOURATES_PLAYLIST, TB_HISTORY,
+    TB_RECENTS,
 };
 use reqwest;
 use std::iter::DoubleEndedIterator;
@@ -8,7 +9,7 @@ use std::time::Duration;
 
 const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/92.0.4515.131 Safari/537.36";
 const FIELDS: [&str; 3] = [
-    "videoId,title,author,lengthSeconds",
+    "videoId,title,author,authorId,lengthSeconds,viewCount",
     "title,playlistId,author,videoCount",
     "author,authorId,videoCount",
 ];
@@ -61,22 +62,48 @@ impl crate::ExtendDuration for Duration {
     }
 }
 
+// Send every request of client through proxy_url from config if it is set. Url is already
+// checked while loading config so it is only skipped here if reqwest still refuse it
+pub fn with_proxy(builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+    let url = &CONFIG.constants.proxy_url;
+    if url.is_empty() {
+        return builder;
+    }
+    match reqwest::Proxy::all(url) {
+        Ok(proxy) => builder.proxy(proxy),
+        Err(err) => {
+            log::warn!("Cannot use proxy {url}. Error: {err}", url = url, err = err);
+            builder
+        }
+    }
+}
+
 impl Default for Fetcher {
     fn default() -> Self {
         super::Fetcher {
             trending_now: None,
+            popular_now: None,
             playlist_content: super::PlaylistRes::default(),
             artist_content: super::ArtistRes::default(),
             search_res: super::SearchRes::default(),
             servers: &CONFIG.servers.list,
-            client: reqwest::ClientBuilder::default()
+            client: with_proxy(reqwest::ClientBuilder::default())
                 .user_agent(USER_AGENT)
                 .gzip(true)
+                .timeout(Duration::from_millis(
+                    CONFIG.constants.server_time_out as u64,
+                ))
                 .build()
                 .unwrap(),
             active_server_index: 0,
+            cache: crate::cache::ResponseCache::new(
+                CONFIG.constants.result_cache_limit,
+                CONFIG.constants.result_cache_ttl_secs,
+                CONFIG.constants.result_cache_on_disk,
+            ),
+            served: Default::default(),
             region: &CONFIG.constants.region,
-            item_per_page: CONFIG.constants.item_per_list,
+            item_per_page: CONFIG.constants.page_size,
         }
     }
 }
@@ -125,17 +152,20 @@ macro_rules! search {
             fields = FIELDS[$filter_index],
             page = $page
         );
-        let lower_limit = $page * $fetcher.item_per_page;
-        let mut upper_limit =
-            std::cmp::min($store_target.len(), lower_limit + $fetcher.item_per_page);
+        let lower_limit = $page * $fetcher.item_per_page[$filter_index];
+        let mut upper_limit = std::cmp::min(
+            $store_target.len(),
+            lower_limit + $fetcher.item_per_page[$filter_index],
+        );
 
         let is_new_query = *$query != $fetcher.search_res.query;
         let is_new_type = $fetcher.search_res.last_fetched != $filter_index;
-        let insufficient_data =
-            upper_limit.checked_sub(lower_limit).unwrap_or(0) < $fetcher.item_per_page;
+        let insufficient_data = upper_limit.checked_sub(lower_limit).unwrap_or(0)
+            < $fetcher.item_per_page[$filter_index];
 
         $fetcher.search_res.last_fetched = $filter_index;
         if is_new_query || insufficient_data || is_new_type {
+            $fetcher.serve($filter_index, &suffix);
             let obj = $fetcher.send_request::<Vec<$unit_type>>(&suffix, 1).await;
             if is_new_query || is_new_type {
                 $store_target.clear();
@@ -144,8 +174,10 @@ macro_rules! search {
                 Ok(data) => {
                     $fetcher.search_res.query = $query.to_string();
                     $store_target.extend_from_slice(data.as_slice());
-                    upper_limit =
-                        std::cmp::min($store_target.len(), lower_limit + $fetcher.item_per_page);
+                    upper_limit = std::cmp::min(
+                        $store_target.len(),
+                        lower_limit + $fetcher.item_per_page[$filter_index],
+                    );
                 }
                 Err(e) => return Err(e),
             }
@@ -164,6 +196,48 @@ impl Fetcher {
         self.active_server_index = (self.active_server_index + 1) % self.servers.len();
     }
 
+    // Number of responses currently in cache and maximum number of responses it can hold
+    pub fn cache_size(&self) -> (usize, usize) {
+        (self.cache.len(), self.cache.limit())
+    }
+
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    // Forget the results of music, playlist and artist list (same order as FILTER_TYPE) for which
+    // `lists` is true so that they are fetched again from server. Only responses those results
+    // came from are evicted from cache, the rest of the cache is kept. Favourates and recents are
+    // always read from storage so they are never kept
+    pub fn forget_results(&mut self, lists: [bool; 3]) {
+        for (index, _) in lists.iter().enumerate().filter(|(_, forget)| **forget) {
+            for path in std::mem::take(&mut self.served[index]) {
+                self.cache.remove(&path);
+            }
+        }
+        if lists[0] {
+            self.trending_now = None;
+            self.popular_now = None;
+            self.playlist_content = super::PlaylistRes::default();
+            self.artist_content.music = Default::default();
+            self.search_res.music.clear();
+        }
+        if lists[1] {
+            self.artist_content.playlist = Default::default();
+            self.search_res.playlist.clear();
+        }
+        if lists[2] {
+            self.search_res.artist.clear();
+        }
+    }
+
+    // Remember that response of path is kept in the results of given list
+    fn serve(&mut self, list: usize, path: &str) {
+        if !self.served[list].iter().any(|served| served == path) {
+            self.served[list].push(path.to_string());
+        }
+    }
+
     // All the request should be send from this function
     async fn send_request<'de, Res>(
         &mut self,
@@ -173,14 +247,32 @@ impl Fetcher {
     where
         Res: serde::de::DeserializeOwned,
     {
+        // Serve from cache if same request was made recently. Only successfully parsed responses
+        // are ever cached so parse failure here is not expected but in that case simply evict it
+        // and send the request again
+        if let Some(body) = self.cache.get(path) {
+            if let Ok(obj) = serde_json::from_str::<Res>(body) {
+                return Ok(obj);
+            }
+            self.cache.remove(path);
+        }
+
         self.change_server();
 
         let url = self.servers[self.active_server_index].to_string() + path;
         let res = self.client.get(url).send().await;
 
         match res {
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+                Err(ReturnAction::NotFound)
+            }
             Ok(response) => {
-                if let Ok(obj) = response.json::<Res>().await {
+                let body = match response.text().await {
+                    Ok(body) => body,
+                    Err(_) => return Err(ReturnAction::Failed),
+                };
+                if let Ok(obj) = serde_json::from_str::<Res>(&body) {
+                    self.cache.insert(path.to_string(), body);
                     Ok(obj)
                 } else {
                     Err(ReturnAction::Failed)
@@ -198,7 +290,7 @@ impl Fetcher {
         &mut self,
         page: usize,
     ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
-        let lower_limit = self.item_per_page * page;
+        let lower_limit = self.item_per_page[0] * page;
 
         if self.trending_now.is_none() {
             let suffix = format!(
@@ -207,6 +299,7 @@ impl Fetcher {
                 music_field = FIELDS[0]
             );
 
+            self.serve(0, &suffix);
             let obj = self.send_request::<Vec<super::MusicUnit>>(&suffix, 2).await;
             match obj {
                 Ok(mut res) => {
@@ -218,7 +311,7 @@ impl Fetcher {
         }
 
         let trending_now = self.trending_now.as_ref().unwrap();
-        let upper_limit = std::cmp::min(trending_now.len(), lower_limit + self.item_per_page);
+        let upper_limit = std::cmp::min(trending_now.len(), lower_limit + self.item_per_page[0]);
 
         if lower_limit >= upper_limit {
             Err(ReturnAction::EOR)
@@ -227,28 +320,60 @@ impl Fetcher {
         }
     }
 
+    // Same as get_trending_music but for videos that are popular in server. All of the popular
+    // videos are returned at once so this is also fetched only once per session
+    pub async fn get_popular_music(
+        &mut self,
+        page: usize,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let lower_limit = self.item_per_page[0] * page;
+
+        if self.popular_now.is_none() {
+            let suffix = format!("/popular?fields={music_field}", music_field = FIELDS[0]);
+
+            self.serve(0, &suffix);
+            let mut res = self
+                .send_request::<Vec<super::MusicUnit>>(&suffix, 2)
+                .await?;
+            res.shrink_to_fit();
+            self.popular_now = Some(res);
+        }
+
+        let popular_now = self.popular_now.as_ref().unwrap();
+        let upper_limit = std::cmp::min(popular_now.len(), lower_limit + self.item_per_page[0]);
+
+        if lower_limit >= upper_limit {
+            Err(ReturnAction::EOR)
+        } else {
+            Ok(popular_now[lower_limit..upper_limit].to_vec())
+        }
+    }
+
     pub async fn get_playlist_content(
         &mut self,
         playlist_id: &str,
         page: usize,
     ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
-        let lower_limit = page * self.item_per_page;
+        let lower_limit = page * self.item_per_page[0];
 
         let is_new_id = *playlist_id != self.playlist_content.id;
         if is_new_id {
-            self.playlist_content.id = playlist_id.to_string();
             let suffix = format!(
                 "/playlists/{playlist_id}?fields=videos({music_field})",
                 playlist_id = playlist_id,
                 music_field = FIELDS[0]
             );
 
+            self.serve(0, &suffix);
             let obj = self
                 .send_request::<super::FetchPlaylistContentRes>(&suffix, 1)
                 .await;
             match obj {
+                // Id is only kept once its content is fetched so that failed playlist is not
+                // served with content of the one before
                 Ok(mut data) => {
                     data.videos.shrink_to_fit();
+                    self.playlist_content.id = playlist_id.to_string();
                     self.playlist_content.music = data.videos;
                 }
                 Err(e) => return Err(e),
@@ -257,7 +382,7 @@ impl Fetcher {
 
         let upper_limit = std::cmp::min(
             self.playlist_content.music.len(),
-            lower_limit + self.item_per_page,
+            lower_limit + self.item_per_page[0],
         );
         if lower_limit >= upper_limit {
             Err(ReturnAction::EOR)
@@ -273,7 +398,7 @@ impl Fetcher {
         channel_id: &str,
         page: usize,
     ) -> Result<Vec<super::PlaylistUnit>, ReturnAction> {
-        let lower_limit = page * self.item_per_page;
+        let lower_limit = page * self.item_per_page[1];
 
         let is_new_id = *channel_id != self.artist_content.playlist.0;
         if is_new_id || self.artist_content.playlist.1.is_empty() {
@@ -284,6 +409,7 @@ impl Fetcher {
                 channel_fields = FIELDS[1],
             );
 
+            self.serve(1, &suffix);
             let obj = self
                 .send_request::<super::FetchArtistPlaylist>(&suffix, 1)
                 .await;
@@ -298,7 +424,7 @@ impl Fetcher {
 
         let upper_limit = std::cmp::min(
             self.artist_content.playlist.1.len(),
-            lower_limit + self.item_per_page,
+            lower_limit + self.item_per_page[1],
         );
         if lower_limit >= upper_limit {
             Err(ReturnAction::EOR)
@@ -314,22 +440,26 @@ impl Fetcher {
         channel_id: &str,
         page: usize,
     ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
-        let lower_limit = page * self.item_per_page;
+        let lower_limit = page * self.item_per_page[0];
 
         let is_new_id = *channel_id != self.artist_content.music.0;
         if is_new_id || self.artist_content.music.1.is_empty() {
             self.artist_content.music.0 = channel_id.to_string();
             let suffix = format!(
-                "/channels/{channel_id}/videos&fields={music_field}",
+                "/channels/{channel_id}/videos?fields=videos({music_field})",
                 channel_id = channel_id,
                 music_field = FIELDS[0]
             );
 
-            let obj = self.send_request::<Vec<super::MusicUnit>>(&suffix, 1).await;
+            self.serve(0, &suffix);
+            // Videos of channel are wrapped in object same as content of playlist
+            let obj = self
+                .send_request::<super::FetchPlaylistContentRes>(&suffix, 1)
+                .await;
             match obj {
                 Ok(mut data) => {
-                    data.shrink_to_fit();
-                    self.artist_content.music.1 = data;
+                    data.videos.shrink_to_fit();
+                    self.artist_content.music.1 = data.videos;
                 }
                 Err(e) => return Err(e),
             }
@@ -337,7 +467,7 @@ impl Fetcher {
 
         let upper_limit = std::cmp::min(
             self.artist_content.music.1.len(),
-            lower_limit + self.item_per_page,
+            lower_limit + self.item_per_page[0],
         );
         if lower_limit >= upper_limit {
             Err(ReturnAction::EOR)
@@ -352,7 +482,7 @@ impl Fetcher {
         &mut self,
         page: usize,
     ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
-        let lower_limit = page * self.item_per_page;
+        let lower_limit = page * self.item_per_page[0];
         let conn = STORAGE.lock().unwrap();
 
         let query = format!(
@@ -364,13 +494,13 @@ impl Fetcher {
         ",
             tb_name = TB_FAVOURATES_MUSIC,
             from = lower_limit,
-            count = self.item_per_page,
+            count = self.item_per_page[0],
         );
 
         let mut stmt = match conn.prepare(&query) {
             Ok(val) => val,
             Err(err) => {
-                eprintln!(
+                log::error!(
                     "Error preparing select statement for favourates music. Error: {err}",
                     err = err
                 );
@@ -383,20 +513,23 @@ impl Fetcher {
                 id: row.get(0).unwrap_or_default(),
                 name: row.get(1).unwrap_or("SQL_ERROR".into()),
                 artist: row.get(2).unwrap_or("SQL_ERROR".into()),
+                artist_id: String::new(),
                 duration: row.get(3).unwrap_or("3:0".into()),
+                views: String::new(),
             })
         });
 
         let res = match results {
             Err(err) => {
-                eprintln!(
+                log::error!(
                     "Cannot get results of favourates music. Error: {err}",
                     err = err
                 );
                 return Err(ReturnAction::Failed);
             }
             Ok(results) => {
-                let mut return_res: Vec<super::MusicUnit> = Vec::with_capacity(self.item_per_page);
+                let mut return_res: Vec<super::MusicUnit> =
+                    Vec::with_capacity(self.item_per_page[0]);
                 for music in results {
                     return_res.push(music.unwrap());
                 }
@@ -412,11 +545,135 @@ impl Fetcher {
         Ok(res)
     }
 
+    // Same as get_favourates_music but list the recently played music with latest one at first
+    pub async fn get_recent_music(
+        &mut self,
+        page: usize,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let lower_limit = page * self.item_per_page[0];
+        let conn = STORAGE.lock().unwrap();
+
+        let query = format!(
+            "
+            SELECT
+            id, title, author, duration
+            FROM {tb_name}
+            ORDER BY played_at DESC
+            LIMIT {from}, {count}
+        ",
+            tb_name = TB_RECENTS,
+            from = lower_limit,
+            count = self.item_per_page[0],
+        );
+
+        let mut stmt = match conn.prepare(&query) {
+            Ok(val) => val,
+            Err(err) => {
+                log::error!(
+                    "Error preparing select statement for recent music. Error: {err}",
+                    err = err
+                );
+                return Err(ReturnAction::Failed);
+            }
+        };
+
+        let results = stmt.query_map([], |row| {
+            Ok(super::MusicUnit {
+                id: row.get(0).unwrap_or_default(),
+                name: row.get(1).unwrap_or("SQL_ERROR".into()),
+                artist: row.get(2).unwrap_or("SQL_ERROR".into()),
+                artist_id: String::new(),
+                duration: row.get(3).unwrap_or("3:0".into()),
+                views: String::new(),
+            })
+        });
+
+        let res = match results {
+            Err(err) => {
+                log::error!(
+                    "Cannot get results of recent music. Error: {err}",
+                    err = err
+                );
+                return Err(ReturnAction::Failed);
+            }
+            Ok(results) => results.filter_map(Result::ok).collect::<Vec<_>>(),
+        };
+
+        if res.is_empty() {
+            return Err(ReturnAction::EOR);
+        }
+
+        Ok(res)
+    }
+
+    // Same as get_favourates_music but list the music played most often with the most played at
+    // first. Every row of play history is one play. Title is taken from the latest play
+    pub async fn get_most_played_music(
+        &mut self,
+        page: usize,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let lower_limit = page * self.item_per_page[0];
+        let conn = STORAGE.lock().unwrap();
+
+        let query = format!(
+            "
+            SELECT
+            id, title, author, duration, COUNT(*) AS plays, MAX(played_at) AS last_played
+            FROM {tb_name}
+            GROUP BY id
+            ORDER BY plays DESC, last_played DESC
+            LIMIT {from}, {count}
+        ",
+            tb_name = TB_HISTORY,
+            from = lower_limit,
+            count = self.item_per_page[0],
+        );
+
+        let mut stmt = match conn.prepare(&query) {
+            Ok(val) => val,
+            Err(err) => {
+                log::error!(
+                    "Error preparing select statement for most played music. Error: {err}",
+                    err = err
+                );
+                return Err(ReturnAction::Failed);
+            }
+        };
+
+        let results = stmt.query_map([], |row| {
+            Ok(super::MusicUnit {
+                id: row.get(0).unwrap_or_default(),
+                name: row.get(1).unwrap_or("SQL_ERROR".into()),
+                artist: row.get(2).unwrap_or("SQL_ERROR".into()),
+                artist_id: String::new(),
+                duration: row.get(3).unwrap_or("3:0".into()),
+                views: String::new(),
+            })
+        });
+
+        let res = match results {
+            Err(err) => {
+                log::error!(
+                    "Cannot get results of most played music. Error: {err}",
+                    err = err
+                );
+                return Err(ReturnAction::Failed);
+            }
+            Ok(results) => results.filter_map(Result::ok).collect::<Vec<_>>(),
+        };
+
+        if res.is_empty() {
+            return Err(ReturnAction::EOR);
+        }
+
+        Ok(res)
+    }
+
     pub async fn get_favourates_playlist(
         &mut self,
         page: usize,
     ) -> Result<Vec<super::PlaylistUnit>, ReturnAction> {
-        let lower_limit = page * self.item_per_page;
+        let lower_limit = page * self.item_per_page[1];
         let conn = STORAGE.lock().unwrap();
 
         let query = format!(
@@ -428,13 +685,13 @@ impl Fetcher {
         ",
             tb_name = TB_FAVOURATES_PLAYLIST,
             from = lower_limit,
-            count = self.item_per_page,
+            count = self.item_per_page[1],
         );
 
         let mut stmt = match conn.prepare(&query) {
             Ok(val) => val,
             Err(err) => {
-                eprintln!(
+                log::error!(
                     "Error preparing select statement for favourates playlist. Error: {err}",
                     err = err
                 );
@@ -453,7 +710,7 @@ impl Fetcher {
 
         let res = match results {
             Err(err) => {
-                eprintln!(
+                log::error!(
                     "Cannot get results of favourates music. Error: {err}",
                     err = err
                 );
@@ -461,7 +718,7 @@ impl Fetcher {
             }
             Ok(results) => {
                 let mut return_res: Vec<super::PlaylistUnit> =
-                    Vec::with_capacity(self.item_per_page);
+                    Vec::with_capacity(self.item_per_page[1]);
                 for playlist in results {
                     return_res.push(playlist.unwrap());
                 }
@@ -481,7 +738,7 @@ impl Fetcher {
         &mut self,
         page: usize,
     ) -> Result<Vec<super::ArtistUnit>, ReturnAction> {
-        let lower_limit = page * self.item_per_page;
+        let lower_limit = page * self.item_per_page[2];
         let conn = STORAGE.lock().unwrap();
 
         let query = format!(
@@ -493,13 +750,13 @@ impl Fetcher {
         ",
             tb_name = TB_FAVOURATES_ARTIST,
             from = lower_limit,
-            count = self.item_per_page,
+            count = self.item_per_page[2],
         );
 
         let mut stmt = match conn.prepare(&query) {
             Ok(val) => val,
             Err(err) => {
-                eprintln!(
+                log::error!(
                     "Error preparing select statement for favourates artist. Error: {err}",
                     err = err
                 );
@@ -517,14 +774,15 @@ impl Fetcher {
 
         let res = match results {
             Err(err) => {
-                eprintln!(
+                log::error!(
                     "Cannot get results of favourates artist. Error: {err}",
                     err = err
                 );
                 return Err(ReturnAction::Failed);
             }
             Ok(results) => {
-                let mut return_res: Vec<super::ArtistUnit> = Vec::with_capacity(self.item_per_page);
+                let mut return_res: Vec<super::ArtistUnit> =
+                    Vec::with_capacity(self.item_per_page[2]);
                 for artist in results {
                     return_res.push(artist.unwrap());
                 }
@@ -540,6 +798,27 @@ impl Fetcher {
         Ok(res)
     }
 
+    // Music suggested by server as related to given music. This is not paginated as all of them
+    // are returned at once. Response is cached so asking for the same music again is cheap
+    pub async fn get_related_music(
+        &mut self,
+        music_id: &str,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let suffix = format!(
+            "/videos/{music_id}?fields=recommendedVideos({music_field})",
+            music_id = music_id,
+            music_field = FIELDS[0]
+        );
+        let res = self
+            .send_request::<super::FetchRelatedRes>(&suffix, 1)
+            .await?;
+        if res.videos.is_empty() {
+            Err(ReturnAction::EOR)
+        } else {
+            Ok(res.videos)
+        }
+    }
+
     pub async fn search_music(
         &mut self,
         query: &str,
@@ -8,6 +8,11 @@ pub struct Options {
     exec_name: String,
     sub_command: String,
     arguments: Vec<String>,
+    // Query to search as soon as app is run. See `Options::startup_search`
+    search: Option<String>,
+    play_first: bool,
+    // File of exported music to add to queue as soon as app is run. See `Options::startup_import`
+    import: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -31,15 +36,67 @@ impl Options {
         if let Some(exec_name) = arguments.next() {
             result.exec_name = exec_name;
         }
-        if let Some(cmd) = arguments.next() {
-            result.sub_command = cmd;
+        result.arguments = arguments.collect::<Vec<String>>();
+        if let Some(cmd) = result.arguments.first() {
+            // Options of run can also be given without the sub command itself
+            if cmd.starts_with("--") {
+                result.sub_command = "run".to_string();
+            } else {
+                result.sub_command = result.arguments.remove(0);
+            }
         }
 
-        result.arguments = arguments.collect::<Vec<String>>();
+        if result.sub_command == "run" {
+            result.parse_run_options()?;
+        }
 
         Ok(result)
     }
 
+    // Options that can be given to run sub command:
+    // --search <query>: search the query right after starting
+    // --play-first: also play the first music found. Only valid along with --search
+    // --import <file>: add music of m3u or json file written by export to the queue
+    fn parse_run_options(&mut self) -> Result<(), &'static str> {
+        let mut arguments = self.arguments.iter();
+        while let Some(arg) = arguments.next() {
+            match arg.as_str() {
+                "--search" => match arguments.next() {
+                    Some(query) if !query.trim().is_empty() => {
+                        self.search = Some(query.clone());
+                    }
+                    _ => return Err("--search requires a non-empty query"),
+                },
+                "--play-first" => self.play_first = true,
+                "--import" => match arguments.next() {
+                    Some(path) if !path.trim().is_empty() => {
+                        self.import = Some(path.clone());
+                    }
+                    _ => return Err("--import requires path of file"),
+                },
+                _ => return Err("Unknown option for run sub command"),
+            }
+        }
+
+        if self.play_first && self.search.is_none() {
+            return Err("--play-first can only be used along with --search");
+        }
+        Ok(())
+    }
+
+    // Query to search on start and whether to play first music found. See
+    // `ui::State::search_on_start`
+    pub fn startup_search(&self) -> Option<(String, bool)> {
+        self.search
+            .as_ref()
+            .map(|query| (query.clone(), self.play_first))
+    }
+
+    // File to import to queue on start. See `ui::State::import_queue`
+    pub fn startup_import(&self) -> Option<String> {
+        self.import.clone()
+    }
+
     pub fn evaluate(self) -> bool {
         let ascii_art = r##"
 __   ___         _                           _
@@ -70,6 +127,11 @@ __   ___         _                           _
                 _ => self.show_help(),
             },
 
+            "export" => match (self.arguments.first(), self.arguments.get(1)) {
+                (Some(arg), Some(path)) if arg == "stats" => self.export_stats(path),
+                _ => self.show_help(),
+            },
+
             "info" => match &self.arguments.first() {
                 Some(arg) => match arg.as_str() {
                     "version" => self.show_version(),
@@ -117,19 +179,86 @@ __   ___         _                           _
             rep = keys.repeat,
             f_add = keys.favourates_add,
             f_rm = keys.favourates_remove,
+            s_pl = keys.save_playlist,
             srch = keys.start_search,
+            e_srch = keys.edit_search,
+            scope = keys.search_scope,
+            s_focus = keys.search_focused,
             view = keys.view,
             bkwd = keys.backward,
             frwd = keys.forward,
             down = keys.download,
+            p_down = keys.pause_downloads,
+            enq = keys.enqueue,
+            p_all = keys.play_all,
+            menu = keys.context_menu,
+            r_enq = keys.restore_queue,
+            s_enq = keys.show_queue,
+            top = keys.list_top,
+            bottom = keys.list_bottom,
+            l_down = keys.list_down,
+            l_up = keys.list_up,
+            l_del = keys.list_delete,
+            filt = keys.filter,
+            fuzz = keys.fuzzy_filter,
+            cache = keys.cache_info,
+            a_mark = keys.add_bookmark,
+            marks = keys.bookmarks,
+            p_sel = keys.play_on_select,
+            stay = keys.stay_on_source,
+            audio = keys.cycle_audio,
+            device = keys.audio_device,
+            sleep = keys.sleep_timer,
+            mark = keys.mark,
+            g_play = keys.goto_playing,
+            g_artist = keys.goto_artist,
+            p_now = keys.play_now,
+            browser = keys.open_browser,
+            refresh = keys.refresh,
+            c_queue = keys.clear_queue,
+            c_history = keys.clear_history,
+            c_results = keys.clear_results,
+            t_music = keys.toggle_musicbar,
+            t_playlist = keys.toggle_playlistbar,
+            t_artist = keys.toggle_artistbar,
+            f_side = keys.focus_sidebar,
+            f_music = keys.focus_musicbar,
+            f_playlist = keys.focus_playlistbar,
+            f_artist = keys.focus_artistbar,
+            f_search = keys.focus_searchbar,
+            grow = keys.grow_pane,
+            shrink = keys.shrink_pane,
+            sort = keys.sort_list,
+            radio = keys.radio,
+            export = keys.export_list,
+            import = keys.import_list,
+            lyrics = keys.lyrics,
+            help = keys.help,
             quit = keys.quit,
             v_inc = keys.vol_increase,
             v_dec = keys.vol_decrease,
+            mute = keys.mute,
+            s_up = keys.speed_up,
+            s_down = keys.speed_down,
+            s_reset = keys.speed_reset,
+            auto = keys.auto_advance,
+            consume = keys.consume_queue,
+            prof = keys.switch_profile,
+            ids = keys.toggle_ids,
         );
     }
 
+    pub fn export_stats(&self, path: &str) {
+        self.initialize_globals();
+        match crate::stats::export(std::path::Path::new(path)) {
+            Ok(0) => println!("No music played yet. Empty stats is written to {}", path),
+            Ok(count) => println!("Stats of {} plays is written to {}", count, path),
+            Err(err) => eprintln!("Cannot export stats. Error: {err}", err = err),
+        }
+    }
+
     pub fn show_help(self) {
-        println!(include_str!("help_message.txt"));
+        show_usage();
     }
 
     pub fn initialize_globals(&self) {
@@ -226,7 +355,7 @@ __   ___         _                           _
             header::HeaderValue::from_static("ytui-music/update-request"),
         );
 
-        let client_json = reqwest::ClientBuilder::new()
+        let client_json = fetcher::utils::with_proxy(reqwest::ClientBuilder::new())
             .default_headers(headers.clone())
             .build()
             .expect("Cannot build json accepting reqwest client.");
@@ -267,7 +396,7 @@ __   ___         _                           _
             header::HeaderValue::from_bytes(asset.content_type.as_bytes())
                 .expect("Invalid content type recived from github api"),
         );
-        let client_octet = reqwest::ClientBuilder::new()
+        let client_octet = fetcher::utils::with_proxy(reqwest::ClientBuilder::new())
             .default_headers(headers)
             .build()
             .expect("Cannot build octet accepting reqwest client.");
@@ -300,3 +429,7 @@ __   ___         _                           _
         eprintln!("You may try to manually delete storage.db3 file under ytui_music directory in config directory");
     }
 }
+
+pub fn show_usage() {
+    println!(include_str!("help_message.txt"));
+}
@@ -1,26 +1,97 @@
 use crate::ui::{
     self,
     event::{MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX, MIDDLE_PLAYLIST_INDEX},
+    LockRecover,
 };
+use config::initilize::CONFIG;
 use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+// Evaluate the fetch expression again when it fails, at most fetch_retries times. Before every
+// retry it waits for retry_base_delay milliseconds, doubled after every attempt, so a server that
+// is busy for a moment is not flooded. Attempt being made is shown in statusbox. EOR is never
+// retried as there is nothing more to fetch.
+// Response of last attempt is returned and Retry is taken as failure at that point
+macro_rules! fetch_with_retry {
+    ($state_original: expr, $notifier: expr, $fetch: expr) => {{
+        let retries = CONFIG.constants.fetch_retries;
+        let mut attempt = 0;
+        loop {
+            let response = $fetch;
+            let failed = matches!(
+                response,
+                Err(fetcher::ReturnAction::Failed | fetcher::ReturnAction::Retry)
+            );
+            let done = {
+                let mut state = $state_original.lock_recover();
+                // Do not keep retrying when user is quitting
+                let done = !failed || attempt >= retries || state.active == ui::Window::None;
+                // Attempts are counted from 1 including the first one that failed
+                state.retrying = (!done).then(|| (attempt + 2, retries + 1));
+                done
+            };
+            if done {
+                break match response {
+                    Err(fetcher::ReturnAction::Retry) => Err(fetcher::ReturnAction::Failed),
+                    response => response,
+                };
+            }
+            attempt += 1;
+            $notifier.notify_all();
+            tokio::time::sleep(retry_delay(CONFIG.constants.retry_base_delay, attempt)).await;
+        }
+    }};
+}
 
 macro_rules! handle_response {
-    ($response: expr, $state_original: expr, $win_index: expr, $target: ident) => {{
-        let mut state = $state_original.lock().unwrap();
+    ($response: expr, $state_original: expr, $win_index: expr, $target: ident, $started: expr) => {{
+        let mut state = $state_original.lock_recover();
+        state.list_dirty[$win_index] = true;
         // return the boolean which is only truw when response is RETRY
         let mut need_retry = false;
+        // Connection may only be slow for a moment. So failure in this period is not shown and
+        // status is left as fetching
+        let in_grace_period = $started.elapsed()
+            < Duration::from_millis(CONFIG.constants.fetch_grace_period);
         match $response {
             Ok(mut data) => {
+                log::debug!("Fetched {} items for {}", data.len(), stringify!($target));
                 state.status = "Success..";
+                state.fetch_failed[$win_index] = false;
+                state.last_page[$win_index] = data.len() < CONFIG.constants.page_size[$win_index];
                 data.shrink_to_fit();
+                // Selection may still be of content shown before the fetch
+                let selection = if data.is_empty() { None } else { Some(0) };
+                state.$target.1.select(selection);
                 state.$target.0 = data;
             }
             Err(e) => {
                 match e {
+                    fetcher::ReturnAction::Failed | fetcher::ReturnAction::Retry
+                        if in_grace_period =>
+                    {
+                        log::debug!("Fetch for {} failed. Retrying..", stringify!($target));
+                        need_retry = true;
+                        // Nothing else may wake the communicator when player is idle
+                        state.dirty = true;
+                    }
+                    // Whatever was shown before the fetch is left in the list
                     fetcher::ReturnAction::Failed => {
+                        log::warn!("Fetch for {} failed", stringify!($target));
                         state.status = "Fetch error..";
+                        let what = stringify!($target).trim_end_matches("bar");
+                        state.show_toast(ui::ToastLevel::Error, format!("Cannot fetch {}", what));
+                        state.fetch_failed[$win_index] = true;
+                    }
+                    // Item is gone so whatever was shown before is left in the list as well
+                    fetcher::ReturnAction::NotFound => {
+                        log::warn!("Nothing found for {}", stringify!($target));
+                        state.status = "Not found..";
+                        state.show_toast(ui::ToastLevel::Warning, "Not found".to_string());
+                        state.fetch_failed[$win_index] = true;
                     }
                     fetcher::ReturnAction::EOR => {
+                        log::debug!("Reached end of result for {}", stringify!($target));
                         state.status = "Result end..";
                         // TODO: Setting this to None means that the next page will always be 0.
                         // That being said when user tries to navigate to previous page after seeing
@@ -44,17 +115,42 @@ macro_rules! handle_response {
                     fetcher::ReturnAction::Retry => {
                         // the respective function from which the data is exptracted
                         // specify the no of times to retry. Simple rerun the loop if retry is feasible
+                        log::debug!("Retrying fetch for {}", stringify!($target));
                         state.status = "Retrying..";
                         need_retry = true;
+                        // Nothing else may wake the communicator when player is idle
+                        state.dirty = true;
                     }
                 }
             }
         }
+        // Fetch that is retried keeps the spinner going
+        if !need_retry {
+            state.stop_fetching($win_index);
+        }
         std::mem::drop(state);
         need_retry
     }};
 }
 
+// Time to wait before given retry (starting from 1) of failed fetch when first retry waits for
+// base milliseconds. See fetch_with_retry!
+fn retry_delay(base: u64, retry: u32) -> Duration {
+    Duration::from_millis(base.saturating_mul(1 << retry.saturating_sub(1).min(16)))
+}
+
+// Returns true if the list filled from `source` should be fetched again.
+// See the documentation inside communicator loop for when this is the case
+fn should_fetch<Source: PartialEq>(
+    source: &Source,
+    prev_source: &Source,
+    need_retry: bool,
+    page: &Option<usize>,
+    prev_page: &Option<usize>,
+) -> bool {
+    source != prev_source || need_retry || (page != prev_page && page.is_some())
+}
+
 pub async fn communicator<'st, 'nt>(
     state_original: &'st mut Arc<Mutex<ui::State<'_>>>,
     notifier: &'nt mut Arc<Condvar>,
@@ -67,7 +163,7 @@ pub async fn communicator<'st, 'nt>(
     let (mut prev_musicbar_source, mut prev_playlistbar_source, mut prev_artistbar_source) = {
         // Initilization is done inside seperate scope so that this state variable is not visible
         // anywhere after that. It helps my autocomplete in editor
-        let state = state_original.lock().unwrap();
+        let state = state_original.lock_recover();
         (
             state.filled_source.0.clone(),
             state.filled_source.1.clone(),
@@ -80,11 +176,54 @@ pub async fn communicator<'st, 'nt>(
     // set these booleans to true when request handeling failed with RETREY response. if this is
     // true then other condition should not have to be true
     let mut need_retry = [false; 3];
+    // Time when fetch of current source and page was first started. This is not updated when
+    // the fetch is retried. See `fetch_grace_period` in config
+    let mut fetch_started = [Instant::now(); 3];
 
     'communicator_loop: loop {
-        let mut state = notifier.wait(state_original.lock().unwrap()).unwrap();
-        if state.active == ui::Window::None {
-            break 'communicator_loop;
+        // State is only kept locked while checking what is to be fetched. It is never held while
+        // web request is made, which may even take indefinite time, so that ui is not blocked
+        let (refresh, keep_selection, lyrics_request) = {
+            let mut state = notifier
+                .wait(state_original.lock_recover())
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if state.active == ui::Window::None {
+                break 'communicator_loop;
+            }
+
+            if state.clear_cache {
+                fetcher.clear_cache();
+                state.clear_cache = false;
+                state.status = "Cache cleared..";
+            }
+
+            // Lists to be refreshed are fetched from server again instead of from memory. Position
+            // of selection in them is kept
+            let refresh = std::mem::take(&mut state.refresh);
+            if refresh.contains(&true) {
+                fetcher.forget_results(refresh);
+            }
+            let keep_selection = [
+                state.musicbar.1.selected(),
+                state.playlistbar.1.selected(),
+                state.artistbar.1.selected(),
+            ];
+            (refresh, keep_selection, state.take_lyrics_request())
+        };
+
+        // Lyrics are only shown in lyrics window. Same as radio none of the lists are changed
+        if let Some(music) = lyrics_request {
+            let lyrics = fetcher.get_lyrics(&music.artist, &music.name).await;
+            state_original.lock_recover().set_lyrics(&music.id, lyrics);
+            notifier.notify_all();
+        }
+
+        // Music for radio is only queued. None of the lists below is changed by it
+        let radio_seed = state_original.lock_recover().take_radio_seed();
+        if let Some(seed) = radio_seed {
+            let related = fetcher.get_related_music(&seed).await;
+            state_original.lock_recover().fill_radio(&seed, related);
+            notifier.notify_all();
         }
 
         // This block is executed when the source of playlist has changed from previous iteration
@@ -100,166 +239,379 @@ pub async fn communicator<'st, 'nt>(
         //    added to ensure that it is requesting at least Some page not nothing. eg: when EOR is
         //    reached fetched_page is set to None and for None there is nothing to fetch. See EOR
         //    condition in handle_response! macro
-        // UGH!! this if statement condition check is too ugly. I hate it
-        if state.filled_source.1 != prev_playlistbar_source
-            || need_retry[MIDDLE_PLAYLIST_INDEX]
-            || (state.fetched_page[MIDDLE_PLAYLIST_INDEX] != prev_playlist_page
-                && state.fetched_page[MIDDLE_PLAYLIST_INDEX].is_some())
-        {
-            // clear the target so that noone gets confused if it the response from previous or
-            // current request
-            state.playlistbar.0.clear();
-            state.status = "Fetch playlist..";
-
-            notifier.notify_one();
+        // Note that this thread is also woken up by notification which have nothing to do with
+        // fetching. eg: when terminal is resized only relayout is needed. As none of the above
+        // condition is met in such case, list is neither cleared nor fetched again
+        let playlist_page = {
+            let mut state = state_original.lock_recover();
+            if should_fetch(
+                &state.filled_source.1,
+                &prev_playlistbar_source,
+                need_retry[MIDDLE_PLAYLIST_INDEX] || refresh[MIDDLE_PLAYLIST_INDEX],
+                &state.fetched_page[MIDDLE_PLAYLIST_INDEX],
+                &prev_playlist_page,
+            ) {
+                // Content of previous source or page is kept until response is received so that
+                // it is still there if fetch fails. See handle_response!
+                state.last_page[MIDDLE_PLAYLIST_INDEX] = false;
+                state.fetch_failed[MIDDLE_PLAYLIST_INDEX] = false;
+                state.list_dirty[MIDDLE_PLAYLIST_INDEX] = true;
+                state.discard_filter(&ui::Window::Playlistbar);
+                state.discard_sort(&ui::Window::Playlistbar);
+                state.clear_marks(&ui::Window::Playlistbar);
+                state.status = "Fetch playlist..";
+                state.start_fetching(MIDDLE_PLAYLIST_INDEX);
+                log::debug!(
+                    "Fetching playlist from {:?} page {:?}",
+                    state.filled_source.1,
+                    state.fetched_page[MIDDLE_PLAYLIST_INDEX]
+                );
 
-            // condition of if made sure that fetched_page[MIDDLE_PLAYLIST_INDEX] is Some vlaue so
-            // unwrapping it is safe.
-            let page = state.fetched_page[MIDDLE_PLAYLIST_INDEX].unwrap();
+                notifier.notify_one();
 
-            // Save this source as previous source for next iteration
-            prev_playlistbar_source = state.filled_source.1.clone();
-            prev_playlist_page = Some(page);
-
-            // early drop the state so ui is not blocked while this thread send web req. See: else
-            // block documentation
-            std::mem::drop(state);
+                // condition of if made sure that fetched_page[MIDDLE_PLAYLIST_INDEX] is Some vlaue so
+                // unwrapping it is safe.
+                let page = state.fetched_page[MIDDLE_PLAYLIST_INDEX].unwrap();
 
+                // Save this source as previous source for next iteration
+                prev_playlistbar_source = state.filled_source.1.clone();
+                prev_playlist_page = Some(page);
+                if !need_retry[MIDDLE_PLAYLIST_INDEX] {
+                    fetch_started[MIDDLE_PLAYLIST_INDEX] = Instant::now();
+                }
+                Some(page)
+            } else {
+                None
+            }
+        };
+        if let Some(page) = playlist_page {
             // This is the variable from which the response from matching source is set and later
             // handled with handle_response! macro
-            let playlist_content;
-
             // At this point state.filled.source.1 and prev_playlistbar_source is same. As state is
             // already dropped we cant match state.filled.source.1 so match this
-            match prev_playlistbar_source {
-                ui::PlaylistbarSource::Search(ref term) => {
-                    playlist_content = fetcher.search_playlist(term, page).await;
-                }
-                ui::PlaylistbarSource::Artist(ref artist_id) => {
-                    playlist_content = fetcher.get_playlist_of_channel(artist_id, page).await;
-                }
-                ui::PlaylistbarSource::Favourates => {
-                    playlist_content = fetcher.get_favourates_playlist(page).await;
-                }
-                ui::PlaylistbarSource::RecentlyPlayed => {
+            let playlist_content = fetch_with_retry!(
+                state_original,
+                notifier,
+                match prev_playlistbar_source {
+                    ui::PlaylistbarSource::Search(ref term) => {
+                        fetcher.search_playlist(term, page).await
+                    }
+                    ui::PlaylistbarSource::Artist(ref artist_id) => {
+                        fetcher.get_playlist_of_channel(artist_id, page).await
+                    }
+                    ui::PlaylistbarSource::Favourates =>
+                        fetcher.get_favourates_playlist(page).await,
                     // TODO
-                    playlist_content = Ok(Vec::new());
+                    ui::PlaylistbarSource::RecentlyPlayed => Ok(Vec::new()),
                 }
-            }
+            );
 
             // if return action is RETRY set so in need_retry so that nex interation will try again
             let retry = handle_response!(
                 playlist_content,
                 state_original,
                 MIDDLE_PLAYLIST_INDEX,
-                playlistbar
+                playlistbar,
+                fetch_started[MIDDLE_PLAYLIST_INDEX]
             );
             need_retry[MIDDLE_PLAYLIST_INDEX] = retry;
-            state_original.lock().unwrap().active = ui::Window::Playlistbar;
+            if let (true, Some(position)) = (
+                refresh[MIDDLE_PLAYLIST_INDEX],
+                keep_selection[MIDDLE_PLAYLIST_INDEX],
+            ) {
+                state_original
+                    .lock_recover()
+                    .reselect(&ui::Window::Playlistbar, position);
+            }
+            state_original
+                .lock_recover()
+                .focus_fetched(ui::Window::Playlistbar);
             notifier.notify_one();
-        } else {
-            // State is always unlocked in above block and dropped in if block. But when if block
-            // condition is not met then the state will never be unlocked so always drop the state.
-            // Note, insetad of dropping state inside and outside if statement one could think to
-            // drop it outside the if/else block so single drop statement will work. But when if
-            // block is executed web request is made and when state is dropped outside if/else
-            // block then the state will remain locked until web request is made which may even
-            // take indefinite time so which in turn keeps the ui blocked state is dropped early
-            // in if block
-            std::mem::drop(state);
         }
 
         // Checks and fills the artistbar.
-        let mut state = state_original.lock().unwrap();
-        if state.filled_source.2 != prev_artistbar_source
-            || need_retry[MIDDLE_ARTIST_INDEX]
-            || (state.fetched_page[MIDDLE_ARTIST_INDEX] != prev_artist_page
-                && state.fetched_page[MIDDLE_ARTIST_INDEX].is_some())
-        {
-            state.artistbar.0.clear();
-            state.status = "Fetch artists..";
-            notifier.notify_one();
-
-            let page = state.fetched_page[MIDDLE_ARTIST_INDEX].unwrap();
-            prev_artistbar_source = state.filled_source.2.clone();
-            prev_artist_page = Some(page);
-            std::mem::drop(state);
+        let artist_page = {
+            let mut state = state_original.lock_recover();
+            if should_fetch(
+                &state.filled_source.2,
+                &prev_artistbar_source,
+                need_retry[MIDDLE_ARTIST_INDEX] || refresh[MIDDLE_ARTIST_INDEX],
+                &state.fetched_page[MIDDLE_ARTIST_INDEX],
+                &prev_artist_page,
+            ) {
+                state.last_page[MIDDLE_ARTIST_INDEX] = false;
+                state.fetch_failed[MIDDLE_ARTIST_INDEX] = false;
+                state.list_dirty[MIDDLE_ARTIST_INDEX] = true;
+                state.discard_filter(&ui::Window::Artistbar);
+                state.discard_sort(&ui::Window::Artistbar);
+                state.clear_marks(&ui::Window::Artistbar);
+                state.status = "Fetch artists..";
+                state.start_fetching(MIDDLE_ARTIST_INDEX);
+                log::debug!(
+                    "Fetching artists from {:?} page {:?}",
+                    state.filled_source.2,
+                    state.fetched_page[MIDDLE_ARTIST_INDEX]
+                );
+                notifier.notify_one();
 
-            let artist_content;
-            match prev_artistbar_source {
-                ui::ArtistbarSource::Search(ref term) => {
-                    artist_content = fetcher.search_artist(term, page).await;
+                let page = state.fetched_page[MIDDLE_ARTIST_INDEX].unwrap();
+                prev_artistbar_source = state.filled_source.2.clone();
+                prev_artist_page = Some(page);
+                if !need_retry[MIDDLE_ARTIST_INDEX] {
+                    fetch_started[MIDDLE_ARTIST_INDEX] = Instant::now();
                 }
-                ui::ArtistbarSource::Favourates => {
-                    artist_content = fetcher.get_favourates_artist(page).await;
-                }
-                ui::ArtistbarSource::RecentlyPlayed => {
+                Some(page)
+            } else {
+                None
+            }
+        };
+        if let Some(page) = artist_page {
+            let artist_content = fetch_with_retry!(
+                state_original,
+                notifier,
+                match prev_artistbar_source {
+                    ui::ArtistbarSource::Search(ref term) =>
+                        fetcher.search_artist(term, page).await,
+                    ui::ArtistbarSource::Favourates => fetcher.get_favourates_artist(page).await,
                     // TODO:
-                    artist_content = Ok(Vec::new());
+                    ui::ArtistbarSource::RecentlyPlayed => Ok(Vec::new()),
                 }
-            }
+            );
+
+            let nothing_followed = page == 0
+                && prev_artistbar_source == ui::ArtistbarSource::Favourates
+                && matches!(artist_content, Err(fetcher::ReturnAction::EOR));
 
             let retry = handle_response!(
                 artist_content,
                 state_original,
                 MIDDLE_ARTIST_INDEX,
-                artistbar
+                artistbar,
+                fetch_started[MIDDLE_ARTIST_INDEX]
             );
             need_retry[MIDDLE_ARTIST_INDEX] = retry;
-            state_original.lock().unwrap().active = ui::Window::Artistbar;
+            if let (true, Some(position)) = (
+                refresh[MIDDLE_ARTIST_INDEX],
+                keep_selection[MIDDLE_ARTIST_INDEX],
+            ) {
+                state_original
+                    .lock_recover()
+                    .reselect(&ui::Window::Artistbar, position);
+            }
+            if nothing_followed {
+                state_original.lock_recover().status = "No followed artists..";
+            }
+            state_original
+                .lock_recover()
+                .focus_fetched(ui::Window::Artistbar);
             notifier.notify_one();
-        } else {
-            std::mem::drop(state);
         }
 
         // Checks and fills the musicbar
-        let mut state = state_original.lock().unwrap();
-        if state.filled_source.0 != prev_musicbar_source
-            || need_retry[MIDDLE_MUSIC_INDEX]
-            || (state.fetched_page[MIDDLE_MUSIC_INDEX] != prev_music_page
-                && state.fetched_page[MIDDLE_MUSIC_INDEX].is_some())
-        {
-            state.musicbar.0.clear();
-            state.status = "Fetch music..";
-            notifier.notify_one();
+        let music_page = {
+            let mut state = state_original.lock_recover();
+            if should_fetch(
+                &state.filled_source.0,
+                &prev_musicbar_source,
+                need_retry[MIDDLE_MUSIC_INDEX] || refresh[MIDDLE_MUSIC_INDEX],
+                &state.fetched_page[MIDDLE_MUSIC_INDEX],
+                &prev_music_page,
+            ) {
+                state.last_page[MIDDLE_MUSIC_INDEX] = false;
+                state.fetch_failed[MIDDLE_MUSIC_INDEX] = false;
+                state.list_dirty[MIDDLE_MUSIC_INDEX] = true;
+                state.discard_filter(&ui::Window::Musicbar);
+                state.discard_sort(&ui::Window::Musicbar);
+                state.clear_marks(&ui::Window::Musicbar);
+                state.status = "Fetch music..";
+                state.start_fetching(MIDDLE_MUSIC_INDEX);
+                log::debug!(
+                    "Fetching music from {:?} page {:?}",
+                    state.filled_source.0,
+                    state.fetched_page[MIDDLE_MUSIC_INDEX]
+                );
+                notifier.notify_one();
 
-            let page = state.fetched_page[MIDDLE_MUSIC_INDEX].unwrap();
-            prev_musicbar_source = state.filled_source.0.clone();
-            prev_music_page = Some(page);
-            std::mem::drop(state);
-            // prev_musicbar_source and current musicbar_source are equal at this point
-            let music_content;
-            match prev_musicbar_source {
-                ui::MusicbarSource::Trending => {
-                    music_content = fetcher.get_trending_music(page).await;
-                }
-                ui::MusicbarSource::Search(ref term) => {
-                    music_content = fetcher.search_music(term, page).await;
-                }
-                ui::MusicbarSource::Playlist(ref playlist_id) => {
-                    music_content = fetcher.get_playlist_content(playlist_id, page).await;
-                }
-                ui::MusicbarSource::Artist(ref artist_id) => {
-                    music_content = fetcher.get_videos_of_channel(artist_id, page).await;
-                }
-                ui::MusicbarSource::Favourates => {
-                    music_content = fetcher.get_favourates_music(page).await;
-                }
-                ui::MusicbarSource::RecentlyPlayed => {
-                    // TODO: handle each variant with accurate function
-                    music_content = Ok(Vec::new());
+                let page = state.fetched_page[MIDDLE_MUSIC_INDEX].unwrap();
+                prev_musicbar_source = state.filled_source.0.clone();
+                prev_music_page = Some(page);
+                if !need_retry[MIDDLE_MUSIC_INDEX] {
+                    fetch_started[MIDDLE_MUSIC_INDEX] = Instant::now();
                 }
+                Some(page)
+            } else {
+                None
             }
+        };
+        if let Some(page) = music_page {
+            // prev_musicbar_source and current musicbar_source are equal at this point
+            let music_content = fetch_with_retry!(
+                state_original,
+                notifier,
+                match prev_musicbar_source {
+                    ui::MusicbarSource::Trending => fetcher.get_trending_music(page).await,
+                    ui::MusicbarSource::Community => fetcher.get_popular_music(page).await,
+                    ui::MusicbarSource::Search(ref term) => fetcher.search_music(term, page).await,
+                    ui::MusicbarSource::Playlist(ref playlist_id) => {
+                        fetcher.get_playlist_content(playlist_id, page).await
+                    }
+                    ui::MusicbarSource::Artist(ref artist_id) => {
+                        fetcher.get_videos_of_channel(artist_id, page).await
+                    }
+                    ui::MusicbarSource::Favourates => fetcher.get_favourates_music(page).await,
+                    ui::MusicbarSource::RecentlyPlayed => fetcher.get_recent_music(page).await,
+                    ui::MusicbarSource::MostPlayed => fetcher.get_most_played_music(page).await,
+                }
+            );
 
-            let retry =
-                handle_response!(music_content, state_original, MIDDLE_MUSIC_INDEX, musicbar);
+            let no_uploads = page == 0
+                && matches!(prev_musicbar_source, ui::MusicbarSource::Artist(_))
+                && matches!(music_content, Err(fetcher::ReturnAction::EOR));
+            // Playlist saved in favourites may have been deleted from youtube since
+            let playlist_gone = matches!(prev_musicbar_source, ui::MusicbarSource::Playlist(_))
+                && matches!(music_content, Err(fetcher::ReturnAction::NotFound));
+
+            let retry = handle_response!(
+                music_content,
+                state_original,
+                MIDDLE_MUSIC_INDEX,
+                musicbar,
+                fetch_started[MIDDLE_MUSIC_INDEX]
+            );
             need_retry[MIDDLE_MUSIC_INDEX] = retry;
-            state_original.lock().unwrap().active = ui::Window::Musicbar;
+            if let (true, Some(position)) = (
+                refresh[MIDDLE_MUSIC_INDEX],
+                keep_selection[MIDDLE_MUSIC_INDEX],
+            ) {
+                state_original
+                    .lock_recover()
+                    .reselect(&ui::Window::Musicbar, position);
+            }
+            if no_uploads {
+                state_original.lock_recover().status = "No uploads..";
+            }
+            if playlist_gone {
+                state_original.lock_recover().show_toast(
+                    ui::ToastLevel::Warning,
+                    "Playlist no longer exists".to_string(),
+                );
+            }
+            {
+                let mut state = state_original.lock_recover();
+                state.focus_fetched(ui::Window::Musicbar);
+                state.play_first_result();
+            }
             notifier.notify_one();
-        } else {
-            // If above if block is not executed state lock should however be released
-            // so that state can be lock again for following if block
-            std::mem::drop(state);
         }
+
+        state_original.lock_recover().cache_size = fetcher.cache_size();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wakeup_without_change_do_not_refetch() {
+        // This is the case after terminal is resized while (or after) the list was fetched.
+        // Source and page are already recorded as previous so nothing should be fetched again
+        let source = ui::MusicbarSource::Search("query".to_string());
+        assert!(!should_fetch(
+            &source,
+            &source.clone(),
+            false,
+            &Some(1),
+            &Some(1)
+        ));
+
+        // A pending retry should survive such wakeup too
+        assert!(should_fetch(
+            &source,
+            &source.clone(),
+            true,
+            &Some(1),
+            &Some(1)
+        ));
+    }
+
+    #[test]
+    fn resize_mid_fetch_leaves_state_alone() {
+        // Terminal is resized while first page of search result is being fetched. Communicator
+        // has already recorded the source and page as previous before making the request
+        let mut state = ui::State::default();
+        state.filled_source.0 = ui::MusicbarSource::Search("query".to_string());
+        state.fetched_page[MIDDLE_MUSIC_INDEX] = Some(0);
+        state.status = "Fetch music..";
+        state.show_toast(ui::ToastLevel::Error, "Fetch error..".to_string());
+        state.start_fetching(MIDDLE_MUSIC_INDEX);
+        let prev_sources = state.filled_source.clone();
+        let prev_pages = state.fetched_page;
+        let (toast, spinning_since) = (state.toast.clone(), state.spinning_since);
+
+        // Resize only wakes up communicator. Nothing is to be fetched on such wakeup so neither
+        // the fetch in progress nor what is shown about it is touched
+        assert!(!should_fetch(
+            &state.filled_source.0,
+            &prev_sources.0,
+            false,
+            &state.fetched_page[MIDDLE_MUSIC_INDEX],
+            &prev_pages[MIDDLE_MUSIC_INDEX]
+        ));
+        assert!(!should_fetch(
+            &state.filled_source.1,
+            &prev_sources.1,
+            false,
+            &state.fetched_page[MIDDLE_PLAYLIST_INDEX],
+            &prev_pages[MIDDLE_PLAYLIST_INDEX]
+        ));
+        assert!(!should_fetch(
+            &state.filled_source.2,
+            &prev_sources.2,
+            false,
+            &state.fetched_page[MIDDLE_ARTIST_INDEX],
+            &prev_pages[MIDDLE_ARTIST_INDEX]
+        ));
+        assert_eq!(state.status, "Fetch music..");
+        assert_eq!(state.toast, toast);
+        assert!(!state.expire_toast());
+        assert_eq!(state.spinning_since, spinning_since);
+    }
+
+    #[test]
+    fn retry_delay_doubles() {
+        assert_eq!(retry_delay(500, 1), Duration::from_millis(500));
+        assert_eq!(retry_delay(500, 2), Duration::from_millis(1000));
+        assert_eq!(retry_delay(500, 3), Duration::from_millis(2000));
+        // Very large retry count do not overflow
+        assert_eq!(retry_delay(u64::MAX, 100), Duration::from_millis(u64::MAX));
+    }
+
+    #[test]
+    fn fetch_on_new_source_or_page() {
+        let source = ui::MusicbarSource::Search("query".to_string());
+        assert!(should_fetch(
+            &source,
+            &ui::MusicbarSource::Trending,
+            false,
+            &Some(0),
+            &Some(0)
+        ));
+        assert!(should_fetch(
+            &source,
+            &source.clone(),
+            false,
+            &Some(2),
+            &Some(1)
+        ));
+
+        // Page is set to None when EOR is reached. There is nothing to fetch for None
+        assert!(!should_fetch(
+            &source,
+            &source.clone(),
+            false,
+            &None,
+            &Some(1)
+        ));
     }
 }
@@ -4,6 +4,7 @@ use std::sync::Mutex;
 use std::thread;
 mod cli;
 mod communicator;
+mod stats;
 mod ui;
 
 /*
@@ -38,17 +39,22 @@ mod ui;
 */
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let startup_search;
+    let startup_import;
     {
         let opts = cli::Options::create_from_args(std::env::args());
         match opts {
             Err(err) => {
                 eprintln!(
-                    "There was an error while prasing cli options.\nError: {err}",
+                    "There was an error while prasing cli options.\nError: {err}\n",
                     err = err
                 );
+                cli::show_usage();
                 std::process::exit(1)
             }
             Ok(opts) => {
+                startup_search = opts.startup_search();
+                startup_import = opts.startup_import();
                 let should_continue = opts.evaluate();
                 if !should_continue {
                     std::process::exit(0)
@@ -57,7 +63,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let state = Arc::new(Mutex::new(ui::State::default()));
+    // Terminal is taken by ui from here on so any message is written to log file instead
+    config::logger::init(
+        &config::initilize::CONFIG.constants.log_level,
+        env!("CARGO_PKG_VERSION"),
+    );
+    let mut initial_state = ui::State::default();
+    initial_state.restore_session();
+    // Search given in command line takes place of lists restored from last session
+    if let Some((query, play_first)) = startup_search {
+        initial_state.search_on_start(&query, play_first);
+    }
+    if let Some(path) = startup_import {
+        initial_state.import_queue(std::path::Path::new(&path));
+    }
+    let state = Arc::new(Mutex::new(initial_state));
     let cvar = Arc::new(Condvar::new());
 
     let (handler, communicate);
@@ -94,7 +114,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 });
         });
 
+        // Media keys and desktop widgets control playback through session bus. See ui/mpris.rs
+        #[cfg(all(feature = "mpris", unix))]
+        let mpris = {
+            let state_for_mpris = Arc::clone(&state);
+            let cvar_for_mpris = Arc::clone(&cvar);
+            thread::spawn(move || ui::mpris::serve(&state_for_mpris, &cvar_for_mpris))
+        };
+
         ui::draw_ui(&mut state_for_painter, &mut cvar_for_painter);
+
+        #[cfg(all(feature = "mpris", unix))]
+        mpris.join().unwrap();
     }
 
     handler.join().unwrap();
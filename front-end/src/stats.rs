@@ -0,0 +1,228 @@
+use config::initilize::{STORAGE, TB_HISTORY};
+use fetcher::ExtendDuration;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Only this much tracks and artists are kept in top list of exported stats
+const TOP_COUNT: usize = 20;
+
+// Single row from play history
+pub struct PlayRecord {
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub duration: String,
+    // Local date in YYYY-MM-DD format when this record was played
+    pub day: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct TrackStat {
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub plays: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CountStat {
+    pub name: String,
+    pub plays: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq, Default)]
+pub struct ListeningStats {
+    pub total_plays: usize,
+    // Sum of duration of everything played. This assumes that every play was listened till end
+    pub total_listening_secs: u64,
+    pub top_tracks: Vec<TrackStat>,
+    pub top_artists: Vec<CountStat>,
+    // Number of plays in each day in ascending order of day
+    pub per_day: Vec<CountStat>,
+}
+
+// Sort by play count with most played at first. Ties are sorted by name so that output is stable
+fn sort_by_plays<T>(items: &mut [T], key: impl Fn(&T) -> (usize, &str)) {
+    items.sort_by(|a, b| {
+        let (a_plays, a_name) = key(a);
+        let (b_plays, b_name) = key(b);
+        b_plays.cmp(&a_plays).then_with(|| a_name.cmp(b_name))
+    });
+}
+
+impl ListeningStats {
+    pub fn from_records(records: &[PlayRecord]) -> Self {
+        let mut tracks: HashMap<&str, TrackStat> = HashMap::new();
+        let mut artists: HashMap<&str, usize> = HashMap::new();
+        let mut days: HashMap<&str, usize> = HashMap::new();
+        let mut total_listening_secs = 0;
+
+        for record in records {
+            tracks
+                .entry(&record.id)
+                .or_insert_with(|| TrackStat {
+                    id: record.id.clone(),
+                    title: record.title.clone(),
+                    author: record.author.clone(),
+                    plays: 0,
+                })
+                .plays += 1;
+            *artists.entry(&record.author).or_default() += 1;
+            *days.entry(&record.day).or_default() += 1;
+            total_listening_secs += Duration::from_string(&record.duration).as_secs();
+        }
+
+        let mut top_tracks = tracks.into_values().collect::<Vec<_>>();
+        sort_by_plays(&mut top_tracks, |track| (track.plays, &track.title));
+        top_tracks.truncate(TOP_COUNT);
+
+        let mut top_artists = artists
+            .into_iter()
+            .map(|(name, plays)| CountStat {
+                name: name.to_string(),
+                plays,
+            })
+            .collect::<Vec<_>>();
+        sort_by_plays(&mut top_artists, |artist| (artist.plays, &artist.name));
+        top_artists.truncate(TOP_COUNT);
+
+        let mut per_day = days
+            .into_iter()
+            .map(|(name, plays)| CountStat {
+                name: name.to_string(),
+                plays,
+            })
+            .collect::<Vec<_>>();
+        per_day.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ListeningStats {
+            total_plays: records.len(),
+            total_listening_secs,
+            top_tracks,
+            top_artists,
+            per_day,
+        }
+    }
+
+    // Every row is in form of `kind,name,author,plays` where kind is one of
+    // total/listening_secs/track/artist/day. Field not applicable to the kind is left empty
+    pub fn to_csv(&self) -> String {
+        fn escape(field: &str) -> String {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        }
+
+        let mut rows = vec![
+            "kind,name,author,plays".to_string(),
+            format!("total,,,{}", self.total_plays),
+            format!("listening_secs,,,{}", self.total_listening_secs),
+        ];
+        for track in self.top_tracks.iter() {
+            rows.push(format!(
+                "track,{},{},{}",
+                escape(&track.title),
+                escape(&track.author),
+                track.plays
+            ));
+        }
+        for artist in self.top_artists.iter() {
+            rows.push(format!("artist,{},,{}", escape(&artist.name), artist.plays));
+        }
+        for day in self.per_day.iter() {
+            rows.push(format!("day,{},,{}", day.name, day.plays));
+        }
+
+        rows.join("\n") + "\n"
+    }
+}
+
+pub fn read_history() -> Result<Vec<PlayRecord>, String> {
+    let query = format!(
+        "
+        SELECT
+        id, title, author, duration, date(played_at, 'unixepoch', 'localtime')
+        FROM {tb_name}
+    ",
+        tb_name = TB_HISTORY
+    );
+    let conn = STORAGE.lock().unwrap();
+    let mut stmt = conn.prepare(&query).map_err(|err| err.to_string())?;
+    let records = stmt
+        .query_map([], |row| {
+            Ok(PlayRecord {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                author: row.get(2)?,
+                duration: row.get(3)?,
+                day: row.get(4)?,
+            })
+        })
+        .map_err(|err| err.to_string())?
+        .filter_map(Result::ok)
+        .collect();
+
+    Ok(records)
+}
+
+// Write the stats computed from play history to given path. Stats are written in csv format if
+// the path ends with .csv and in json format otherwise. Returns the number of plays exported
+pub fn export(path: &std::path::Path) -> Result<usize, String> {
+    let stats = ListeningStats::from_records(&read_history()?);
+
+    let is_csv = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+    let content = if is_csv {
+        stats.to_csv()
+    } else {
+        serde_json::to_string_pretty(&stats).map_err(|err| err.to_string())?
+    };
+
+    std::fs::write(path, content).map_err(|err| err.to_string())?;
+    Ok(stats.total_plays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, author: &str, day: &str) -> PlayRecord {
+        PlayRecord {
+            id: id.to_string(),
+            title: format!("title {}", id),
+            author: author.to_string(),
+            duration: "01:30".to_string(),
+            day: day.to_string(),
+        }
+    }
+
+    #[test]
+    fn aggregate_history() {
+        let records = [
+            record("a", "artist, one", "2021-01-02"),
+            record("b", "artist two", "2021-01-01"),
+            record("a", "artist, one", "2021-01-02"),
+        ];
+        let stats = ListeningStats::from_records(&records);
+
+        assert_eq!(stats.total_plays, 3);
+        assert_eq!(stats.total_listening_secs, 270);
+        assert_eq!(stats.top_tracks[0].id, "a");
+        assert_eq!(stats.top_tracks[0].plays, 2);
+        assert_eq!(stats.top_artists[0].name, "artist, one");
+        assert_eq!(stats.per_day[0].name, "2021-01-01");
+        assert!(stats.to_csv().contains("artist,\"artist, one\",,2"));
+    }
+
+    #[test]
+    fn empty_history() {
+        let stats = ListeningStats::from_records(&[]);
+        assert_eq!(stats, ListeningStats::default());
+        assert_eq!(stats.to_csv().lines().count(), 3);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::ui;
+use config::initilize::{STORAGE, TB_BOOKMARKS};
+use fetcher::ExtendDuration;
+use std::time::Duration;
+
+pub const LABEL_TITLE: &str = "Bookmark";
+pub const JUMP_TITLE: &str = "Bookmarks";
+pub const DELETE_TITLE: &str = "Delete bookmark";
+
+// Only this much bookmarks are listed in popup as each of them is selected with single digit
+const MAX_LISTED: usize = 9;
+
+// Named position inside a track
+pub struct Bookmark {
+    // Position in seconds from the start of track
+    pub position: u64,
+    // Label given by user. This may be empty
+    pub label: String,
+}
+
+// Input which bookmark popup is waiting for
+pub enum Prompt {
+    // Label of bookmark to be saved. Track id and position are taken when user started bookmarking
+    // so they do not change while user is typing the label
+    Label {
+        music_id: String,
+        position: u64,
+        label: String,
+    },
+    // Bookmarks of track with given id are listed and pressing the digit seeks to that bookmark
+    Jump(String, Vec<Bookmark>),
+    // Same as Jump but pressing the digit removes the bookmark
+    Delete(String, Vec<Bookmark>),
+}
+
+// Extract the youtube id from url passed to mpv. mpv also reports the url of playlist entry in
+// different format than what we pass so this handles both watch?v=<id> and youtu.be/<id> form
+pub fn id_from_url(url: &str) -> Option<&str> {
+    let id = if let Some((_, rest)) = url.split_once("v=") {
+        rest.split('&').next()
+    } else if let Some((_, rest)) = url.split_once("youtu.be/") {
+        rest.split(['?', '&']).next()
+    } else {
+        Some(url)
+    }?;
+
+    // All youtube video id are of 11 characters
+    if id.len() == 11 {
+        Some(id)
+    } else {
+        None
+    }
+}
+
+impl ui::State<'_> {
+    // Start bookmarking current position of playing track. Label is asked in popup
+    pub fn start_bookmark(&mut self) {
+        let music_id = match self.playing_id() {
+            Some(id) => id,
+            None => {
+                self.status = "Nothing to bookmark..";
+                return;
+            }
+        };
+        let position = self
+            .player
+            .get_property::<f64>("time-pos")
+            .unwrap_or_default() as u64;
+
+        self.bookmark = Some(Prompt::Label {
+            music_id,
+            position,
+            label: String::new(),
+        });
+        self.show_bookmark_prompt();
+    }
+
+    // Open the list of bookmarks of currently playing track
+    pub fn list_bookmarks(&mut self) {
+        let music_id = match self.playing_id() {
+            Some(id) => id,
+            None => {
+                self.status = "Nothing playing..";
+                return;
+            }
+        };
+
+        match Self::load_bookmarks(&music_id) {
+            Some(bookmarks) if bookmarks.is_empty() => self.status = "No bookmarks..",
+            Some(bookmarks) => {
+                self.bookmark = Some(Prompt::Jump(music_id, bookmarks));
+                self.show_bookmark_prompt();
+            }
+            None => self.status = "Err reading bookmarks..",
+        }
+    }
+
+    // Returns true if bookmark popup is being shown and is waiting for input
+    pub fn is_bookmarking(&self) -> bool {
+        self.bookmark.is_some()
+            && matches!(
+                self.active,
+                ui::Window::Popup(LABEL_TITLE | JUMP_TITLE | DELETE_TITLE, _)
+            )
+    }
+
+    // Handle the character key typed while bookmark popup is shown.
+    // `list_key` is the key used to list bookmark. Pressing it again in the list switch between
+    // jumping to and deleting the bookmark
+    pub fn handle_bookmark_input(&mut self, ch: char, list_key: char) {
+        match self.bookmark.take() {
+            Some(Prompt::Label {
+                music_id,
+                position,
+                mut label,
+            }) => {
+                label.push(ch);
+                self.bookmark = Some(Prompt::Label {
+                    music_id,
+                    position,
+                    label,
+                });
+            }
+            Some(Prompt::Jump(music_id, bookmarks)) if ch == list_key => {
+                self.bookmark = Some(Prompt::Delete(music_id, bookmarks));
+            }
+            Some(Prompt::Delete(music_id, bookmarks)) if ch == list_key => {
+                self.bookmark = Some(Prompt::Jump(music_id, bookmarks));
+            }
+            Some(Prompt::Jump(music_id, bookmarks)) => {
+                match Self::selected_bookmark(&bookmarks, ch) {
+                    Some(bookmark) => {
+                        self.player.seek_absolute(bookmark.position as f64).ok();
+                        self.status = "Jumped..";
+                        self.active = self.active.next();
+                        return;
+                    }
+                    None => self.bookmark = Some(Prompt::Jump(music_id, bookmarks)),
+                }
+            }
+            Some(Prompt::Delete(music_id, mut bookmarks)) => {
+                if let Some(bookmark) = Self::selected_bookmark(&bookmarks, ch) {
+                    let position = bookmark.position;
+                    if Self::delete_bookmark(&music_id, position) {
+                        self.status = "Bookmark removed..";
+                        bookmarks.retain(|bookmark| bookmark.position != position);
+                    } else {
+                        self.status = "Err removing..";
+                    }
+                }
+                if bookmarks.is_empty() {
+                    self.active = self.active.next();
+                    return;
+                }
+                self.bookmark = Some(Prompt::Delete(music_id, bookmarks));
+            }
+            None => return,
+        }
+        self.show_bookmark_prompt();
+    }
+
+    // Remove last character from label being typed
+    pub fn handle_bookmark_backspace(&mut self) {
+        if let Some(Prompt::Label { label, .. }) = &mut self.bookmark {
+            label.pop();
+            self.show_bookmark_prompt();
+        }
+    }
+
+    // Save the bookmark whose label is being typed. Returns false if there was no label prompt
+    pub fn save_bookmark(&mut self) -> bool {
+        let (music_id, position, label) = match self.bookmark.take() {
+            Some(Prompt::Label {
+                music_id,
+                position,
+                label,
+            }) => (music_id, position, label),
+            other => {
+                self.bookmark = other;
+                return false;
+            }
+        };
+
+        let query = format!(
+            "
+            INSERT OR REPLACE INTO {tb_name}
+            (id, position, label)
+            VALUES (:id, :position, :label)
+        ",
+            tb_name = TB_BOOKMARKS
+        );
+        let position = position.to_string();
+        let args = [
+            (":id", &music_id),
+            (":position", &position),
+            (":label", &label),
+        ];
+        if let Err(err) = STORAGE.lock().unwrap().execute(&query, &args) {
+            log::error!("Error saving bookmark. Error: {err}", err = err);
+            self.status = "Err bookmarking..";
+        } else {
+            self.status = "Bookmarked..";
+        }
+        self.active = self.active.next();
+        true
+    }
+
+    // Close the bookmark popup without doing anything
+    pub fn cancel_bookmark(&mut self) {
+        self.bookmark = None;
+    }
+
+    // Youtube id of music loaded in mpv. None if nothing is being played
+    pub fn playing_id(&self) -> Option<String> {
+        self.bottom.playing.as_ref()?;
+        let path = self.player.get_property::<String>("path").ok()?;
+        id_from_url(&path).map(str::to_string)
+    }
+
+    fn selected_bookmark(bookmarks: &[Bookmark], ch: char) -> Option<&Bookmark> {
+        let index = ch.to_digit(10)?.checked_sub(1)? as usize;
+        bookmarks.iter().take(MAX_LISTED).nth(index)
+    }
+
+    fn show_bookmark_prompt(&mut self) {
+        let format_position = |position: u64| Duration::from_secs(position).to_string();
+        let list = |bookmarks: &[Bookmark]| {
+            let mut list = bookmarks
+                .iter()
+                .take(MAX_LISTED)
+                .enumerate()
+                .map(|(index, bookmark)| {
+                    format!(
+                        "{}) {} {}",
+                        index + 1,
+                        format_position(bookmark.position),
+                        bookmark.label
+                    )
+                })
+                .collect::<Vec<_>>();
+            if bookmarks.len() > MAX_LISTED {
+                list.push(format!("... and {} more", bookmarks.len() - MAX_LISTED));
+            }
+            list.join("\n")
+        };
+
+        self.active = match &self.bookmark {
+            Some(Prompt::Label {
+                position, label, ..
+            }) => ui::Window::Popup(
+                LABEL_TITLE,
+                format!(
+                    "Label for {} (optional): {}\nPress <ENTER> to save",
+                    format_position(*position),
+                    label
+                ),
+            ),
+            Some(Prompt::Jump(_, bookmarks)) => ui::Window::Popup(
+                JUMP_TITLE,
+                format!("Press number to jump\n{}", list(bookmarks)),
+            ),
+            Some(Prompt::Delete(_, bookmarks)) => ui::Window::Popup(
+                DELETE_TITLE,
+                format!("Press number to remove\n{}", list(bookmarks)),
+            ),
+            None => return,
+        };
+    }
+
+    fn load_bookmarks(music_id: &str) -> Option<Vec<Bookmark>> {
+        let query = format!(
+            "
+            SELECT
+            position, label
+            FROM {tb_name}
+            WHERE id = :id
+            ORDER BY position
+        ",
+            tb_name = TB_BOOKMARKS
+        );
+        let conn = STORAGE.lock().unwrap();
+        let mut stmt = match conn.prepare(&query) {
+            Ok(stmt) => stmt,
+            Err(err) => {
+                log::error!(
+                    "Error preparing select statement for bookmarks. Error: {err}",
+                    err = err
+                );
+                return None;
+            }
+        };
+
+        let rows = stmt.query_map(&[(":id", music_id)], |row| {
+            Ok(Bookmark {
+                position: row.get::<_, i64>(0)? as u64,
+                label: row.get(1).unwrap_or_default(),
+            })
+        });
+        match rows {
+            Ok(rows) => Some(rows.filter_map(Result::ok).collect()),
+            Err(err) => {
+                log::error!("Cannot get bookmarks. Error: {err}", err = err);
+                None
+            }
+        }
+    }
+
+    fn delete_bookmark(music_id: &str, position: u64) -> bool {
+        let query = format!(
+            "
+            DELETE FROM {tb_name}
+            WHERE id = :id AND position = :position
+        ",
+            tb_name = TB_BOOKMARKS
+        );
+        let position = position.to_string();
+        let args = [(":id", music_id), (":position", position.as_str())];
+        STORAGE.lock().unwrap().execute(&query, &args).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_id_from_mpv_path() {
+        assert_eq!(
+            id_from_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=abc"),
+            Some("dQw4w9WgXcQ")
+        );
+        assert_eq!(
+            id_from_url("https://youtu.be/dQw4w9WgXcQ"),
+            Some("dQw4w9WgXcQ")
+        );
+        assert_eq!(id_from_url("dQw4w9WgXcQ"), Some("dQw4w9WgXcQ"));
+        assert_eq!(id_from_url("https://example.com/audio.mp3"), None);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::ui;
+use std::sync::mpsc;
+
+// Url of youtube page of item with given id shown in given window. Url of music and playlist is
+// same as the one shown by view key
+pub fn item_url(window: &ui::Window, id: &str) -> Option<String> {
+    match window {
+        ui::Window::Musicbar => Some(format!("https://youtu.be/{}", id)),
+        ui::Window::Playlistbar => Some(format!("https://youtu.be/playlist?list={}", id)),
+        ui::Window::Artistbar => Some(format!("https://www.youtube.com/channel/{}", id)),
+        _ => None,
+    }
+}
+
+// Command that opens url in default browser of the platform
+fn open_command(url: &str) -> std::process::Command {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    command
+}
+
+impl ui::State<'_> {
+    // Open youtube page of item selected in active list in browser. Browser is started in another
+    // thread so that ui do not wait for it. Whether it could be started is told afterwards by
+    // `State::report_browser`
+    pub fn open_in_browser(&mut self) {
+        let selected = match self.active {
+            ui::Window::Musicbar => self
+                .musicbar
+                .1
+                .selected()
+                .and_then(|index| self.musicbar.0.get(index))
+                .map(|music| music.id.clone()),
+            ui::Window::Playlistbar => self
+                .playlistbar
+                .1
+                .selected()
+                .and_then(|index| self.playlistbar.0.get(index))
+                .map(|playlist| playlist.id.clone()),
+            ui::Window::Artistbar => self
+                .artistbar
+                .1
+                .selected()
+                .and_then(|index| self.artistbar.0.get(index))
+                .map(|artist| artist.id.clone()),
+            _ => None,
+        };
+        let url = match selected.and_then(|id| item_url(&self.active, &id)) {
+            Some(url) => url,
+            None => {
+                self.status = "Nothing selected..";
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let mut command = open_command(&url);
+        std::thread::spawn(move || {
+            let opened = command.status().is_ok_and(|status| status.success());
+            sender.send(opened).ok();
+        });
+        self.browser = Some(receiver);
+        self.status = "Opening in browser..";
+    }
+
+    // Show in status if browser could not be started. This happens in session without display
+    // or where no browser is installed. Returns true if status was changed
+    pub fn report_browser(&mut self) -> bool {
+        let opened = match &self.browser {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(opened) => opened,
+                Err(mpsc::TryRecvError::Empty) => return false,
+                Err(mpsc::TryRecvError::Disconnected) => false,
+            },
+            None => return false,
+        };
+        self.browser = None;
+        if opened {
+            return false;
+        }
+        self.status = "Cannot open browser..";
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_of_each_list() {
+        assert_eq!(
+            item_url(&ui::Window::Musicbar, "abc").as_deref(),
+            Some("https://youtu.be/abc")
+        );
+        assert_eq!(
+            item_url(&ui::Window::Playlistbar, "PL1").as_deref(),
+            Some("https://youtu.be/playlist?list=PL1")
+        );
+        assert_eq!(
+            item_url(&ui::Window::Artistbar, "UC1").as_deref(),
+            Some("https://www.youtube.com/channel/UC1")
+        );
+        assert_eq!(item_url(&ui::Window::Sidebar, "abc"), None);
+    }
+}
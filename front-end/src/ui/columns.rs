@@ -0,0 +1,55 @@
+// Width of length and views column of musicbar. Length is at most of form 10:00:00 and views of
+// form 999.9K. See `fetcher::short_count`
+const LENGTH_WIDTH: u16 = 8;
+const VIEWS_WIDTH: u16 = 6;
+// Space between two columns of list
+pub const COLUMN_SPACING: u16 = 2;
+// Percent of width left after length and views column that is given to title. Artist gets the
+// rest
+const TITLE_SHARE: u16 = 65;
+
+// Header of musicbar. Title and length are always shown, artist and views only when detailed
+pub fn music_header(detailed: bool) -> Vec<&'static str> {
+    if detailed {
+        vec!["Title", "Artist", "Length", "Views"]
+    } else {
+        vec!["Title", "Length"]
+    }
+}
+
+// Width of each column in music_header for musicbar of given width including the border.
+// Length and views have fixed width so that they stay visible in narrow terminal and title and
+// artist share what is left
+pub fn music_widths(width: u16, detailed: bool) -> Vec<u16> {
+    // 2 for left and right border
+    let inner = width.saturating_sub(2);
+    if !detailed {
+        return vec![
+            inner.saturating_sub(LENGTH_WIDTH + COLUMN_SPACING),
+            LENGTH_WIDTH,
+        ];
+    }
+    let rest = inner.saturating_sub(LENGTH_WIDTH + VIEWS_WIDTH + 3 * COLUMN_SPACING);
+    let title = (u32::from(rest) * u32::from(TITLE_SHARE) / 100) as u16;
+    vec![title, rest - title, LENGTH_WIDTH, VIEWS_WIDTH]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_always_fits() {
+        assert_eq!(
+            music_widths(62, true),
+            vec![26, 14, LENGTH_WIDTH, VIEWS_WIDTH]
+        );
+        assert_eq!(music_widths(62, false), vec![50, LENGTH_WIDTH]);
+        // Title and artist are left out first when there is no space
+        assert_eq!(
+            music_widths(20, true),
+            vec![0, 0, LENGTH_WIDTH, VIEWS_WIDTH]
+        );
+        assert_eq!(music_widths(62, true).len(), music_header(true).len());
+    }
+}
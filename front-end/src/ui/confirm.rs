@@ -0,0 +1,59 @@
+use crate::ui;
+use config::initilize::CONFIG;
+
+pub const TITLE: &str = "Confirm (y/n)";
+
+impl ui::State<'_> {
+    // Quitting now would stop the running downloads or lose the queue as queue is only kept
+    // across sessions when shared_queue is enabled
+    pub fn quit_needs_confirm(&self) -> bool {
+        self.has_running_downloads() || (!CONFIG.constants.shared_queue && !self.queue.is_empty())
+    }
+
+    // Ask user to confirm the action before doing it. Window to go back to when it is cancelled
+    // is kept same as with other overlays
+    pub fn ask_confirm(&mut self, action: ui::ConfirmAction) {
+        if !self.active.is_overlay() {
+            self.before_overlay = self.active.clone();
+        }
+        self.active = ui::Window::Confirm(action);
+    }
+
+    // Close the confirm window without doing anything. Returns the action that was being
+    // confirmed if confirm window was open
+    pub fn close_confirm(&mut self) -> Option<ui::ConfirmAction> {
+        match self.active {
+            ui::Window::Confirm(action) => {
+                self.active = self.before_overlay.clone();
+                Some(action)
+            }
+            _ => None,
+        }
+    }
+
+    // Text shown in confirm window explaining what will happen if action is done
+    pub fn confirm_message(&self, action: ui::ConfirmAction) -> String {
+        match action {
+            ui::ConfirmAction::Quit => {
+                let mut lost = Vec::with_capacity(2);
+                if self.has_running_downloads() {
+                    lost.push("running downloads will be stopped");
+                }
+                if !CONFIG.constants.shared_queue && !self.queue.is_empty() {
+                    lost.push("queued music will be lost");
+                }
+                format!(
+                    "Quit anyway? {}. Press y to quit or n to stay",
+                    lost.join(" and ")
+                )
+            }
+            ui::ConfirmAction::ClearQueue => format!(
+                "Remove all {} music from play queue? Press y to clear or n to keep them",
+                self.queue.len()
+            ),
+            ui::ConfirmAction::ClearHistory => String::from(
+                "Forget every recently played music? Press y to clear or n to keep them",
+            ),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::ui::{self, shared_import::*, utils::ExtendMpv};
+use config::initilize::CONFIG;
+
+// Next music is opened by fader this many seconds before it starts fading in so that its stream
+// is already resolved by youtube-dl when it is needed
+pub const LOAD_AHEAD_SECS: u64 = 10;
+
+// Second mpv that plays the start of next music over the end of the one being played. Main player
+// moves on to next music by itself when current one ends and continues it from where fader has
+// reached, after which fader is dropped
+pub struct Fader {
+    player: libmpv::Mpv,
+    // Id of music fader has opened. This is the next entry of mpv playlist. See `State::prefetched`
+    music_id: String,
+    // true once fader has started playing along with the end of current music
+    started: bool,
+}
+
+impl Fader {
+    // Open the music paused and muted so that it can be faded in right away later
+    fn load(music_id: &str) -> Option<Self> {
+        let player = libmpv::Mpv::new().ok()?;
+        player.configure_defult();
+        player.prefer_audio_language(&CONFIG.constants.audio_language);
+        player.set_output_device(&CONFIG.constants.audio_device);
+        player.set_volume(0);
+        player.set_property("pause", true).ok()?;
+        let url = format!("https://www.youtube.com/watch?v={}", music_id);
+        if let Err(err) = player.command("loadfile", [url.as_str()].as_ref()) {
+            log::warn!("Fader cannot load music {}. Error: {}", music_id, err);
+            return None;
+        }
+        Some(Fader {
+            player,
+            music_id: music_id.to_string(),
+            started: false,
+        })
+    }
+
+    // Pause or resume along with main player once both are playing
+    pub fn follow_pause(&self, paused: bool) {
+        if self.started {
+            self.player.set_property("pause", paused).ok();
+        }
+    }
+}
+
+// Fraction of user's volume for music fading out and the one fading in when remaining of the
+// current music is left. Both add up to full volume through the fade
+pub fn fade_levels(remaining: Duration, fade: Duration) -> (f64, f64) {
+    if fade.is_zero() {
+        return (1.0, 0.0);
+    }
+    let fading_out = (remaining.as_secs_f64() / fade.as_secs_f64()).min(1.0);
+    (fading_out, 1.0 - fading_out)
+}
+
+impl ui::State<'_> {
+    // Overlap the end of music with the start of next one. Next music is opened in fader as soon
+    // as it is prefetched (see `State::prefetch_next`) and played along with last crossfade_secs
+    // of current music. Main player is told to start next music from where fader will be by then,
+    // so switching back to main player is not heard. Volume of both is stepped every time mpv
+    // status is refreshed
+    pub fn crossfade(&mut self) {
+        if let Some(fader) = self.fader.as_ref().filter(|fader| fader.started) {
+            match self.playing_id() {
+                // Main player is still opening next music. Fader keeps playing meanwhile
+                None => return,
+                // Main player has moved on to music that fader was playing
+                Some(id) if id == fader.music_id => {
+                    self.skip_crossfade();
+                    return;
+                }
+                Some(_) => {}
+            }
+        }
+
+        let fade = Duration::from_secs(CONFIG.constants.crossfade_secs);
+        let duration = self
+            .player
+            .get_property::<f64>("duration")
+            .unwrap_or_default();
+        let elapsed = self
+            .player
+            .get_property::<f64>("audio-pts")
+            .unwrap_or_default();
+        // Live stream has no end to fade at. Nothing is faded when playlist stops at end of
+        // music, when music repeats itself or when playback is muted
+        let can_fade = !fade.is_zero()
+            && duration > 0.0
+            && self.playback_behaviour.auto_advance
+            && self.playback_behaviour.repeat != ui::RepeatMode::One
+            && self.playback_behaviour.muted_volume.is_none();
+        let next = match self.prefetched.clone() {
+            Some(next) if can_fade => next,
+            _ => {
+                self.skip_crossfade();
+                return;
+            }
+        };
+        if self.fader.as_ref().map(|fader| &fader.music_id) != Some(&next) {
+            self.skip_crossfade();
+            self.fader = Fader::load(&next);
+        }
+
+        let elapsed = Duration::from_secs_f64(elapsed.clamp(0.0, duration));
+        let remaining = Duration::from_secs_f64(duration).saturating_sub(elapsed);
+        let fader = match self.fader.as_mut() {
+            Some(fader) if remaining <= fade => fader,
+            _ => return,
+        };
+        if !fader.started {
+            // Both play the same part of music at same speed so next music is continued by main
+            // player from where fader has reached when this one ends
+            fader.player.set_speed(self.playback_behaviour.speed);
+            self.player
+                .set_property("start", format!("+{}", remaining.as_secs_f64()))
+                .ok();
+            fader.player.unpause().ok();
+            fader.started = true;
+        }
+
+        let (fading_out, fading_in) = fade_levels(remaining, fade);
+        let volume = |level: f64| (f64::from(self.playback_behaviour.volume) * level).round() as u8;
+        fader.player.set_volume(volume(fading_in));
+        let fading_out = volume(fading_out);
+        if self.fade_volume != Some(fading_out) && self.player.set_volume(fading_out) {
+            self.fade_volume = Some(fading_out);
+        }
+    }
+
+    // Bring the volume back to level set by user if it was lowered by fade
+    pub fn stop_fade(&mut self) {
+        if self.fade_volume.take().is_some() {
+            self.player.set_volume(self.playback_behaviour.volume);
+        }
+    }
+
+    // Stop fader and play only from main player at volume set by user. This is also done when
+    // music is changed by user as switching is expected to be immediate
+    pub fn skip_crossfade(&mut self) {
+        if self.fader.take().is_some() {
+            self.player.set_property("start", "none").ok();
+        }
+        self.stop_fade();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_out_as_next_fades_in() {
+        let secs = Duration::from_secs;
+        let fade = secs(4);
+        assert_eq!(fade_levels(secs(60), fade), (1.0, 0.0));
+        assert_eq!(fade_levels(secs(4), fade), (1.0, 0.0));
+        assert_eq!(fade_levels(secs(1), fade), (0.25, 0.75));
+        assert_eq!(fade_levels(secs(0), fade), (0.0, 1.0));
+        assert_eq!(fade_levels(secs(0), Duration::ZERO), (1.0, 0.0));
+    }
+}
@@ -0,0 +1,141 @@
+use crate::ui::{
+    self, shared_import::*, sort::Sortable, MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX,
+    MIDDLE_PLAYLIST_INDEX,
+};
+use config::initilize::{STORAGE, TB_RECENTS};
+
+// Remove item with given id from shown list and from the copies of it kept by filter and sort.
+// Selection stays at the same row or moves to new last row when last row was removed
+pub fn remove_item<T: Sortable>(
+    list: &mut (Vec<T>, TableState),
+    copies: [&mut Vec<T>; 2],
+    id: &str,
+) {
+    list.0.retain(|item| item.sort_id() != id);
+    for copy in copies {
+        copy.retain(|item| item.sort_id() != id);
+    }
+    let selection = match list.1.selected() {
+        Some(_) if list.0.is_empty() => None,
+        Some(index) => Some(index.min(list.0.len() - 1)),
+        None => None,
+    };
+    list.1.select(selection);
+}
+
+impl ui::State<'_> {
+    // Remove the selected item from the local collection shown in active window. That is
+    // favourites, recently played, most played or the queue. List filled from youtube is left
+    // as it is
+    pub fn delete_selected(&mut self) {
+        match self.active {
+            ui::Window::Queuebar => self.remove_queued(),
+            ui::Window::Musicbar => {
+                let music = match self.musicbar.1.selected() {
+                    Some(index) => self.musicbar.0[index].clone(),
+                    None => return,
+                };
+                let removed = match self.filled_source.0 {
+                    ui::MusicbarSource::Favourates => self.remove_music_from_favourates(&music),
+                    ui::MusicbarSource::RecentlyPlayed => self.remove_from_recents(&music),
+                    ui::MusicbarSource::MostPlayed => self.forget_plays(&music),
+                    _ => return,
+                };
+                if removed {
+                    let (unfiltered, unsorted) = (&mut self.unfiltered.0, &mut self.unsorted.0);
+                    remove_item(&mut self.musicbar, [unfiltered, unsorted], &music.id);
+                    self.list_removed(MIDDLE_MUSIC_INDEX);
+                }
+            }
+            ui::Window::Playlistbar => {
+                if self.filled_source.1 != ui::PlaylistbarSource::Favourates {
+                    return;
+                }
+                let playlist = match self.playlistbar.1.selected() {
+                    Some(index) => self.playlistbar.0[index].clone(),
+                    None => return,
+                };
+                if self.remove_playlist_from_favourates(&playlist) {
+                    let (unfiltered, unsorted) = (&mut self.unfiltered.1, &mut self.unsorted.1);
+                    remove_item(&mut self.playlistbar, [unfiltered, unsorted], &playlist.id);
+                    self.list_removed(MIDDLE_PLAYLIST_INDEX);
+                }
+            }
+            ui::Window::Artistbar => {
+                if self.filled_source.2 != ui::ArtistbarSource::Favourates {
+                    return;
+                }
+                let artist = match self.artistbar.1.selected() {
+                    Some(index) => self.artistbar.0[index].clone(),
+                    None => return,
+                };
+                if self.remove_artist_from_favourates(&artist) {
+                    let (unfiltered, unsorted) = (&mut self.unfiltered.2, &mut self.unsorted.2);
+                    remove_item(&mut self.artistbar, [unfiltered, unsorted], &artist.id);
+                    self.list_removed(MIDDLE_ARTIST_INDEX);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Forget music from recently played list. Play count kept in history is left as it is
+    fn remove_from_recents(&mut self, music: &fetcher::MusicUnit) -> bool {
+        let query = format!(
+            "
+            DELETE FROM {tb_name}
+            WHERE id = :id
+        ",
+            tb_name = TB_RECENTS
+        );
+        let args = [(":id", &music.id)];
+
+        match STORAGE.lock().unwrap().execute(&query, &args) {
+            Ok(_) => {
+                self.status = "Removed..";
+                true
+            }
+            Err(err) => {
+                log::error!("Error removing from recents. Error: {err}", err = err);
+                self.status = "Err removing..";
+                false
+            }
+        }
+    }
+
+    // Marks are of rows before removal so they are cleared along with drawing the list again
+    fn list_removed(&mut self, index: usize) {
+        let window = self.active.clone();
+        self.clear_marks(&window);
+        self.list_dirty[index] = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artist(id: &str) -> fetcher::ArtistUnit {
+        fetcher::ArtistUnit {
+            name: id.to_string(),
+            id: id.to_string(),
+            video_count: "0".to_string(),
+        }
+    }
+
+    #[test]
+    fn selection_kept_in_list() {
+        let mut list = (vec![artist("1"), artist("2")], TableState::default());
+        let mut unfiltered = vec![artist("1"), artist("2"), artist("3")];
+        let mut unsorted = Vec::new();
+        list.1.select(Some(1));
+
+        remove_item(&mut list, [&mut unfiltered, &mut unsorted], "2");
+        assert_eq!(list.0.len(), 1);
+        assert_eq!(list.1.selected(), Some(0));
+        assert_eq!(unfiltered.len(), 2);
+
+        remove_item(&mut list, [&mut unfiltered, &mut unsorted], "1");
+        assert_eq!(list.1.selected(), None);
+    }
+}
@@ -1,6 +1,14 @@
-use crate::ui::{self, utils::ExtendMpv};
-use config::initilize::{CONFIG, STORAGE};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::ui::{
+    self,
+    utils::{ExtendMpv, LockRecover},
+};
+use config::{
+    initilize::{CONFIG, STORAGE},
+    keymap::{Action, KeyCombo},
+};
+use crossterm::event::{
+    self, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use std::{
     convert::TryFrom,
     sync::{Arc, Condvar, Mutex},
@@ -10,6 +18,10 @@ use std::{
 pub const MIDDLE_MUSIC_INDEX: usize = 0;
 pub const MIDDLE_PLAYLIST_INDEX: usize = 1;
 pub const MIDDLE_ARTIST_INDEX: usize = 2;
+// Terminal errors in a row after which app is quit as terminal is most likely gone
+const MAX_INPUT_ERRORS: u32 = 10;
+// Title of popup showing the size of response cache
+const CACHE_POPUP_TITLE: &str = "Result cache";
 
 #[derive(Clone)]
 enum HeadTo {
@@ -19,19 +31,31 @@ enum HeadTo {
 }
 
 // Helper function to return the index of something depending the current position and direction to
-// move to
-fn advance_index(current: usize, limit: usize, direction: HeadTo) -> usize {
+// move to. When wrap is false, index stays at first/last item instead of moving to the other end
+fn advance_index(current: usize, limit: usize, direction: HeadTo, wrap: bool) -> usize {
     // This means that the list is empty.
     if limit == 0 {
         return 0;
     }
     match direction {
+        HeadTo::Next if !wrap => (current + 1).min(limit - 1),
+        HeadTo::Prev if !wrap => current.saturating_sub(1).min(limit - 1),
         HeadTo::Next => (current + 1) % limit,
         HeadTo::Prev => current.checked_sub(1).unwrap_or(limit - 1) % limit,
         HeadTo::Initial => current,
     }
 }
 
+// Whether track is changed to next (true) or previous (false) one in given direction. None for
+// Initial as there is no first page of tracks to go back to
+fn track_direction(direction: HeadTo) -> Option<bool> {
+    match direction {
+        HeadTo::Next => Some(true),
+        HeadTo::Prev => Some(false),
+        HeadTo::Initial => None,
+    }
+}
+
 // Helper function to drop the first paramater and call the function in second paramater and
 // optional arguments provided in later arguments
 // This is used to drop the state and call the function as such pattern is found redundant while
@@ -52,6 +76,66 @@ macro_rules! drop_and_call {
     }};
 }
 
+// Returns true if the character key should quit the application. While typing in searchbar,
+// filter or bookmark popup quit key is always taken as input. Otherwise quit key should be
+// pressed as bound (with CTRL by default) or alone just after leaving searchbar with ESC (See
+// constants.esc_quit)
+fn is_quit_key(
+    action: Option<Action>,
+    ch: char,
+    is_input: bool,
+    quit_armed: bool,
+    quit_key: char,
+) -> bool {
+    let is_quit = matches!(action, Some(Action::Quit | Action::ForceQuit));
+    !is_input && (is_quit || (quit_armed && ch == quit_key))
+}
+
+// Returns how many times the given key is pressed including this one by reading the same key
+// events that are already waiting to be read. When a key is held, terminal sends the key event
+// repeatedly so this is used to handle all of them at once instead of one at a time.
+// Any other event read in the process is stored in `pending` to be handled next
+fn count_repeats(key: event::KeyEvent, pending: &mut Option<Event>) -> u32 {
+    let mut count = 1;
+    while let Ok(true) = event::poll(Duration::ZERO) {
+        match event::read() {
+            Ok(Event::Key(next)) if next == key => count += 1,
+            Ok(other) => {
+                *pending = Some(other);
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+    count
+}
+
+// Wait up to timeout for next terminal event. Returns None on timeout or when terminal failed to
+// give the event. Failure is only logged and counted in errors so that a single bad read do not
+// crash the app. errors is reset once an event is read successfully
+fn next_input<P, R>(timeout: Duration, errors: &mut u32, poll: P, read: R) -> Option<Event>
+where
+    P: FnOnce(Duration) -> crossterm::Result<bool>,
+    R: FnOnce() -> crossterm::Result<Event>,
+{
+    let next = match poll(timeout) {
+        Ok(true) => read().map(Some),
+        Ok(false) => Ok(None),
+        Err(err) => Err(err),
+    };
+    match next {
+        Ok(event) => {
+            *errors = 0;
+            event
+        }
+        Err(err) => {
+            *errors += 1;
+            log::error!("Cannot read terminal event. Error: {err}", err = err);
+            None
+        }
+    }
+}
+
 // Heklper function to get the next page depending on the current page and direction to move
 // This was mainly created to fetch the next page of the musicbar/playlist bar when user
 // hits NEXT_SH_KEY or PREV_SH_KEY
@@ -89,46 +173,64 @@ pub async fn event_sender(
         video_count: "NaN".to_string(),
     }];
 
-    let download_counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
-
     // There is several option in sidebar like trending/ favourates,
     // this handler will change the selected option from sidebar depending on the direction user
     // move (Up or DOwn).
     let advance_sidebar = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         let current = state.sidebar.selected().unwrap_or_default();
-        state.sidebar.select(Some(advance_index(
+        let next_index = advance_index(
             current,
             ui::utils::SIDEBAR_LIST_COUNT,
             direction,
-        )));
+            CONFIG.constants.wrap_navigation,
+        );
+        if state.sidebar.selected() == Some(next_index) {
+            return;
+        }
+        state.sidebar.select(Some(next_index));
         notifier.notify_all();
     };
 
     // select the next or previous element in musicbar list. This is done simply by setting the
     // correct index in corresponding TableState
     let advance_music_list = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        let next_index;
-        match state.musicbar.1.selected() {
-            None => next_index = 0,
-            Some(current) => {
-                next_index = advance_index(current, state.musicbar.0.len(), direction);
-            }
+        let mut state = state_original.lock_recover();
+        let current = state.musicbar.1.selected();
+        let next_index = match current {
+            None => 0,
+            Some(current) => advance_index(
+                current,
+                state.musicbar.0.len(),
+                direction,
+                CONFIG.constants.wrap_navigation,
+            ),
+        };
+        // Already at the end of list when navigation do not wrap around
+        if current == Some(next_index) {
+            return;
         }
         state.musicbar.1.select(Some(next_index));
+        state.select_for_playing();
         notifier.notify_all();
     };
 
     // simialr to advance_music_list but instead rotate data in `playlistbar` variable of state
     let advance_playlist_list = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        let next_index;
-        match state.playlistbar.1.selected() {
-            None => next_index = 0,
-            Some(current) => {
-                next_index = advance_index(current, state.playlistbar.0.len(), direction);
-            }
+        let mut state = state_original.lock_recover();
+        let current = state.playlistbar.1.selected();
+        let next_index = match current {
+            None => 0,
+            Some(current) => advance_index(
+                current,
+                state.playlistbar.0.len(),
+                direction,
+                CONFIG.constants.wrap_navigation,
+            ),
+        };
+        // Already at the end of list when navigation do not wrap around
+        if current == Some(next_index) {
+            return;
         }
         state.playlistbar.1.select(Some(next_index));
         notifier.notify_all();
@@ -136,21 +238,111 @@ pub async fn event_sender(
 
     // simialr to advance_playlist_list but instead rotate data in `artistbar` variable of state
     let advance_artist_list = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         // if the list is empty then do nothing else.
         // It is necessary to return instantly otherwise the next_index will get value 0 and this
         // closure will endup doing select(Some(0)) to the empty list
-        let next_index;
-        match state.artistbar.1.selected() {
-            None => next_index = 0,
-            Some(current) => {
-                next_index = advance_index(current, state.artistbar.0.len(), direction);
-            }
+        let current = state.artistbar.1.selected();
+        let next_index = match current {
+            None => 0,
+            Some(current) => advance_index(
+                current,
+                state.artistbar.0.len(),
+                direction,
+                CONFIG.constants.wrap_navigation,
+            ),
+        };
+        // Already at the end of list when navigation do not wrap around
+        if current == Some(next_index) {
+            return;
         }
         state.artistbar.1.select(Some(next_index));
         notifier.notify_all();
     };
 
+    // Select the item at given index of list in active window. Index past the end selects the
+    // last item so usize::MAX can be used to jump to bottom. Nothing is done if selection is
+    // already there
+    let jump_in_list = |to: usize| {
+        let mut state = state_original.lock_recover();
+        let target = |len: usize| to.min(len.saturating_sub(1));
+        let (current, target) = match state.active {
+            ui::Window::Sidebar => {
+                let target = target(ui::utils::SIDEBAR_LIST_COUNT);
+                let current = state.sidebar.selected();
+                state.sidebar.select(Some(target));
+                (current, target)
+            }
+            ui::Window::Musicbar if !state.musicbar.0.is_empty() => {
+                let target = target(state.musicbar.0.len());
+                let current = state.musicbar.1.selected();
+                state.musicbar.1.select(Some(target));
+                if current != Some(target) {
+                    state.select_for_playing();
+                }
+                (current, target)
+            }
+            ui::Window::Playlistbar if !state.playlistbar.0.is_empty() => {
+                let target = target(state.playlistbar.0.len());
+                let current = state.playlistbar.1.selected();
+                state.playlistbar.1.select(Some(target));
+                (current, target)
+            }
+            ui::Window::Artistbar if !state.artistbar.0.is_empty() => {
+                let target = target(state.artistbar.0.len());
+                let current = state.artistbar.1.selected();
+                state.artistbar.1.select(Some(target));
+                (current, target)
+            }
+            // Number of lines in help is only known when drawing. Scroll is clamped there
+            ui::Window::Helpbar | ui::Window::Lyrics => {
+                let target = u16::try_from(to).unwrap_or(u16::MAX);
+                let current = std::mem::replace(&mut state.overlay_scroll, target);
+                (Some(current as usize), target as usize)
+            }
+            ui::Window::Queuebar => {
+                let target = target(state.queue.len());
+                (
+                    Some(std::mem::replace(&mut state.queue_selected, target)),
+                    target,
+                )
+            }
+            _ => return,
+        };
+        if current != Some(target) {
+            notifier.notify_all();
+        }
+    };
+
+    // Keep the count or operator typed for motion until next key. Motion is only typed while
+    // some list, help or lyrics is focused so that it never takes any input being typed
+    let continue_motion = |motion: ui::motion::Motion| {
+        let mut state = state_original.lock_recover();
+        match state.active {
+            ui::Window::Sidebar
+            | ui::Window::Musicbar
+            | ui::Window::Playlistbar
+            | ui::Window::Artistbar
+            | ui::Window::Queuebar
+            | ui::Window::Helpbar
+            | ui::Window::Lyrics => {
+                state.motion = motion;
+                notifier.notify_all();
+            }
+            _ => {}
+        }
+    };
+
+    // Remove given number of items from focused local list or queue starting from selected one.
+    // Selection stays at same row after each removal so next item is removed every time
+    let delete_rows = |count: usize| {
+        let mut state = state_original.lock_recover();
+        for _ in 0..count {
+            state.delete_selected();
+        }
+        notifier.notify_all();
+    };
+
     // When active window is set to NONE, it means user had requested to quit the application,
     // This handle will fire when user hits QUIT_SH_KEY
     // Before breaking the loop which this function is running on
@@ -158,53 +350,117 @@ pub async fn event_sender(
     // thread can also respond to the event (which is usally again breking the running loop in
     // thread)
     let quit = |force_quit: bool| -> bool {
-        let mut state = state_original.lock().unwrap();
-        // Do not quit when some download is in progress as it may leave partial file on the disk.
-        // If it is urgent required to quit the application user should also press ALT key along
-        // with CTRL and QUIT key
-        if !force_quit && *download_counter.lock().unwrap() > 0 {
-            state.active = ui::Window::Popup(
-                "Error",
-                "Some download are in progress. Press this shortcut with ALT key to force quit"
-                    .to_string(),
-            );
+        let mut state = state_original.lock_recover();
+        // Ask before quitting when some download is in progress as it may leave partial file on
+        // the disk, or when queue would be lost. If it is urgent required to quit the application
+        // user can also press ALT key along with CTRL and QUIT key to skip asking
+        if !force_quit && state.quit_needs_confirm() {
+            state.ask_confirm(ui::ConfirmAction::Quit);
+            notifier.notify_all();
             return false;
         }
 
+        state.save_session();
         // setting active window to None is to quit
         state.active = ui::Window::None;
         // Also make sure databse is flushed.
         if let Err(err) = STORAGE.lock().unwrap().cache_flush() {
-            eprintln!("Cannot flush the storage db. Error: {err}", err = err);
+            log::error!("Cannot flush the storage db. Error: {err}", err = err);
         }
 
         notifier.notify_all();
         true
     };
 
+    // Answer given to confirm window. Confirmed action is done and cancelled one is simply
+    // forgotten. Returns true if application is quitting
+    let handle_confirm = |confirmed: bool| -> bool {
+        let mut state = state_original.lock_recover();
+        match state.close_confirm() {
+            Some(ui::ConfirmAction::Quit) if confirmed => drop_and_call!(state, quit, true),
+            Some(ui::ConfirmAction::ClearQueue) if confirmed => {
+                state.clear_queue();
+                notifier.notify_all();
+                false
+            }
+            Some(ui::ConfirmAction::ClearHistory) if confirmed => {
+                state.clear_history();
+                notifier.notify_all();
+                false
+            }
+            _ => {
+                notifier.notify_all();
+                false
+            }
+        }
+    };
+
     // This handler will fire up when user request to move between sections like musicbar, sidebar
     // etc. Similar handler moveto_next_window / moveto_prev_window are not merged as these
-    // closures as these handlers are frequently called so avoid more branching
+    // closures as these handlers are frequently called so avoid more branching. Panes hidden by
+    // user are skipped. Sidebar is never hidden so these always find a window
     let moveto_next_window = || {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
+        if state.active.is_modal() {
+            return;
+        }
         state.active = state.active.next();
+        while state.is_hidden(&state.active) {
+            state.active = state.active.next();
+        }
         notifier.notify_all();
     };
 
     let moveto_prev_window = || {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
+        if state.active.is_modal() {
+            return;
+        }
         state.active = state.active.prev();
+        while state.is_hidden(&state.active) {
+            state.active = state.active.prev();
+        }
         notifier.notify_all();
     };
 
     // This handler is fired when user press ESC key,
     // if searchbar is active clear the content in search bar and move to next window
-    // if helpbar is active anway move to sidebar just to hide the help window
+    // if help or queue window is active go back to window that was active before it
     let handle_esc = || {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
+        // Unmark items of focused list before anything else
+        let active = state.active.clone();
+        if state.clear_marks(&active) {
+            notifier.notify_all();
+            return;
+        }
+        // When focused list is filtered, remove the filter first
+        if let Some(filter) = &state.filter {
+            if filter.window == state.active {
+                state.clear_filter();
+                notifier.notify_all();
+                return;
+            }
+        }
         match state.active {
+            ui::Window::Helpbar | ui::Window::Queuebar | ui::Window::Lyrics => {
+                state.active = state.before_overlay.clone();
+                notifier.notify_all();
+            }
+            ui::Window::Confirm(_) => {
+                drop_and_call!(state, handle_confirm, false);
+            }
+            ui::Window::Menu => {
+                state.close_menu();
+                notifier.notify_all();
+            }
             ui::Window::Searchbar | ui::Window::Popup(..) => {
+                state.quit_armed =
+                    CONFIG.constants.esc_quit && state.active == ui::Window::Searchbar;
                 state.search.0.clear();
+                state.search_typed_at = None;
+                state.forget_recall();
+                state.cancel_bookmark();
                 drop_and_call!(state, moveto_next_window);
             }
             ui::Window::BottomControl => {
@@ -228,28 +484,273 @@ pub async fn event_sender(
     // and if this key is pressed from somewhere else other than searchbar then will simply
     // move to previous window
     let handle_backspace = || {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
+        if state.is_bookmarking() {
+            state.handle_bookmark_backspace();
+            notifier.notify_all();
+            return;
+        }
+        if state.is_filtering() {
+            if let Some(filter) = &mut state.filter {
+                filter.query.pop();
+            }
+            state.apply_filter();
+            notifier.notify_all();
+            return;
+        }
         match state.active {
             ui::Window::Searchbar => {
                 state.search.0.pop();
+                state.forget_recall();
+                state.typed_search();
+                notifier.notify_all();
+            }
+            ui::Window::Queuebar => {
+                state.remove_queued();
                 notifier.notify_all();
             }
             _ => drop_and_call!(state, moveto_prev_window),
         }
     };
 
+    // Delete key still erases typed text like backspace. In lists it removes the selected item
+    // from favourites, recently played or the queue. Nothing is done in other lists
+    let handle_delete = || {
+        let mut state = state_original.lock_recover();
+        let is_typing =
+            state.is_bookmarking() || state.is_filtering() || state.active == ui::Window::Searchbar;
+        if is_typing {
+            drop_and_call!(state, handle_backspace);
+        } else {
+            state.delete_selected();
+            notifier.notify_all();
+        }
+    };
+
     // This is fires when user press any character key
     // this will simpley push the recived character in search query term and update state
     // so can the added character becomes visible
     let handle_search_input = |ch| {
-        state_original.lock().unwrap().search.0.push(ch);
+        let mut state = state_original.lock_recover();
+        state.search.0.push(ch);
+        state.forget_recall();
+        state.typed_search();
+        notifier.notify_all();
+    };
+
+    // Same as handle_search_input but push the character in filter query and filter the list
+    let handle_filter_input = |ch| {
+        let mut state = state_original.lock_recover();
+        if let Some(filter) = &mut state.filter {
+            filter.query.push(ch);
+        }
+        state.apply_filter();
+        notifier.notify_all();
+    };
+
+    let start_filter = || {
+        let mut state = state_original.lock_recover();
+        if state.start_filter() {
+            state.status = "Filter..";
+        } else {
+            state.status = "Nothing to filter..";
+        }
+        notifier.notify_all();
+    };
+
+    let toggle_filter_mode = || {
+        let mut state = state_original.lock_recover();
+        if state.filter_mode == ui::filter::FilterMode::Fuzzy {
+            state.filter_mode = ui::filter::FilterMode::Substring;
+            state.status = "Substring filter..";
+        } else {
+            state.filter_mode = ui::filter::FilterMode::Fuzzy;
+            state.status = "Fuzzy filter..";
+        }
+        state.apply_filter();
+        notifier.notify_all();
+    };
+
+    // Show how many web responses are cached. If this info is already being shown then ask
+    // communicator to clear the cache
+    let handle_cache_info = || {
+        let mut state = state_original.lock_recover();
+        if let ui::Window::Popup(CACHE_POPUP_TITLE, _) = state.active {
+            state.clear_cache = true;
+            state.status = "Clearing cache..";
+            state.active = state.active.next();
+        } else {
+            let message = format!(
+                "{} of {} responses are cached. Press {} again to clear the cache",
+                state.cache_size.0,
+                state.cache_size.1,
+                CONFIG
+                    .key_map(state.key_profile)
+                    .describe(Action::CacheInfo)
+            );
+            state.active = ui::Window::Popup(CACHE_POPUP_TITLE, message);
+        }
+        notifier.notify_all();
+    };
+
+    // Show the help window over everything else. Whatever window was active before is made
+    // active again when help is hidden
+    let toggle_help = || {
+        let mut state = state_original.lock_recover();
+        if state.active == ui::Window::Helpbar {
+            state.active = state.before_overlay.clone();
+        } else {
+            // When switching from queue window, hiding help goes back to where queue was opened
+            if !matches!(state.active, ui::Window::Queuebar | ui::Window::Lyrics) {
+                state.before_overlay = state.active.clone();
+            }
+            state.overlay_scroll = 0;
+            state.active = ui::Window::Helpbar;
+        }
+        notifier.notify_all();
+    };
+
+    // Same as toggle_help but for the window listing the play queue
+    let toggle_queue = || {
+        let mut state = state_original.lock_recover();
+        if state.active == ui::Window::Queuebar {
+            state.active = state.before_overlay.clone();
+        } else {
+            if !matches!(state.active, ui::Window::Helpbar | ui::Window::Lyrics) {
+                state.before_overlay = state.active.clone();
+            }
+            state.queue_selected = 0;
+            state.active = ui::Window::Queuebar;
+        }
+        notifier.notify_all();
+    };
+
+    // Same as toggle_help but for the lyrics of music being played
+    let toggle_lyrics = || {
+        let mut state = state_original.lock_recover();
+        if state.active == ui::Window::Lyrics {
+            state.active = state.before_overlay.clone();
+        } else {
+            let previous = state.active.clone();
+            if !state.show_lyrics() {
+                notifier.notify_all();
+                return;
+            }
+            if !matches!(previous, ui::Window::Helpbar | ui::Window::Queuebar) {
+                state.before_overlay = previous;
+            }
+            state.active = ui::Window::Lyrics;
+        }
+        notifier.notify_all();
+    };
+
+    // Move the selected music in queue window up or down. Returns false if queue window is not
+    // active so that key can be handled as usual
+    let move_queued = |direction: HeadTo| {
+        let mut state = state_original.lock_recover();
+        if state.active != ui::Window::Queuebar {
+            return false;
+        }
+        state.move_queued(matches!(direction, HeadTo::Next));
+        notifier.notify_all();
+        true
+    };
+
+    let toggle_play_on_select = || {
+        let mut state = state_original.lock_recover();
+        state.play_on_select = !state.play_on_select;
+        state.status = if state.play_on_select {
+            "Play on select.."
+        } else {
+            "Play on enter.."
+        };
+        notifier.notify_all();
+    };
+
+    let toggle_stay_on_source = || {
+        let mut state = state_original.lock_recover();
+        state.stay_on_source = !state.stay_on_source;
+        state.status = if state.stay_on_source {
+            "Stay on source.."
+        } else {
+            "Follow playback.."
+        };
+        notifier.notify_all();
+    };
+
+    let cycle_sleep_timer = || {
+        state_original.lock_recover().cycle_sleep_timer();
+        notifier.notify_all();
+    };
+
+    let export_list = || {
+        state_original.lock_recover().export_list();
+        notifier.notify_all();
+    };
+
+    let import_list = || {
+        state_original.lock_recover().import_latest();
+        notifier.notify_all();
+    };
+
+    let toggle_radio = || {
+        state_original.lock_recover().toggle_radio();
+        notifier.notify_all();
+    };
+
+    let goto_playing = || {
+        if state_original.lock_recover().select_now_playing() {
+            notifier.notify_all();
+        }
+    };
+
+    let toggle_mark = || {
+        state_original.lock_recover().toggle_mark();
+        notifier.notify_all();
+    };
+
+    let cycle_output_device = || {
+        let mut state = state_original.lock_recover();
+        state.cycle_output_device();
+        notifier.notify_all();
+    };
+
+    let cycle_audio_track = || {
+        let mut state = state_original.lock_recover();
+        state.cycle_audio_track();
+        notifier.notify_all();
+    };
+
+    let start_bookmark = || {
+        let mut state = state_original.lock_recover();
+        state.start_bookmark();
+        notifier.notify_all();
+    };
+
+    let list_bookmarks = || {
+        let mut state = state_original.lock_recover();
+        state.list_bookmarks();
+        notifier.notify_all();
+    };
+
+    let handle_bookmark_input = |ch, list_key| {
+        let mut state = state_original.lock_recover();
+        state.handle_bookmark_input(ch, list_key);
         notifier.notify_all();
     };
 
     // This handler is fired when use press SEARCH_SH_KEY
     // this will move the curson to the searchbar from which user can start to type the query
-    let activate_search = || {
-        let mut state = state_original.lock().unwrap();
+    // if prefill is true, the searchbar is filled with last searched query. As the cursor is
+    // always drawn after the query, user can continue editing from the end.
+    // Otherwise searchbar is cleared. Search result already in list is kept in both case
+    let activate_search = |prefill: bool| {
+        let mut state = state_original.lock_recover();
+        if prefill {
+            state.search.0 = state.search.1.clone();
+        } else {
+            state.search.0.clear();
+        }
         state.active = ui::Window::Searchbar;
         notifier.notify_all();
     };
@@ -260,8 +761,34 @@ pub async fn event_sender(
     // So, depending on the window which is currently active, this closure will call
     // the respective handler which will advance the corersponding list
     let handle_up_down = |direction: HeadTo| {
-        let state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         match state.active {
+            // Scroll offset is clamped to the end of help text when drawing
+            ui::Window::Helpbar | ui::Window::Lyrics => {
+                state.overlay_scroll = match direction {
+                    HeadTo::Next => state.overlay_scroll.saturating_add(1),
+                    HeadTo::Prev => state.overlay_scroll.saturating_sub(1),
+                    _ => unreachable!(),
+                };
+                notifier.notify_all();
+            }
+            ui::Window::Menu => {
+                state.move_menu(matches!(direction, HeadTo::Next));
+                notifier.notify_all();
+            }
+            // Selection is clamped to the end of queue when drawing
+            ui::Window::Queuebar => {
+                state.queue_selected = match direction {
+                    HeadTo::Next => state.queue_selected.saturating_add(1),
+                    HeadTo::Prev => state.queue_selected.saturating_sub(1),
+                    _ => unreachable!(),
+                };
+                notifier.notify_all();
+            }
+            ui::Window::Searchbar => {
+                state.recall_search(matches!(direction, HeadTo::Prev));
+                notifier.notify_all();
+            }
             ui::Window::Sidebar => drop_and_call!(state, advance_sidebar, direction),
             ui::Window::Musicbar => drop_and_call!(state, advance_music_list, direction),
             ui::Window::Playlistbar => drop_and_call!(state, advance_playlist_list, direction),
@@ -274,45 +801,88 @@ pub async fn event_sender(
         }
     };
 
+    // Move selection of focused list by given number of rows. Without count it moves a single
+    // row same as arrow keys, which may wrap around the list. Counted move stops at either end
+    let move_by = |count: Option<usize>, direction: HeadTo| match count {
+        None => handle_up_down(direction),
+        Some(count) => {
+            let current = state_original.lock_recover().list_position();
+            if let Some(current) = current {
+                let down = matches!(direction, HeadTo::Next);
+                jump_in_list(ui::motion::moved_index(current, count, down));
+            }
+        }
+    };
+
     let start_search = || {
-        let mut state = state_original.lock().unwrap();
-        let search_term = state.search.0.trim();
+        let mut state = state_original.lock_recover();
+        // Searching now so nothing is left for live search
+        state.search_typed_at = None;
+        let search_term = state.search.0.trim().to_string();
 
         // return instantly if query is empty
         if search_term.is_empty() {
             return;
         }
+        // Remember this as the last searched query
+        state.search.1 = search_term.clone();
+
         // When prefiexed by the string as defined in config only show the specific result type
-        // respectively
-        else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[0]) {
-            let search_term =
-                search_term[CONFIG.constants.search_by_type[0].len() - 1..].to_string();
-            state.fetched_page[0] = Some(0);
-            state.filled_source.0 = ui::MusicbarSource::Search(search_term);
-        } else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[1]) {
-            let search_term =
-                search_term[CONFIG.constants.search_by_type[1].len() - 1..].to_string();
-            state.fetched_page[1] = Some(0);
-            state.filled_source.1 = ui::PlaylistbarSource::Search(search_term);
-        } else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[2]) {
-            let search_term =
-                search_term[&CONFIG.constants.search_by_type[2].len() - 1..].to_string();
-            state.fetched_page[2] = Some(0);
-            state.filled_source.2 = ui::ArtistbarSource::Search(search_term);
-        }
-        // If nothing of the prefix is defined then search for all type
-        else {
-            let search_term = search_term.to_string();
-            state.fetched_page = [Some(0); 3];
-            state.filled_source.0 = ui::MusicbarSource::Search(search_term.clone());
-            state.filled_source.1 = ui::PlaylistbarSource::Search(search_term.clone());
-            state.filled_source.2 = ui::ArtistbarSource::Search(search_term);
+        // respectively. Otherwise search for the type chosen with search_scope key.
+        // List not being searched is left as it is
+        let (prefix_scope, query) = ui::SearchScope::from_query(&search_term);
+        if query.is_empty() {
+            return;
         }
+        let query = query.to_string();
+        let scope = match prefix_scope.unwrap_or(state.search_scope) {
+            ui::SearchScope::Focused => state.focused_scope(),
+            scope => scope,
+        };
+        match scope {
+            ui::SearchScope::Music => {
+                state.fetched_page[MIDDLE_MUSIC_INDEX] = Some(0);
+                state.filled_source.0 = ui::MusicbarSource::Search(query);
+            }
+            ui::SearchScope::Playlist => {
+                state.fetched_page[MIDDLE_PLAYLIST_INDEX] = Some(0);
+                state.filled_source.1 = ui::PlaylistbarSource::Search(query);
+            }
+            ui::SearchScope::Artist => {
+                state.fetched_page[MIDDLE_ARTIST_INDEX] = Some(0);
+                state.filled_source.2 = ui::ArtistbarSource::Search(query);
+            }
+            ui::SearchScope::All | ui::SearchScope::Focused => {
+                state.fetched_page = [Some(0); 3];
+                state.filled_source.0 = ui::MusicbarSource::Search(query.clone());
+                state.filled_source.1 = ui::PlaylistbarSource::Search(query.clone());
+                state.filled_source.2 = ui::ArtistbarSource::Search(query);
+            }
+        }
+        notifier.notify_all();
+    };
+
+    let cycle_search_scope = || {
+        let mut state = state_original.lock_recover();
+        state.search_scope = state.search_scope.next();
+        state.status = match state.search_scope {
+            ui::SearchScope::All => "Search all..",
+            ui::SearchScope::Music => "Search music..",
+            ui::SearchScope::Playlist => "Search playlist..",
+            ui::SearchScope::Artist => "Search artist..",
+            ui::SearchScope::Focused => "Search focused list..",
+        };
+        notifier.notify_all();
+    };
+
+    let toggle_search_focused = || {
+        let mut state = state_original.lock_recover();
+        state.toggle_search_focused();
         notifier.notify_all();
     };
 
     let fill_trending_music = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         state.fetched_page[MIDDLE_MUSIC_INDEX] =
             Some(get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction));
         state.filled_source.0 = ui::MusicbarSource::Trending;
@@ -320,22 +890,44 @@ pub async fn event_sender(
     };
 
     let fill_community_source = || {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
+        state.discard_filter(&ui::Window::Artistbar);
+        state.discard_sort(&ui::Window::Artistbar);
         state.artistbar.0 = youtube_community_channels.clone();
         state.active = ui::Window::Artistbar;
+        // Musicbar is filled with popular videos by communicator
+        state.filled_source.0 = ui::MusicbarSource::Community;
+        let page = get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], HeadTo::Initial);
+        state.fetched_page[MIDDLE_MUSIC_INDEX] = Some(page);
         notifier.notify_all();
     };
 
     let fill_fav_music = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         state.filled_source.0 = ui::MusicbarSource::Favourates;
         let page = get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction);
         state.fetched_page[MIDDLE_MUSIC_INDEX] = Some(page);
         notifier.notify_all();
     };
 
+    let fill_recent_music = |direction: HeadTo| {
+        let mut state = state_original.lock_recover();
+        state.filled_source.0 = ui::MusicbarSource::RecentlyPlayed;
+        let page = get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction);
+        state.fetched_page[MIDDLE_MUSIC_INDEX] = Some(page);
+        notifier.notify_all();
+    };
+
+    let fill_most_played = |direction: HeadTo| {
+        let mut state = state_original.lock_recover();
+        state.filled_source.0 = ui::MusicbarSource::MostPlayed;
+        let page = get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction);
+        state.fetched_page[MIDDLE_MUSIC_INDEX] = Some(page);
+        notifier.notify_all();
+    };
+
     let fill_fav_playlist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         state.filled_source.1 = ui::PlaylistbarSource::Favourates;
         let page = get_page(&state.fetched_page[MIDDLE_PLAYLIST_INDEX], direction);
         state.fetched_page[MIDDLE_PLAYLIST_INDEX] = Some(page);
@@ -343,7 +935,7 @@ pub async fn event_sender(
     };
 
     let fill_fav_artist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         state.filled_source.2 = ui::ArtistbarSource::Favourates;
         let page = get_page(&state.fetched_page[MIDDLE_ARTIST_INDEX], direction);
         state.fetched_page[MIDDLE_ARTIST_INDEX] = Some(page);
@@ -351,7 +943,7 @@ pub async fn event_sender(
     };
 
     let fill_music_from_playlist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         if let ui::MusicbarSource::Playlist(playlist_id) = &state.filled_source.0 {
             state.filled_source.0 = ui::MusicbarSource::Playlist(playlist_id.to_string());
             state.fetched_page[MIDDLE_MUSIC_INDEX] =
@@ -361,7 +953,7 @@ pub async fn event_sender(
     };
 
     let fill_music_from_artist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         if let ui::MusicbarSource::Artist(artist_id) = &state.filled_source.0 {
             state.filled_source.0 = ui::MusicbarSource::Artist(artist_id.to_string());
             state.fetched_page[MIDDLE_MUSIC_INDEX] =
@@ -371,7 +963,7 @@ pub async fn event_sender(
     };
 
     let fill_playlist_from_artist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         if let ui::PlaylistbarSource::Artist(artist_id) = &state.filled_source.1 {
             state.filled_source.1 = ui::PlaylistbarSource::Artist(artist_id.to_string());
             state.fetched_page[MIDDLE_PLAYLIST_INDEX] = Some(get_page(
@@ -383,16 +975,22 @@ pub async fn event_sender(
     };
 
     // play next/previous song from queue
-    let change_track = |direction: HeadTo| match direction {
-        HeadTo::Next => state_original.lock().unwrap().player.play_next(),
-        HeadTo::Prev => state_original.lock().unwrap().player.play_prev(),
-        HeadTo::Initial => unreachable!(),
+    // Track changed by user is switched right away without crossfade
+    let change_track = |direction: HeadTo| {
+        let mut state = state_original.lock_recover();
+        match track_direction(direction) {
+            Some(next) => state.change_track(next),
+            None => {
+                state.status = "No pages here..";
+                notifier.notify_all();
+            }
+        }
     };
 
     // navigating page is just changing to fetched_page value to next/prev value
-    // or changing the prev/next track
+    // or changing the prev/next track. Initial goes back to the first page
     let handle_nav = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         let target_index: usize;
         match state.active {
             ui::Window::Musicbar => target_index = MIDDLE_MUSIC_INDEX,
@@ -403,125 +1001,237 @@ pub async fn event_sender(
                 // It implied to change the track
                 return drop_and_call!(state, change_track, direction);
             }
-            ui::Window::Searchbar | ui::Window::Sidebar | ui::Window::Popup(..) => {
+            ui::Window::Searchbar
+            | ui::Window::Sidebar
+            | ui::Window::Popup(..)
+            | ui::Window::Helpbar
+            | ui::Window::Queuebar
+            | ui::Window::Lyrics
+            | ui::Window::Confirm(_)
+            | ui::Window::Menu => {
                 // If none of above windows are active then nothing to navigate.
                 // Early return instead of initilizing `target_index`
                 return;
             }
             ui::Window::None => unreachable!(),
         }
+        // Next page of last page would be empty anyway
+        if matches!(direction, HeadTo::Next) && state.last_page[target_index] {
+            state.status = "No more results..";
+            notifier.notify_all();
+            return;
+        }
+        if matches!(direction, HeadTo::Initial) {
+            state.status = if state.fetched_page[target_index] == Some(0) {
+                "Already on first page.."
+            } else {
+                "First page.."
+            };
+        }
         let page = get_page(&state.fetched_page[target_index], direction);
         state.fetched_page[target_index] = Some(page);
         notifier.notify_all();
     };
 
-    let seek_forward = || {
-        state_original
-            .lock()
-            .unwrap()
-            .player
-            .seek_forward(CONFIG.constants.seek_forward_secs as f64)
-            .ok();
-        notifier.notify_all();
-    };
-
-    let seek_backward = || {
-        state_original
-            .lock()
-            .unwrap()
-            .player
-            .seek_backward(CONFIG.constants.seek_backward_secs as f64)
-            .ok();
+    // Seek forward or backward by `count` steps. Coarse step is used instead of step
+    // configured for each direction when `coarse` is true
+    let seek = |direction: HeadTo, count: u32, coarse: bool| {
+        let mut state = state_original.lock_recover();
+        let step = match direction {
+            _ if coarse => state.coarse_seek_step(),
+            HeadTo::Prev => CONFIG.constants.seek_backward_secs as f64,
+            _ => CONFIG.constants.seek_forward_secs as f64,
+        };
+        let offset = step * count as f64;
+        match direction {
+            HeadTo::Prev => state.seek(-offset),
+            _ => state.seek(offset),
+        }
         notifier.notify_all();
     };
 
     let handle_repeat = || {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
+        let mode = state.playback_behaviour.repeat.next();
         state.player.repeat_nothing();
-        if state.playback_behaviour.repeat {
-            state.player.repeat_one();
-        } else {
-            state.player.repeat_playlist();
-        }
-        state.playback_behaviour.repeat = !state.playback_behaviour.repeat;
+        state.status = match mode {
+            ui::RepeatMode::All => {
+                state.player.repeat_playlist();
+                "Repeat all.."
+            }
+            ui::RepeatMode::One => {
+                state.player.repeat_one();
+                "Repeat one.."
+            }
+            ui::RepeatMode::Nothing => "No repeat..",
+        };
+        state.playback_behaviour.repeat = mode;
         notifier.notify_all();
     };
 
     let toggle_shuffle = || {
-        let mut state = state_original.lock().unwrap();
-        if state.playback_behaviour.shuffle {
-            state.player.unshuffle();
+        state_original.lock_recover().toggle_shuffle();
+        notifier.notify_all();
+    };
+
+    let toggle_auto_advance = || {
+        let mut state = state_original.lock_recover();
+        let enable = !state.playback_behaviour.auto_advance;
+        state.player.auto_advance(enable);
+        state.playback_behaviour.auto_advance = enable;
+        if enable {
+            state.status = "Auto next on..";
         } else {
-            state.player.shuffle();
+            state.status = "Auto next off..";
         }
-        state.playback_behaviour.shuffle = !state.playback_behaviour.shuffle;
+        notifier.notify_all();
+    };
+
+    let toggle_consume_queue = || {
+        let mut state = state_original.lock_recover();
+        state.toggle_consume_queue();
+        notifier.notify_all();
+    };
+
+    // Move to next keybinding profile and show the name of profile now being used
+    let switch_key_profile = || {
+        let mut state = state_original.lock_recover();
+        state.key_profile = (state.key_profile + 1) % CONFIG.key_profile_count();
+        state.status = CONFIG.key_profile(state.key_profile).0;
+        notifier.notify_all();
+    };
+
+    let toggle_ids = || {
+        let mut state = state_original.lock_recover();
+        state.show_ids = !state.show_ids;
         notifier.notify_all();
     };
 
     let toggle_play = || {
-        state_original.lock().unwrap().toggle_pause();
+        state_original.lock_recover().toggle_pause();
         notifier.notify_all();
     };
 
     let handle_download = || async {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
 
         // TODO: Ask for conformation before downloading
-        let mut command = tokio::process::Command::new("youtube-dl");
-        let download_url;
-        if let Some(focused_index) = state.musicbar.1.selected() {
-            let music_id = &state.musicbar.0[focused_index].id;
-            download_url = format!("https://www.youtube.com/watch?v={}", music_id);
-        } else if let Some(focused_index) = state.playlistbar.1.selected() {
-            let playlist_id = &state.playlistbar.0[focused_index].id;
-            download_url = format!("https://www.youtube.com/playlist?list={}", playlist_id);
-        } else {
-            return;
-        }
+        // Marked items are downloaded when any is marked. Otherwise only the focused one
+        let download_urls: Vec<String> = match state.active {
+            ui::Window::Musicbar => state
+                .marked_music()
+                .iter()
+                .map(|music| format!("https://www.youtube.com/watch?v={}", music.id))
+                .collect(),
+            ui::Window::Playlistbar => state
+                .marked_playlists()
+                .iter()
+                .map(|playlist| format!("https://www.youtube.com/playlist?list={}", playlist.id))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let described = match download_urls.as_slice() {
+            [] => return,
+            [download_url] => download_url.clone(),
+            urls => format!("{} items", urls.len()),
+        };
 
         state.status = "Download started..";
         state.active = ui::Window::Popup(
             "Downloading...",
-            format!(
-                "Download of {} have an eye on your Music folder",
-                download_url
-            ),
+            format!("Download of {} have an eye on your Music folder", described),
         );
-        command.arg(download_url);
-
-        command
-            .stdin(std::process::Stdio::null())
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .args(&["--extract-audio", "--audio-format", &CONFIG.download.format])
-            .current_dir(&CONFIG.download.path)
-            .kill_on_drop(false);
+        state.clear_marks(&ui::Window::Musicbar);
+        state.clear_marks(&ui::Window::Playlistbar);
+
+        let mut commands = Vec::with_capacity(download_urls.len());
+        for download_url in download_urls {
+            let mut command = std::process::Command::new("youtube-dl");
+            // Downloader leads its own process group so that pausing it also pauses ffmpeg it
+            // starts. See `State::toggle_pause_downloads`
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+            let mut command = tokio::process::Command::from(command);
+            command.arg(&download_url);
+
+            // Id is always kept in file name so that music with same title do not overwrite each
+            // other
+            command
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .args(["--extract-audio", "--audio-format", &CONFIG.download.format])
+                .args(["--output", "%(title)s-%(id)s.%(ext)s"])
+                .current_dir(&CONFIG.download.path)
+                .kill_on_drop(false);
+            if !CONFIG.constants.proxy_url.is_empty() {
+                command.args(["--proxy", &CONFIG.constants.proxy_url]);
+            }
 
+            let job = ui::DownloadJob::new(download_url);
+            commands.push((command, Arc::clone(&job.state)));
+            state.downloads.push(job);
+        }
+        let downloaders = Arc::clone(&state.downloaders);
         std::mem::drop(state);
 
-        *download_counter.lock().unwrap() += 1;
-        let counter_clone = Arc::clone(&download_counter);
+        for (mut command, job_state) in commands {
+            let downloaders = Arc::clone(&downloaders);
+            // Wait for 5 second just to make sure that command has finished executing.
+            // It usually donot take all those 5 seconds
+            // Anyway, download won't finish before 5 seconds
+            // Then just wait for command to finish by waiting for exit status
+            // decrease the download queue count
+            tokio::task::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                let mut child = match command.spawn() {
+                    Ok(child) => child,
+                    Err(err) => {
+                        log::error!("Cannot start youtube-dl. Error: {err}", err = err);
+                        *job_state.lock().unwrap() = ui::DownloadState::Failed;
+                        return;
+                    }
+                };
+                let group = child.id();
+                {
+                    let mut downloaders = downloaders.lock().unwrap();
+                    let paused = group.is_some_and(|group| downloaders.started(group));
+                    *job_state.lock().unwrap() = if paused {
+                        ui::DownloadState::Paused
+                    } else {
+                        ui::DownloadState::Downloading
+                    };
+                }
+                let finished = child.wait().await.map(|status| status.success());
+                if let Some(group) = group {
+                    downloaders.lock().unwrap().finished(group);
+                }
+                *job_state.lock().unwrap() = match finished {
+                    Ok(true) => ui::DownloadState::Done,
+                    _ => ui::DownloadState::Failed,
+                };
+            });
+        }
+    };
 
-        // Wait for 5 second just to make sure that command has finished executing.
-        // It usually donot take all those 5 seconds
-        // Anyway, download won't finish before 5 seconds
-        // Then just wait for command to finish by waiting for exit status
-        // decrease the download queue count
-        tokio::task::spawn(async move {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-            command.status().await.unwrap();
-            *counter_clone.lock().unwrap() -= 1;
-        });
+    let toggle_pause_downloads = || {
+        state_original.lock_recover().toggle_pause_downloads();
+        notifier.notify_all();
     };
 
     // If play is true it means also play the playlist
     // if is false then only expand the playlist and show url but do not play it
     let select_playlist = |play: bool| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         if let Some(selected_index) = state.playlistbar.1.selected() {
             let playlist_id = state.playlistbar.0[selected_index].id.clone();
             if play {
                 state.activate_playlist(&playlist_id);
+                // Keep showing whatever musicbar had before
+                if state.stay_on_source {
+                    notifier.notify_all();
+                    return;
+                }
             } else {
                 let message = format!(
                     "Playlist url: https://youtu.be/playlist?list={}",
@@ -535,12 +1245,15 @@ pub async fn event_sender(
     };
 
     let select_music = |play: bool| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
         if let Some(selected_index) = state.musicbar.1.selected() {
             let music_id = &state.musicbar.0[selected_index].id;
             if play {
                 let music_id = music_id.clone();
-                state.play_music(&music_id);
+                // Tell why nothing is being played
+                if state.play_music(&music_id).is_err() {
+                    notifier.notify_all();
+                }
             } else {
                 let message = format!("Music url: https://youtu.be/{}", music_id);
                 state.active = ui::Window::Popup("Info!", message);
@@ -549,31 +1262,66 @@ pub async fn event_sender(
         }
     };
 
+    // Add focused music (or all marked music) from musicbar to the play queue
+    let enqueue_music = || {
+        let mut state = state_original.lock_recover();
+        if state.active != ui::Window::Musicbar {
+            return;
+        }
+        let marked = state.marked_music();
+        if marked.is_empty() {
+            state.status = "Nothing selected..";
+        }
+        for music in &marked {
+            state.enqueue_music(music);
+        }
+        state.clear_marks(&ui::Window::Musicbar);
+        notifier.notify_all();
+    };
+
+    let play_all = || {
+        let mut state = state_original.lock_recover();
+        state.play_all();
+        notifier.notify_all();
+    };
+
+    let restore_queue_order = || {
+        state_original.lock_recover().restore_queue_order();
+        notifier.notify_all();
+    };
+
     let change_volume = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
 
         let increase_by = match direction {
             HeadTo::Next => CONFIG.constants.volume_step,
             HeadTo::Prev => CONFIG.constants.volume_step * -1,
-            HeadTo::Initial => 0
+            HeadTo::Initial => 0,
         };
+        state.change_volume(increase_by);
 
-        let res = state.player.change_volume(increase_by);
+        notifier.notify_all();
+    };
 
-        match res {
-            Some(vol) => {
-                state.playback_behaviour.volume = vol;
-            }
-            None => {
-                state.status = "Volume error..";
-            }
+    // Initial resets the speed to normal
+    let change_speed = |direction: HeadTo| {
+        let mut state = state_original.lock_recover();
+        let rate = match direction {
+            HeadTo::Next => state.speed() + ui::utils::SPEED_STEP,
+            HeadTo::Prev => state.speed() - ui::utils::SPEED_STEP,
+            HeadTo::Initial => 1.0,
         };
+        state.set_speed(rate);
+        notifier.notify_all();
+    };
 
+    let toggle_mute = || {
+        state_original.lock_recover().toggle_mute();
         notifier.notify_all();
     };
 
     let handle_view = || {
-        let state = state_original.lock().unwrap();
+        let state = state_original.lock_recover();
         match state.active {
             ui::Window::Playlistbar => {
                 drop_and_call!(state, select_playlist, false);
@@ -586,8 +1334,108 @@ pub async fn event_sender(
         }
     };
 
+    // Open the context menu for item selected in focused list or close it if it is already open
+    let toggle_menu = || {
+        let mut state = state_original.lock_recover();
+        if state.active == ui::Window::Menu {
+            state.close_menu();
+        } else {
+            state.open_menu();
+        }
+        notifier.notify_all();
+    };
+
+    // Close the context menu if it is open and return the action selected in it. Action is then
+    // done by its own handler on the item menu was opened for
+    let choose_menu_action = || {
+        let mut state = state_original.lock_recover();
+        let action = state.close_menu();
+        if action.is_some() {
+            notifier.notify_all();
+        }
+        action
+    };
+
+    // Queue and history cannot be brought back once cleared so they are only cleared after
+    // confirming
+    let clear_queue = || {
+        let mut state = state_original.lock_recover();
+        if state.queue.is_empty() {
+            state.status = "Queue is empty..";
+        } else {
+            state.ask_confirm(ui::ConfirmAction::ClearQueue);
+        }
+        notifier.notify_all();
+    };
+
+    let clear_history = || {
+        state_original
+            .lock_recover()
+            .ask_confirm(ui::ConfirmAction::ClearHistory);
+        notifier.notify_all();
+    };
+
+    let clear_search_results = || {
+        state_original.lock_recover().clear_search_results();
+        notifier.notify_all();
+    };
+
+    let toggle_pane = |window: ui::Window| {
+        state_original.lock_recover().toggle_pane(window);
+        notifier.notify_all();
+    };
+
+    let focus_pane = |window: ui::Window| {
+        state_original.lock_recover().focus_pane(window);
+        notifier.notify_all();
+    };
+
+    let resize_pane = |grow: bool| {
+        state_original.lock_recover().resize_pane(grow);
+        notifier.notify_all();
+    };
+
+    let sort_list = || {
+        state_original.lock_recover().cycle_sort();
+        notifier.notify_all();
+    };
+
+    let refresh_list = || {
+        state_original.lock_recover().refresh_active();
+        notifier.notify_all();
+    };
+
+    let open_in_browser = || {
+        state_original.lock_recover().open_in_browser();
+        notifier.notify_all();
+    };
+
+    // Show content of artist of the music selected in musicbar
+    let goto_artist = || {
+        let found = state_original.lock_recover().goto_artist();
+        if found {
+            fill_music_from_artist(HeadTo::Initial);
+            fill_playlist_from_artist(HeadTo::Initial);
+        } else {
+            notifier.notify_all();
+        }
+    };
+
     let handle_enter = || {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
+        // Save the bookmark if its label is being typed
+        if state.is_bookmarking() && state.save_bookmark() {
+            notifier.notify_all();
+            return;
+        }
+        // While typing filter query, enter only finish the query. Filter is kept as it is
+        if state.is_filtering() {
+            if let Some(filter) = &mut state.filter {
+                filter.editing = false;
+            }
+            notifier.notify_all();
+            return;
+        }
         let active_window = state.active.clone();
         match active_window {
             ui::Window::Sidebar => {
@@ -610,15 +1458,58 @@ pub async fn event_sender(
                     ui::SidebarOption::Following => {
                         drop_and_call!(state, fill_fav_artist, HeadTo::Initial);
                     }
-                    ui::SidebarOption::Search => drop_and_call!(state, activate_search),
+                    ui::SidebarOption::RecentlyPlayed => {
+                        drop_and_call!(state, fill_recent_music, HeadTo::Initial);
+                    }
+                    ui::SidebarOption::MostPlayed => {
+                        drop_and_call!(state, fill_most_played, HeadTo::Initial);
+                    }
+                    ui::SidebarOption::Search => drop_and_call!(
+                        state,
+                        activate_search,
+                        CONFIG.constants.sidebar_search_prefill
+                    ),
                 }
             }
+            // Youtube link pasted in searchbar opens what it points to instead of being searched
             ui::Window::Searchbar => {
-                drop_and_call!(state, start_search);
+                let query = state.search.0.trim().to_string();
+                state.save_search(&query);
+                state.live_searched = false;
+                match ui::link::parse_link(&query) {
+                    Some(ui::link::Link::Playlist(playlist_id)) => {
+                        state.search_typed_at = None;
+                        state.filled_source.0 = ui::MusicbarSource::Playlist(playlist_id);
+                        drop_and_call!(state, fill_music_from_playlist, HeadTo::Initial);
+                    }
+                    Some(ui::link::Link::Music(music_id)) => {
+                        state.search_typed_at = None;
+                        // Toast telling why is shown by load_music itself
+                        if state.load_music(&music_id).is_err() {
+                            state.status = "Err playing link..";
+                        }
+                        notifier.notify_all();
+                    }
+                    Some(ui::link::Link::Artist(artist_id)) => {
+                        state.search_typed_at = None;
+                        state.filled_source.0 = ui::MusicbarSource::Artist(artist_id.clone());
+                        state.filled_source.1 = ui::PlaylistbarSource::Artist(artist_id);
+                        std::mem::drop(state);
+                        fill_music_from_artist(HeadTo::Initial);
+                        fill_playlist_from_artist(HeadTo::Initial);
+                    }
+                    None => drop_and_call!(state, start_search),
+                }
             }
 
-            // On enter play the music
-            ui::Window::Musicbar => drop_and_call!(state, select_music, true),
+            // On enter play the music or queue it if configured so
+            ui::Window::Musicbar => {
+                if CONFIG.constants.enter_enqueues {
+                    drop_and_call!(state, enqueue_music);
+                } else {
+                    drop_and_call!(state, select_music, true);
+                }
+            }
 
             // On enter selection view the playlist content as well as play it
             ui::Window::Playlistbar => drop_and_call!(state, select_playlist, true),
@@ -633,154 +1524,583 @@ pub async fn event_sender(
                     fill_playlist_from_artist(HeadTo::Initial);
                 }
             }
-            ui::Window::None | ui::Window::BottomControl | ui::Window::Popup(..) => {}
+            ui::Window::None
+            | ui::Window::BottomControl
+            | ui::Window::Popup(..)
+            | ui::Window::Helpbar
+            | ui::Window::Queuebar
+            | ui::Window::Lyrics
+            | ui::Window::Confirm(_)
+            // Action chosen from menu is done before reaching here. See `choose_menu_action`
+            | ui::Window::Menu => {}
         }
     };
 
     let handle_favourates = |add: bool| {
-        let mut state = state_original.lock().unwrap();
+        let mut state = state_original.lock_recover();
 
         state.status = "Processing..";
 
-        match state.active {
+        // Items are cloned as adding/removing may also change the list they are taken from
+        let is_empty = match state.active {
             ui::Window::Musicbar => {
-                if let Some(selected_index) = state.musicbar.1.selected() {
-                    // Why clone? Used unsafe to bypass borrow as immutable and mutable at same time error.
-                    // yeah. I am sure this is fine (up until now).
-                    let selected_music =
-                        &state.musicbar.0[selected_index] as *const fetcher::MusicUnit;
+                let marked = state.marked_music();
+                for music in &marked {
                     if add {
-                        state.add_music_to_favourates(unsafe { &*selected_music });
+                        state.add_music_to_favourates(music);
                     } else {
-                        state.remove_music_from_favourates(unsafe { &*selected_music });
+                        state.remove_music_from_favourates(music);
                     }
-                } else {
-                    state.status = "Nothing selected..";
                 }
+                marked.is_empty()
             }
 
             ui::Window::Playlistbar => {
-                if let Some(selected_index) = state.playlistbar.1.selected() {
-                    let selected_playlist =
-                        &state.playlistbar.0[selected_index] as *const fetcher::PlaylistUnit;
+                let marked = state.marked_playlists();
+                for playlist in &marked {
                     if add {
-                        state.add_playlist_to_favourates(unsafe { &*selected_playlist });
+                        state.add_playlist_to_favourates(playlist);
                     } else {
-                        state.remove_playlist_from_favourates(unsafe { &*selected_playlist });
+                        state.remove_playlist_from_favourates(playlist);
                     }
-                } else {
-                    state.status = "Nothing selected..";
                 }
+                marked.is_empty()
             }
 
             ui::Window::Artistbar => {
-                if let Some(selected_index) = state.artistbar.1.selected() {
-                    let selected_artist =
-                        &state.artistbar.0[selected_index] as *const fetcher::ArtistUnit;
+                let marked = state.marked_artists();
+                for artist in &marked {
                     if add {
-                        state.add_artist_to_favourates(unsafe { &*selected_artist });
+                        state.add_artist_to_favourates(artist);
                     } else {
-                        state.remove_artist_from_favourates(unsafe { &*selected_artist });
+                        state.remove_artist_from_favourates(artist);
                     }
-                } else {
-                    state.status = "Nothing selected..";
                 }
+                marked.is_empty()
             }
-            _ => {}
+            _ => false,
+        };
+        if is_empty {
+            state.status = "Nothing selected..";
         }
+        let active = state.active.clone();
+        state.clear_marks(&active);
 
         notifier.notify_all();
     };
 
+    let save_playlist = || {
+        let mut state = state_original.lock_recover();
+        state.save_viewed_playlist();
+        notifier.notify_all();
+    };
+
+    // Play the focused music right away whatever <ENTER> does in musicbar. In other list this is
+    // same as <ENTER>
+    let play_now = || {
+        let in_musicbar = state_original.lock_recover().active == ui::Window::Musicbar;
+        if in_musicbar {
+            select_music(true);
+        } else {
+            handle_enter();
+        }
+    };
+
+    // Clicking on item of list focus that list and select the item. Clicking on item that is
+    // already selected is same as pressing enter so double click on any item also selects it.
+    // Scrolling over a list is same as pressing up/down in that list.
+    // Mouse is ignored while help, queue or popup is shown and outside of lists
+    let handle_mouse = |mouse: MouseEvent| {
+        let direction = match mouse.kind {
+            MouseEventKind::ScrollDown => Some(HeadTo::Next),
+            MouseEventKind::ScrollUp => Some(HeadTo::Prev),
+            MouseEventKind::Down(MouseButton::Left) => None,
+            _ => return,
+        };
+        let mut state = state_original.lock_recover();
+        if state.active.is_overlay() {
+            return;
+        }
+        let (window, index) = match state.list_at(mouse.column, mouse.row) {
+            Some(hit) => hit,
+            None => return,
+        };
+        state.mark_lists_dirty();
+
+        if let Some(direction) = direction {
+            state.active = window;
+            drop_and_call!(state, handle_up_down, direction);
+            return;
+        }
+
+        let was_selected = state.active == window
+            && index.is_some()
+            && index
+                == match window {
+                    ui::Window::Sidebar => state.sidebar.selected(),
+                    ui::Window::Musicbar => state.musicbar.1.selected(),
+                    ui::Window::Playlistbar => state.playlistbar.1.selected(),
+                    ui::Window::Artistbar => state.artistbar.1.selected(),
+                    _ => None,
+                };
+        state.active = window.clone();
+        if was_selected {
+            drop_and_call!(state, handle_enter);
+            return;
+        }
+        // Clicking on border, header or empty area of list only focus that list
+        if let Some(index) = index {
+            match window {
+                ui::Window::Sidebar => state.sidebar.select(Some(index)),
+                ui::Window::Musicbar => {
+                    state.musicbar.1.select(Some(index));
+                    state.select_for_playing();
+                }
+                ui::Window::Playlistbar => state.playlistbar.1.select(Some(index)),
+                ui::Window::Artistbar => state.artistbar.1.select(Some(index)),
+                _ => {}
+            }
+        }
+        notifier.notify_all();
+    };
+
+    // Event that was read ahead while counting repeated key. See `count_repeats`
+    let mut pending_event: Option<Event> = None;
+    // Terminal errors in a row. See `next_input`
+    let mut input_errors = 0;
+
     'listener_loop: loop {
-        if event::poll(Duration::from_millis(CONFIG.constants.refresh_rate)).unwrap() {
-            match event::read().unwrap() {
+        // Wait for input only until something is due in the idle branch below
+        let poll_timeout = state_original.lock_recover().poll_timeout();
+        let next_event = match pending_event.take() {
+            Some(event) => Some(event),
+            None => next_input(poll_timeout, &mut input_errors, event::poll, event::read),
+        };
+        if input_errors >= MAX_INPUT_ERRORS {
+            log::error!("Terminal keeps failing. Quitting..");
+            quit(true);
+            break 'listener_loop;
+        }
+        if let Some(next_event) = next_event {
+            log::trace!("Terminal event: {:?}", next_event);
+            {
+                let mut state = state_original.lock_recover();
+                state.saw_input();
+                state.remember_pane();
+            }
+            match next_event {
                 Event::Key(key) => {
                     let is_with_control = key.modifiers.contains(KeyModifiers::CONTROL);
+                    let is_with_alt = key.modifiers.contains(KeyModifiers::ALT);
+                    let (quit_armed, motion) = {
+                        let mut state = state_original.lock_recover();
+                        // Any key may change what is shown in lists so draw all of them again
+                        state.mark_lists_dirty();
+                        // Motion is only kept while it is being typed. Any other key uses or
+                        // cancels it
+                        state.expire_motion();
+                        let motion = std::mem::take(&mut state.motion);
+                        if !motion.is_empty() {
+                            notifier.notify_all();
+                        }
+                        // Only the key right after leaving searchbar can use this
+                        (std::mem::take(&mut state.quit_armed), motion)
+                    };
 
                     match key.code {
-                        KeyCode::Down | KeyCode::PageDown => {
+                        KeyCode::Down | KeyCode::PageDown
+                            if !(is_with_control && move_queued(HeadTo::Next)) =>
+                        {
                             handle_up_down(HeadTo::Next);
                         }
-                        KeyCode::Up | KeyCode::PageUp => {
+                        KeyCode::Up | KeyCode::PageUp
+                            if !(is_with_control && move_queued(HeadTo::Prev)) =>
+                        {
                             handle_up_down(HeadTo::Prev);
                         }
+                        KeyCode::Home => {
+                            jump_in_list(0);
+                        }
+                        KeyCode::End => {
+                            jump_in_list(usize::MAX);
+                        }
                         KeyCode::Right | KeyCode::Tab => {
                             moveto_next_window();
                         }
                         KeyCode::Left | KeyCode::BackTab => {
                             moveto_prev_window();
                         }
-                        KeyCode::Esc => {
+                        // Esc only cancels the motion being typed
+                        KeyCode::Esc if motion.is_empty() => {
                             handle_esc();
                         }
-                        KeyCode::Enter => {
-                            handle_enter();
-                        }
-                        KeyCode::Backspace | KeyCode::Delete => {
+                        KeyCode::Enter => match motion.count {
+                            // Typed number starts from 1
+                            Some(number) => jump_in_list(number.saturating_sub(1)),
+                            None => match choose_menu_action() {
+                                Some(ui::menu::MenuAction::Play) => play_now(),
+                                Some(ui::menu::MenuAction::Enqueue) => enqueue_music(),
+                                Some(ui::menu::MenuAction::AddFavourite) => handle_favourates(true),
+                                Some(ui::menu::MenuAction::RemoveFavourite) => {
+                                    handle_favourates(false)
+                                }
+                                Some(ui::menu::MenuAction::Download) => handle_download().await,
+                                Some(ui::menu::MenuAction::ShowUrl) => handle_view(),
+                                Some(ui::menu::MenuAction::GotoArtist) => goto_artist(),
+                                None => handle_enter(),
+                            },
+                        },
+                        KeyCode::Backspace => {
                             handle_backspace();
                         }
+                        KeyCode::Delete => {
+                            handle_delete();
+                        }
                         KeyCode::Char(ch) => {
-                            /* If searchbar is active register every char key as input term */
-                            if state_original.lock().unwrap().active == ui::Window::Searchbar {
-                                handle_search_input(ch);
+                            let (
+                                is_confirming,
+                                in_menu,
+                                is_searching,
+                                is_filtering,
+                                is_bookmarking,
+                                key_map,
+                            ) = {
+                                let state = state_original.lock_recover();
+                                (
+                                    matches!(state.active, ui::Window::Confirm(_)),
+                                    state.active == ui::Window::Menu,
+                                    state.active == ui::Window::Searchbar,
+                                    state.is_filtering(),
+                                    state.is_bookmarking(),
+                                    CONFIG.key_map(state.key_profile),
+                                )
+                            };
+                            let action = key_map.action(KeyCombo {
+                                key: ch,
+                                ctrl: is_with_control,
+                                alt: is_with_alt,
+                            });
+                            let key_of = |action: Action| {
+                                key_map.combo(action).map_or('\0', |combo| combo.key)
+                            };
+
+                            if is_quit_key(
+                                action,
+                                ch,
+                                is_searching || is_filtering || is_bookmarking,
+                                quit_armed,
+                                key_of(Action::Quit),
+                            ) {
+                                if quit(action == Some(Action::ForceQuit)) {
+                                    break 'listener_loop;
+                                }
                             }
-                            // Now as this is not the input, call the shortcuts action if this key
-                            // is defined in shortcuts
-                            else if ch == CONFIG.shortcut_keys.start_search {
-                                activate_search();
-                            } else if ch == CONFIG.shortcut_keys.toggle_play {
-                                toggle_play();
-                            } else if ch == CONFIG.shortcut_keys.repeat {
-                                handle_repeat();
-                            } else if ch == CONFIG.shortcut_keys.suffle {
-                                toggle_shuffle();
-                            } else if ch == CONFIG.shortcut_keys.forward {
-                                seek_forward();
-                            } else if ch == CONFIG.shortcut_keys.backward {
-                                seek_backward();
-                            } else if ch == CONFIG.shortcut_keys.view {
-                                handle_view();
-                            } else if ch == CONFIG.shortcut_keys.favourates_add {
-                                handle_favourates(true);
-                            } else if ch == CONFIG.shortcut_keys.favourates_remove {
-                                handle_favourates(false);
-                            } else if ch == CONFIG.shortcut_keys.prev {
-                                if is_with_control {
-                                    change_track(HeadTo::Prev);
-                                } else {
-                                    handle_nav(HeadTo::Prev);
+                            // Confirm window only takes the answer. Every other key is ignored
+                            else if is_confirming {
+                                let answer = ch.to_ascii_lowercase();
+                                if (answer == 'y' || answer == 'n') && handle_confirm(answer == 'y')
+                                {
+                                    break 'listener_loop;
                                 }
-                            } else if ch == CONFIG.shortcut_keys.next {
-                                if is_with_control {
-                                    change_track(HeadTo::Next);
-                                } else {
-                                    handle_nav(HeadTo::Next);
+                            }
+                            // Menu is only navigated with Up/Down and Enter. Menu key closes it
+                            else if in_menu {
+                                if action == Some(Action::ContextMenu) {
+                                    toggle_menu();
                                 }
-                            } else if ch == CONFIG.shortcut_keys.download && is_with_control {
-                                handle_download().await;
-                            } else if ch == CONFIG.shortcut_keys.vol_increase {
-                                change_volume(HeadTo::Next);
-                            } else if ch == CONFIG.shortcut_keys.vol_decrease {
-                                change_volume(HeadTo::Prev);
-                            } else if ch == CONFIG.shortcut_keys.quit && is_with_control {
-                                let force_quit = key.modifiers.contains(KeyModifiers::ALT);
-                                if quit(force_quit) {
-                                    break 'listener_loop;
+                            }
+                            /* If searchbar is active register every char key as input term */
+                            else if is_searching {
+                                handle_search_input(ch);
+                            }
+                            // Same as with searchbar when filter query is being typed
+                            else if is_filtering {
+                                handle_filter_input(ch);
+                            }
+                            // Bookmark popup takes label or the number of bookmark to select
+                            else if is_bookmarking {
+                                handle_bookmark_input(ch, key_of(Action::Bookmarks));
+                            }
+                            // Now as this is not the input, do the action bound to this key.
+                            // Digit that is not bound to anything is the count of motion
+                            else if let Some(action) = action {
+                                match action {
+                                    Action::Help => toggle_help(),
+                                    Action::StartSearch => activate_search(false),
+                                    Action::EditSearch => activate_search(true),
+                                    Action::SearchScope => cycle_search_scope(),
+                                    Action::SearchFocused => toggle_search_focused(),
+                                    Action::TogglePlay => toggle_play(),
+                                    Action::Next => handle_nav(HeadTo::Next),
+                                    Action::Prev => handle_nav(HeadTo::Prev),
+                                    Action::PlayNext => change_track(HeadTo::Next),
+                                    Action::PlayPrev => change_track(HeadTo::Prev),
+                                    Action::FirstPage => handle_nav(HeadTo::Initial),
+                                    Action::Forward => {
+                                        let count = count_repeats(key, &mut pending_event);
+                                        seek(HeadTo::Next, count, is_with_control);
+                                    }
+                                    Action::Backward => {
+                                        let count = count_repeats(key, &mut pending_event);
+                                        seek(HeadTo::Prev, count, is_with_control);
+                                    }
+                                    Action::Repeat => handle_repeat(),
+                                    Action::Suffle => toggle_shuffle(),
+                                    Action::AutoAdvance => toggle_auto_advance(),
+                                    Action::ConsumeQueue => toggle_consume_queue(),
+                                    Action::VolIncrease => change_volume(HeadTo::Next),
+                                    Action::VolDecrease => change_volume(HeadTo::Prev),
+                                    Action::Mute => toggle_mute(),
+                                    Action::SpeedUp => change_speed(HeadTo::Next),
+                                    Action::SpeedDown => change_speed(HeadTo::Prev),
+                                    Action::SpeedReset => change_speed(HeadTo::Initial),
+                                    Action::CycleAudio => cycle_audio_track(),
+                                    Action::AudioDevice => cycle_output_device(),
+                                    Action::SleepTimer => cycle_sleep_timer(),
+                                    Action::FavouratesAdd => handle_favourates(true),
+                                    Action::FavouratesRemove => handle_favourates(false),
+                                    Action::SavePlaylist => save_playlist(),
+                                    Action::View => handle_view(),
+                                    Action::OpenBrowser => open_in_browser(),
+                                    Action::Refresh => refresh_list(),
+                                    Action::ClearQueue => clear_queue(),
+                                    Action::ClearHistory => clear_history(),
+                                    Action::ClearResults => clear_search_results(),
+                                    Action::ToggleMusicbar => toggle_pane(ui::Window::Musicbar),
+                                    Action::TogglePlaylistbar => {
+                                        toggle_pane(ui::Window::Playlistbar)
+                                    }
+                                    Action::ToggleArtistbar => toggle_pane(ui::Window::Artistbar),
+                                    // While count of motion is being typed the key is taken as
+                                    // part of the count instead
+                                    Action::FocusSidebar
+                                    | Action::FocusMusicbar
+                                    | Action::FocusPlaylistbar
+                                    | Action::FocusArtistbar
+                                    | Action::FocusSearchbar => {
+                                        match (ch.to_digit(10), motion.is_empty()) {
+                                            (Some(digit), false) => {
+                                                continue_motion(motion.with_digit(digit))
+                                            }
+                                            _ => {
+                                                if let Some(window) =
+                                                    ui::panes::focus_window_of(action)
+                                                {
+                                                    focus_pane(window);
+                                                }
+                                            }
+                                        }
+                                    }
+                                    Action::GrowPane => resize_pane(true),
+                                    Action::ShrinkPane => resize_pane(false),
+                                    Action::SortList => sort_list(),
+                                    Action::Download => handle_download().await,
+                                    Action::PauseDownloads => toggle_pause_downloads(),
+                                    Action::PlayNow => play_now(),
+                                    Action::Enqueue => enqueue_music(),
+                                    Action::PlayAll => play_all(),
+                                    Action::ContextMenu => toggle_menu(),
+                                    Action::Mark => toggle_mark(),
+                                    Action::GotoPlaying => goto_playing(),
+                                    Action::GotoArtist => goto_artist(),
+                                    Action::Radio => toggle_radio(),
+                                    Action::ExportList => export_list(),
+                                    Action::ImportList => import_list(),
+                                    Action::Lyrics => toggle_lyrics(),
+                                    Action::RestoreQueue => restore_queue_order(),
+                                    Action::ShowQueue => toggle_queue(),
+                                    Action::Filter => start_filter(),
+                                    Action::FuzzyFilter => toggle_filter_mode(),
+                                    Action::AddBookmark => start_bookmark(),
+                                    Action::Bookmarks => list_bookmarks(),
+                                    Action::PlayOnSelect => toggle_play_on_select(),
+                                    Action::StayOnSource => toggle_stay_on_source(),
+                                    Action::ToggleIds => toggle_ids(),
+                                    Action::SwitchProfile => switch_key_profile(),
+                                    Action::CacheInfo => handle_cache_info(),
+                                    Action::ListTop => {
+                                        // Pressed twice in a row. Count is the position
+                                        // starting from 1
+                                        if motion.operator == Some(ch) {
+                                            jump_in_list(
+                                                motion.count.unwrap_or(1).saturating_sub(1),
+                                            );
+                                        } else {
+                                            continue_motion(motion.with_operator(ch));
+                                        }
+                                    }
+                                    Action::ListBottom => jump_in_list(
+                                        motion.count.map_or(usize::MAX, |n| n.saturating_sub(1)),
+                                    ),
+                                    Action::ListDown => move_by(motion.count, HeadTo::Next),
+                                    Action::ListUp => move_by(motion.count, HeadTo::Prev),
+                                    Action::ListDelete => {
+                                        if motion.operator == Some(ch) {
+                                            delete_rows(motion.count.unwrap_or(1));
+                                        } else {
+                                            continue_motion(motion.with_operator(ch));
+                                        }
+                                    }
+                                    // Quit is done above as it is also taken in searchbar
+                                    Action::Quit | Action::ForceQuit => {}
                                 }
+                            } else if let Some(digit) = ch.to_digit(10) {
+                                continue_motion(motion.with_digit(digit));
                             }
                         }
                         _ => {}
                     }
                 }
                 Event::Resize(..) => {
-                    // just update the layout
+                    // just update the layout. Layout is recalculated in painter when it sees the
+                    // new dimension so nothing in state is touched here. Any fetch in progress,
+                    // status message or playback state is kept as it is.
                     notifier.notify_all();
                 }
-                Event::Mouse(..) => {}
+                Event::Mouse(mouse) => handle_mouse(mouse),
             }
         } else {
-            notifier.notify_all();
+            let mut state = state_original.lock_recover();
+            // Typing in searchbar is paused in live search mode
+            if state.take_pending_search() {
+                state.live_searched = true;
+                drop_and_call!(state, start_search);
+                continue 'listener_loop;
+            }
+            // Pick up the change in queue made by other instances
+            if state.sync_queue() {
+                state.dirty = true;
+            }
+            // Sleep timer is checked against the time it should end so how often this branch is
+            // reached do not change when it ends
+            if state.take_sleep_timer() {
+                if CONFIG.constants.sleep_timer_quit {
+                    std::mem::drop(state);
+                    if quit(false) {
+                        break 'listener_loop;
+                    }
+                    continue 'listener_loop;
+                }
+                state.dirty = true;
+            }
+            // Notify about music that have been playing for a while
+            state.notify_track_change();
+            // Tell about downloads that have finished since last time
+            if state.report_downloads() {
+                state.dirty = true;
+            }
+            if state.report_browser() {
+                state.dirty = true;
+            }
+            if state.report_export() {
+                state.dirty = true;
+            }
+            if state.expire_osd() {
+                state.dirty = true;
+            }
+            if state.expire_toast() {
+                state.dirty = true;
+            }
+            if state.expire_motion() {
+                state.dirty = true;
+            }
+            // Spinner only moves while something is being fetched
+            if state.spinner().is_some() {
+                state.dirty = true;
+            }
+            // Ask for related music when radio is about to run out of music. It is queued by
+            // communicator so that play_queue picks it up afterwards
+            if state.check_radio() {
+                state.dirty = true;
+            }
+            // Fall back to default device when chosen one is unplugged
+            if state.check_output_device() {
+                state.dirty = true;
+            }
+            // Retry or skip the music mpv could not load
+            if state.check_playback() {
+                state.dirty = true;
+            }
+            // Keep first music of queue as next entry of mpv playlist
+            if state.play_queue() {
+                state.dirty = true;
+            }
+            // This branch is only reached when no key is pressed for a while so selection is not
+            // being moved anymore
+            if state.play_pending_selection() {
+                state.dirty = true;
+            }
+            // When nothing is playing or playback is paused, nothing on screen changes. Do not
+            // wake up painter and communicator in such case
+            if state.take_dirty() {
+                std::mem::drop(state);
+                notifier.notify_all();
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_with_and_without_wrap() {
+        assert_eq!(advance_index(4, 5, HeadTo::Next, true), 0);
+        assert_eq!(advance_index(0, 5, HeadTo::Prev, true), 4);
+        assert_eq!(advance_index(4, 5, HeadTo::Next, false), 4);
+        assert_eq!(advance_index(0, 5, HeadTo::Prev, false), 0);
+        assert_eq!(advance_index(2, 5, HeadTo::Next, false), 3);
+        // Selection past the end of list that has shrunk comes back to last item
+        assert_eq!(advance_index(9, 5, HeadTo::Prev, false), 4);
+        assert_eq!(advance_index(0, 0, HeadTo::Next, false), 0);
+    }
+
+    #[test]
+    fn first_page_is_not_a_track() {
+        assert_eq!(track_direction(HeadTo::Next), Some(true));
+        assert_eq!(track_direction(HeadTo::Prev), Some(false));
+        assert_eq!(track_direction(HeadTo::Initial), None);
+    }
+
+    #[test]
+    fn quit_key_typed_in_input_never_quit() {
+        assert!(!is_quit_key(None, 'c', true, false, 'c'));
+        assert!(!is_quit_key(Some(Action::Quit), 'c', true, false, 'c'));
+        assert!(!is_quit_key(None, 'c', true, true, 'c'));
+    }
+
+    #[test]
+    fn quit_outside_input() {
+        assert!(is_quit_key(Some(Action::Quit), 'c', false, false, 'c'));
+        assert!(is_quit_key(Some(Action::ForceQuit), 'c', false, false, 'c'));
+        assert!(!is_quit_key(None, 'c', false, false, 'c'));
+        // Just after leaving searchbar with ESC
+        assert!(is_quit_key(None, 'c', false, true, 'c'));
+        assert!(!is_quit_key(None, 'x', false, true, 'c'));
+    }
+
+    #[test]
+    fn poll_error_keeps_listening() {
+        let failed_poll = |_| Err(std::io::Error::other("poll"));
+        let unread = || -> crossterm::Result<Event> { unreachable!() };
+        let mut errors = 0;
+        assert!(next_input(Duration::ZERO, &mut errors, failed_poll, unread).is_none());
+        assert_eq!(errors, 1);
+        assert!(errors < MAX_INPUT_ERRORS);
+
+        // Error while reading after successful poll is same as poll error
+        let failed_read = || Err(std::io::Error::other("read"));
+        assert!(next_input(Duration::ZERO, &mut errors, |_| Ok(true), failed_read).is_none());
+        assert_eq!(errors, 2);
+
+        // Terminal working again forgets previous errors
+        let resized = || Ok(Event::Resize(10, 10));
+        let event = next_input(Duration::ZERO, &mut errors, |_| Ok(true), resized);
+        assert!(matches!(event, Some(Event::Resize(10, 10))));
+        assert_eq!(errors, 0);
+
+        assert!(next_input(Duration::ZERO, &mut errors, |_| Ok(false), unread).is_none());
+        assert_eq!(errors, 0);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::ui;
+
+// Method used to check weather an item in list matches the filter query
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FilterMode {
+    // Item matches when query is found as it is in item text. List order is kept as it is
+    Substring,
+    // Item matches when all character of query appears in item text in same order (but not
+    // necessarily next to each other). Matched items are ranked by how well they match
+    Fuzzy,
+}
+
+// Filter applied to the list of one of musicbar/playlistbar/artistbar
+pub struct ListFilter {
+    // Window whose list is being filtered
+    pub window: ui::Window,
+    // Text typed by user to filter the list with
+    pub query: String,
+    // true while user is still typing the query. Character keys are taken as input on this state
+    pub editing: bool,
+}
+
+// Text of list item against which filter query is matched
+pub trait Filterable {
+    fn filter_text(&self) -> String;
+}
+
+impl Filterable for fetcher::MusicUnit {
+    fn filter_text(&self) -> String {
+        format!("{} {}", self.name, self.artist)
+    }
+}
+
+impl Filterable for fetcher::PlaylistUnit {
+    fn filter_text(&self) -> String {
+        format!("{} {}", self.name, self.author)
+    }
+}
+
+impl Filterable for fetcher::ArtistUnit {
+    fn filter_text(&self) -> String {
+        self.name.clone()
+    }
+}
+
+// Returns None if text do not match the query. Otherwise return the score of match where higher
+// score means better match. Matching is always case insensitive
+pub fn score(mode: FilterMode, query: &str, text: &str) -> Option<i64> {
+    let query = query.to_lowercase();
+    let text = text.to_lowercase();
+
+    match mode {
+        FilterMode::Substring => text.find(&query).map(|_| 0),
+        FilterMode::Fuzzy => {
+            let mut query_chars = query.chars().peekable();
+            let mut score = 0;
+            let mut prev_matched = false;
+            let mut prev_char = ' ';
+
+            for ch in text.chars() {
+                match query_chars.peek() {
+                    None => break,
+                    Some(wanted) if *wanted == ch => {
+                        query_chars.next();
+                        score += 1;
+                        // Consecutive matches and matches at the start of word are given more
+                        // weight so that "lofi" ranks "lofi beats" above "love finder"
+                        if prev_matched {
+                            score += 5;
+                        }
+                        if !prev_char.is_alphanumeric() {
+                            score += 3;
+                        }
+                        prev_matched = true;
+                    }
+                    Some(_) => {
+                        prev_matched = false;
+                    }
+                }
+                prev_char = ch;
+            }
+
+            if query_chars.peek().is_none() {
+                Some(score)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// Returns the items that matches the query. In fuzzy mode best match comes first and items with
+// same score keeps their original order
+pub fn apply<T: Filterable + Clone>(items: &[T], query: &str, mode: FilterMode) -> Vec<T> {
+    let mut matched = items
+        .iter()
+        .filter_map(|item| score(mode, query, &item.filter_text()).map(|score| (score, item)))
+        .collect::<Vec<_>>();
+    if mode == FilterMode::Fuzzy {
+        matched.sort_by_key(|(score, _)| -score);
+    }
+
+    matched.into_iter().map(|(_, item)| item.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substring_match() {
+        assert!(score(FilterMode::Substring, "beat", "Lofi Beats").is_some());
+        assert!(score(FilterMode::Substring, "lbeat", "Lofi Beats").is_none());
+    }
+
+    #[test]
+    fn fuzzy_match_and_rank() {
+        assert!(score(FilterMode::Fuzzy, "lbeat", "Lofi Beats").is_some());
+        assert!(score(FilterMode::Fuzzy, "beatl", "Lofi Beats").is_none());
+
+        let items = vec![
+            fetcher::ArtistUnit {
+                name: "love finder".to_string(),
+                id: "1".to_string(),
+                video_count: "0".to_string(),
+            },
+            fetcher::ArtistUnit {
+                name: "lofi beats".to_string(),
+                id: "2".to_string(),
+                video_count: "0".to_string(),
+            },
+        ];
+        let filtered = apply(&items, "lofi", FilterMode::Fuzzy);
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].id, "2");
+    }
+}
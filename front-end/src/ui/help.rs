@@ -0,0 +1,179 @@
+use crate::ui::{
+    self,
+    shared_import::*,
+    utils::{ExtendBlock, ExtendStyle},
+};
+use config::{
+    initilize::CONFIG,
+    keymap::{Action, KeyMap},
+};
+
+pub const TITLE: &str = "Help (Up/Down to scroll) ";
+
+// What each action does as shown in help. Every action has to be described here so help cannot
+// miss any shortcut
+fn describe(action: Action) -> &'static str {
+    match action {
+        Action::Help => "Show/hide this help",
+        Action::StartSearch => "Type in searchbar",
+        Action::EditSearch => "Edit last searched query",
+        Action::SearchScope => "Cycle search all/music/playlist/artist",
+        Action::SearchFocused => "Search all/only focused list",
+        Action::TogglePlay => "Pause/unpause the playback",
+        Action::Next => "Next page of focused list",
+        Action::Prev => "Previous page of focused list",
+        Action::PlayNext => "Play next track",
+        Action::PlayPrev => "Play previous track",
+        Action::FirstPage => "First page of focused list",
+        Action::Forward => "Seek forward (larger step with <CTRL>)",
+        Action::Backward => "Seek backward (larger step with <CTRL>)",
+        Action::Repeat => "Cycle repeat all/one/off",
+        Action::Suffle => "Shuffle/unshuffle",
+        Action::AutoAdvance => "Toggle auto play of next track",
+        Action::ConsumeQueue => "Toggle removing played track from queue",
+        Action::VolIncrease => "Increase volume",
+        Action::VolDecrease => "Decrease volume",
+        Action::Mute => "Mute/unmute",
+        Action::SpeedUp => "Play faster",
+        Action::SpeedDown => "Play slower",
+        Action::SpeedReset => "Play at normal speed",
+        Action::CycleAudio => "Next audio track/language",
+        Action::AudioDevice => "Next audio output device",
+        Action::SleepTimer => "Cycle sleep timer 15/30/60 min/off",
+        Action::FavouratesAdd => "Add focused item to favourates",
+        Action::FavouratesRemove => "Remove focused item from favourates",
+        Action::SavePlaylist => "Add playlist shown in musicbar to favourates",
+        Action::View => "Show url of focused item",
+        Action::OpenBrowser => "Open focused item in browser",
+        Action::Refresh => "Fetch focused list again",
+        Action::ClearQueue => "Clear play queue",
+        Action::ClearHistory => "Clear recently played",
+        Action::ClearResults => "Clear search results",
+        Action::ToggleMusicbar => "Hide/show musicbar",
+        Action::TogglePlaylistbar => "Hide/show playlistbar",
+        Action::ToggleArtistbar => "Hide/show artistbar",
+        Action::FocusSidebar => "Focus sidebar",
+        Action::FocusMusicbar => "Focus musicbar",
+        Action::FocusPlaylistbar => "Focus playlistbar",
+        Action::FocusArtistbar => "Focus artistbar",
+        Action::FocusSearchbar => "Focus searchbar",
+        Action::GrowPane => "Make focused list bigger",
+        Action::ShrinkPane => "Make focused list smaller",
+        Action::SortList => "Sort focused list",
+        Action::Download => "Download focused music/playlist",
+        Action::PauseDownloads => "Pause/resume all downloads",
+        Action::PlayNow => "Play focused music right away",
+        Action::Enqueue => "Add focused music to queue",
+        Action::PlayAll => "Play whole musicbar through queue",
+        Action::ContextMenu => "Show actions for focused item",
+        Action::Mark => "Mark/unmark focused item",
+        Action::GotoPlaying => "Select playing music in musicbar",
+        Action::GotoArtist => "Show artist of selected music",
+        Action::Radio => "Toggle radio of related music",
+        Action::ExportList => "Export musicbar or queue to file",
+        Action::ImportList => "Queue music of last exported file",
+        Action::Lyrics => "Show/hide lyrics of playing music",
+        Action::RestoreQueue => "Restore queue to order items were added",
+        Action::ShowQueue => "Show/reorder the play queue",
+        Action::Filter => "Filter focused list",
+        Action::FuzzyFilter => "Switch substring/fuzzy filter",
+        Action::AddBookmark => "Bookmark current position",
+        Action::Bookmarks => "List/jump to bookmarks",
+        Action::PlayOnSelect => "Toggle play on select",
+        Action::StayOnSource => "Toggle staying on list after play",
+        Action::ToggleIds => "Show youtube ids in lists",
+        Action::SwitchProfile => "Switch keybinding profile",
+        Action::CacheInfo => "Show/clear result cache",
+        Action::Quit => "Quit",
+        Action::ForceQuit => "Quit without asking to confirm",
+        Action::ListTop => "Select first item of list (press twice)",
+        Action::ListBottom => "Select last item of list",
+        Action::ListDown => "Move down in list",
+        Action::ListUp => "Move up in list",
+        Action::ListDelete => "Remove from favourites/recents/queue (press twice)",
+    }
+}
+
+// Every action of the key map with its key followed by keys that cannot be changed. Help window
+// is built from this list
+pub fn shortcut_list(key_map: &KeyMap) -> Vec<(String, &'static str)> {
+    let mut list = key_map
+        .entries()
+        .iter()
+        .map(|(action, combo)| (combo.to_string(), describe(*action)))
+        .collect::<Vec<_>>();
+    list.extend([
+        (
+            "<ENTER>".to_string(),
+            if CONFIG.constants.enter_enqueues {
+                "Queue focused music/open item"
+            } else {
+                "Play focused music/open item"
+            },
+        ),
+        (
+            "Up/Down".to_string(),
+            "Move in list or recall searched query",
+        ),
+        ("Home".to_string(), "Select first item of list"),
+        ("End".to_string(), "Select last item of list"),
+        (
+            format!(
+                "<number>{}, <number>{}",
+                key_map.describe(Action::ListDown),
+                key_map.describe(Action::ListDelete)
+            ),
+            "Move or remove that many rows",
+        ),
+        ("Left/Right, Tab".to_string(), "Move between windows"),
+        (
+            "<number>, Enter".to_string(),
+            "Select item at that position",
+        ),
+        (
+            "Click".to_string(),
+            "Focus clicked item, click again to select",
+        ),
+        ("Esc".to_string(), "Leave searchbar/popup or remove filter"),
+        ("Backspace".to_string(), "Delete typed character"),
+        ("Delete".to_string(), "Remove from favourites/recents/queue"),
+    ]);
+    list
+}
+
+// Build the help window. Scroll offset in state is clamped here as only now the height of
+// window is known
+pub fn get_help<'a>(state: &mut ui::State, area: &Rect) -> Paragraph<'a> {
+    let list = shortcut_list(&CONFIG.key_map(state.key_profile));
+    let key_width = list
+        .iter()
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or_default();
+
+    // 2 for top and bottom border
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let max_scroll = list.len().saturating_sub(visible_height) as u16;
+    state.overlay_scroll = state.overlay_scroll.min(max_scroll);
+
+    let lines = list
+        .into_iter()
+        .map(|(key, action)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!(" {:>width$} ", key, width = key_width),
+                    Style::list_highlight(),
+                ),
+                Span::raw(format!(" {}", action)),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    Paragraph::new(lines)
+        .block(Block::active(format!(
+            "{}[Output: {}] ",
+            TITLE,
+            state.output_label()
+        )))
+        .scroll((state.overlay_scroll, 0))
+}
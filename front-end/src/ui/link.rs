@@ -0,0 +1,86 @@
+use crate::ui::bookmark;
+
+// Youtube item pointed by a link pasted in searchbar
+#[derive(Debug, PartialEq)]
+pub enum Link {
+    Playlist(String),
+    Music(String),
+    Artist(String),
+}
+
+// Characters youtube uses in id of music, playlist and channel
+fn is_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+}
+
+fn playlist_id(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("list=")?;
+    rest.split(['&', '#']).next().filter(|id| is_id(id))
+}
+
+// All channel id are of 24 characters starting with UC
+fn channel_id(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("channel/")?;
+    rest.split(['/', '?', '#'])
+        .next()
+        .filter(|id| id.len() == 24 && id.starts_with("UC") && is_id(id))
+}
+
+// Find out what youtube link of playlist (list=), music (watch?v= or youtu.be/) or channel
+// (channel/) points to. Playlist is taken first as link of music played from a playlist also
+// has list= in it. Anything that is not a youtube link or has malformed id in it gives None so
+// that it is searched as text
+pub fn parse_link(query: &str) -> Option<Link> {
+    let url = query.trim();
+    if url.contains(char::is_whitespace)
+        || !(url.contains("youtube.com") || url.contains("youtu.be"))
+    {
+        return None;
+    }
+
+    if let Some(id) = playlist_id(url) {
+        Some(Link::Playlist(id.to_string()))
+    } else if let Some(id) = channel_id(url) {
+        Some(Link::Artist(id.to_string()))
+    } else {
+        bookmark::id_from_url(url)
+            .filter(|id| is_id(id))
+            .map(|id| Link::Music(id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forms_of_link() {
+        assert_eq!(
+            parse_link("https://www.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"),
+            Some(Link::Playlist(
+                "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_link("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PL123&index=2"),
+            Some(Link::Playlist("PL123".to_string()))
+        );
+        assert_eq!(
+            parse_link(" https://youtu.be/dQw4w9WgXcQ "),
+            Some(Link::Music("dQw4w9WgXcQ".to_string()))
+        );
+        assert_eq!(
+            parse_link("https://www.youtube.com/channel/UC-9-kyTW8ZkZNDHQJ6FgpwQ/videos"),
+            Some(Link::Artist("UC-9-kyTW8ZkZNDHQJ6FgpwQ".to_string()))
+        );
+
+        // Malformed id and plain query are searched as text
+        assert_eq!(parse_link("https://www.youtube.com/watch?v=short"), None);
+        assert_eq!(parse_link("https://www.youtube.com/channel/UCshort"), None);
+        assert_eq!(parse_link("youtube.com music"), None);
+        assert_eq!(parse_link("dQw4w9WgXcQ"), None);
+    }
+}
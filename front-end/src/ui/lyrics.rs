@@ -0,0 +1,80 @@
+use crate::ui::{self, shared_import::*, utils::ExtendBlock};
+
+pub const TITLE: &str = "Lyrics (Up/Down to scroll) ";
+
+// Lyrics of single music. These are kept for whole session by id of music
+pub enum Lyrics {
+    Fetching,
+    Found(String),
+    // Provider do not have lyrics of this music
+    NotFound,
+    // Lyrics could not be fetched. These are asked again next time lyrics window is opened
+    Failed,
+}
+
+// Build the lyrics window for music lyrics window was opened for. Scroll offset is clamped here
+// same as in help window
+pub fn get_lyrics<'a>(state: &mut ui::State, area: &Rect) -> Paragraph<'a> {
+    let (title, lyrics) = match &state.lyrics_of {
+        Some(music) => (
+            format!("{}- {} ", TITLE, music.name),
+            state.lyrics.get(&music.id),
+        ),
+        None => (TITLE.to_string(), None),
+    };
+    let text = match lyrics {
+        Some(Lyrics::Found(lyrics)) => lyrics.clone(),
+        Some(Lyrics::NotFound) => String::from(" No lyrics found for this music"),
+        Some(Lyrics::Failed) => String::from(" Cannot fetch lyrics. Open lyrics again to retry"),
+        Some(Lyrics::Fetching) | None => String::from(" Fetching lyrics.."),
+    };
+
+    // 2 for top and bottom border
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let max_scroll = text.lines().count().saturating_sub(visible_height) as u16;
+    state.overlay_scroll = state.overlay_scroll.min(max_scroll);
+
+    Paragraph::new(text)
+        .block(Block::active(title))
+        .scroll((state.overlay_scroll, 0))
+}
+
+impl ui::State<'_> {
+    // Open the lyrics window for music being played. Lyrics are asked from communicator unless
+    // they are already known. Returns false if nothing is being played
+    pub fn show_lyrics(&mut self) -> bool {
+        let music = match &self.now_playing {
+            Some(music) if !music.name.is_empty() => music.clone(),
+            _ => {
+                self.status = "Nothing playing..";
+                return false;
+            }
+        };
+        let known = matches!(
+            self.lyrics.get(&music.id),
+            Some(Lyrics::Found(_) | Lyrics::NotFound | Lyrics::Fetching)
+        );
+        if !known {
+            self.lyrics.insert(music.id.clone(), Lyrics::Fetching);
+            self.lyrics_request = Some(music.clone());
+        }
+        self.lyrics_of = Some(music);
+        self.overlay_scroll = 0;
+        true
+    }
+
+    // Music whose lyrics are to be fetched. See `State::show_lyrics`
+    pub fn take_lyrics_request(&mut self) -> Option<fetcher::MusicUnit> {
+        self.lyrics_request.take()
+    }
+
+    // Keep the lyrics fetched by communicator
+    pub fn set_lyrics(&mut self, music_id: &str, lyrics: Result<String, fetcher::ReturnAction>) {
+        let lyrics = match lyrics {
+            Ok(lyrics) => Lyrics::Found(lyrics),
+            Err(fetcher::ReturnAction::EOR) => Lyrics::NotFound,
+            Err(_) => Lyrics::Failed,
+        };
+        self.lyrics.insert(music_id.to_string(), lyrics);
+    }
+}
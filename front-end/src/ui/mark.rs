@@ -0,0 +1,175 @@
+use crate::ui::{
+    self, shared_import::*, utils::ExtendStyle, MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX,
+    MIDDLE_PLAYLIST_INDEX,
+};
+use ui::now_playing::truncate;
+
+// Shown before the first column of marked row
+pub const MARKER: &str = "+ ";
+
+// First cell of row in list. Marked row is prefixed with MARKER
+pub fn marked_cell(text: &str, marked: bool) -> Cell<'_> {
+    if marked {
+        Cell::from(format!("{}{}", MARKER, text))
+    } else {
+        Cell::from(text)
+    }
+}
+
+// Same as marked_cell but text is shortened to fit in given width along with MARKER
+pub fn marked_title(text: &str, marked: bool, width: usize) -> Cell<'static> {
+    if marked {
+        let text = truncate(text, width.saturating_sub(MARKER.chars().count()));
+        Cell::from(format!("{}{}", MARKER, text))
+    } else {
+        Cell::from(truncate(text, width))
+    }
+}
+
+// Style of whole row in list. Highlight of selected row is still applied over it
+pub fn marked_style(marked: bool) -> Style {
+    if marked {
+        Style::list_marked()
+    } else {
+        Style::default()
+    }
+}
+
+// Index in MIDDLE_*_INDEX of list shown in given window. None if window has no list
+fn list_index(window: &ui::Window) -> Option<usize> {
+    match window {
+        ui::Window::Musicbar => Some(MIDDLE_MUSIC_INDEX),
+        ui::Window::Playlistbar => Some(MIDDLE_PLAYLIST_INDEX),
+        ui::Window::Artistbar => Some(MIDDLE_ARTIST_INDEX),
+        _ => None,
+    }
+}
+
+// Items at marked indices if any is marked. Otherwise the selected item if there is one
+fn targets<T: Clone>(items: &[T], marked: &BTreeSet<usize>, selected: Option<usize>) -> Vec<T> {
+    if marked.is_empty() {
+        selected
+            .and_then(|index| items.get(index))
+            .cloned()
+            .into_iter()
+            .collect()
+    } else {
+        marked
+            .iter()
+            .filter_map(|index| items.get(*index))
+            .cloned()
+            .collect()
+    }
+}
+
+impl ui::State<'_> {
+    // Mark the selected item of active list or unmark it if it is already marked. Selection then
+    // moves to next item so that holding the key marks the items below too
+    pub fn toggle_mark(&mut self) {
+        let (index, selected, len) = match self.active {
+            ui::Window::Musicbar => (
+                MIDDLE_MUSIC_INDEX,
+                self.musicbar.1.selected(),
+                self.musicbar.0.len(),
+            ),
+            ui::Window::Playlistbar => (
+                MIDDLE_PLAYLIST_INDEX,
+                self.playlistbar.1.selected(),
+                self.playlistbar.0.len(),
+            ),
+            ui::Window::Artistbar => (
+                MIDDLE_ARTIST_INDEX,
+                self.artistbar.1.selected(),
+                self.artistbar.0.len(),
+            ),
+            _ => return,
+        };
+        let selected = match selected.filter(|selected| *selected < len) {
+            Some(selected) => selected,
+            None => {
+                self.status = "Nothing selected..";
+                return;
+            }
+        };
+
+        if !self.marked[index].remove(&selected) {
+            self.marked[index].insert(selected);
+        }
+        let next = Some((selected + 1).min(len - 1));
+        match index {
+            MIDDLE_MUSIC_INDEX => self.musicbar.1.select(next),
+            MIDDLE_PLAYLIST_INDEX => self.playlistbar.1.select(next),
+            _ => self.artistbar.1.select(next),
+        }
+    }
+
+    // Returns true if item at given position of list shown in window is marked
+    pub fn is_marked(&self, window: &ui::Window, item: usize) -> bool {
+        list_index(window).is_some_and(|index| self.marked[index].contains(&item))
+    }
+
+    // Number of marked items in active list
+    pub fn marked_count(&self) -> usize {
+        list_index(&self.active).map_or(0, |index| self.marked[index].len())
+    }
+
+    // Unmark every item of list shown in window. Returns false if nothing was marked.
+    // This should also be called whenever content of list changes as marked indices are no longer
+    // of the same items
+    pub fn clear_marks(&mut self, window: &ui::Window) -> bool {
+        match list_index(window) {
+            Some(index) if !self.marked[index].is_empty() => {
+                self.marked[index].clear();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Music that bulk action in musicbar applies to. These are the marked music or the selected
+    // one if nothing is marked
+    pub fn marked_music(&self) -> Vec<fetcher::MusicUnit> {
+        targets(
+            &self.musicbar.0,
+            &self.marked[MIDDLE_MUSIC_INDEX],
+            self.musicbar.1.selected(),
+        )
+    }
+
+    // Same as `marked_music` but for playlistbar
+    pub fn marked_playlists(&self) -> Vec<fetcher::PlaylistUnit> {
+        targets(
+            &self.playlistbar.0,
+            &self.marked[MIDDLE_PLAYLIST_INDEX],
+            self.playlistbar.1.selected(),
+        )
+    }
+
+    // Same as `marked_music` but for artistbar
+    pub fn marked_artists(&self) -> Vec<fetcher::ArtistUnit> {
+        targets(
+            &self.artistbar.0,
+            &self.marked[MIDDLE_ARTIST_INDEX],
+            self.artistbar.1.selected(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_prefer_marked() {
+        let items = vec!["a", "b", "c", "d"];
+        let mut marked = BTreeSet::new();
+        assert_eq!(targets(&items, &marked, Some(1)), vec!["b"]);
+        assert!(targets(&items, &marked, None).is_empty());
+
+        marked.insert(3);
+        marked.insert(0);
+        // Marked index that is no longer in the list is skipped
+        marked.insert(9);
+        assert_eq!(targets(&items, &marked, Some(1)), vec!["a", "d"]);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::ui::{
+    self,
+    shared_import::*,
+    utils::{ExtendBlock, ExtendStyle},
+};
+
+pub const TITLE: &str = "Actions (Enter to do, Esc to close) ";
+
+// Action that can be done on item selected in list from the context menu. Each of them is done
+// by the same handler as its own shortcut key
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuAction {
+    // Same as pressing ENTER on the item
+    Play,
+    Enqueue,
+    AddFavourite,
+    RemoveFavourite,
+    Download,
+    ShowUrl,
+    // Show music and playlists of the artist of music
+    GotoArtist,
+}
+
+impl MenuAction {
+    // Text shown in menu for this action on item of given window
+    pub fn label(self, window: &ui::Window) -> &'static str {
+        match (self, window) {
+            (MenuAction::Play, ui::Window::Artistbar) => "Open artist",
+            (MenuAction::Play, ui::Window::Playlistbar) => "Play playlist",
+            (MenuAction::Play, _) => "Play",
+            (MenuAction::Enqueue, _) => "Add to queue",
+            (MenuAction::AddFavourite, ui::Window::Artistbar) => "Follow",
+            (MenuAction::AddFavourite, _) => "Add to favourites",
+            (MenuAction::RemoveFavourite, ui::Window::Artistbar) => "Unfollow",
+            (MenuAction::RemoveFavourite, _) => "Remove from favourites",
+            (MenuAction::Download, _) => "Download",
+            (MenuAction::ShowUrl, _) => "Show url",
+            (MenuAction::GotoArtist, _) => "Go to artist",
+        }
+    }
+}
+
+// Actions that can be done on item in list of given window
+pub fn actions_for(window: &ui::Window) -> Vec<MenuAction> {
+    match window {
+        ui::Window::Musicbar => vec![
+            MenuAction::Play,
+            MenuAction::Enqueue,
+            MenuAction::AddFavourite,
+            MenuAction::RemoveFavourite,
+            MenuAction::Download,
+            MenuAction::ShowUrl,
+            MenuAction::GotoArtist,
+        ],
+        ui::Window::Playlistbar => vec![
+            MenuAction::Play,
+            MenuAction::AddFavourite,
+            MenuAction::RemoveFavourite,
+            MenuAction::Download,
+            MenuAction::ShowUrl,
+        ],
+        ui::Window::Artistbar => vec![
+            MenuAction::Play,
+            MenuAction::AddFavourite,
+            MenuAction::RemoveFavourite,
+        ],
+        _ => Vec::new(),
+    }
+}
+
+// Build the context menu. Menu is only open while there are actions in it
+pub fn get_menu<'a>(state: &ui::State) -> (List<'a>, ListState) {
+    let items = state
+        .menu
+        .iter()
+        .map(|action| ListItem::new(format!(" {}", action.label(&state.before_overlay))))
+        .collect::<Vec<_>>();
+    let mut list_state = ListState::default();
+    list_state.select(Some(state.menu_selected));
+
+    let list = List::new(items)
+        .block(Block::active(TITLE.to_string()))
+        .highlight_style(Style::list_highlight());
+    (list, list_state)
+}
+
+impl ui::State<'_> {
+    // Open the context menu for item selected in active list. Returns false if there is no
+    // selected item to act on
+    pub fn open_menu(&mut self) -> bool {
+        let selected = match self.active {
+            ui::Window::Musicbar => self.musicbar.1.selected(),
+            ui::Window::Playlistbar => self.playlistbar.1.selected(),
+            ui::Window::Artistbar => self.artistbar.1.selected(),
+            _ => None,
+        };
+        if selected.is_none() {
+            self.status = "Nothing selected..";
+            return false;
+        }
+
+        self.menu = actions_for(&self.active);
+        self.menu_selected = 0;
+        self.before_overlay = self.active.clone();
+        self.active = ui::Window::Menu;
+        true
+    }
+
+    // Move selection in context menu one step down or up
+    pub fn move_menu(&mut self, down: bool) {
+        let last = self.menu.len().saturating_sub(1);
+        self.menu_selected = if down {
+            (self.menu_selected + 1).min(last)
+        } else {
+            self.menu_selected.saturating_sub(1)
+        };
+    }
+
+    // Close the context menu and go back to the list it was opened for. Returns the selected
+    // action if menu was open
+    pub fn close_menu(&mut self) -> Option<MenuAction> {
+        if self.active != ui::Window::Menu {
+            return None;
+        }
+        self.active = self.before_overlay.clone();
+        let action = self.menu.get(self.menu_selected).copied();
+        self.menu.clear();
+        action
+    }
+}
@@ -1,4 +1,40 @@
+mod bookmark;
+mod browser;
+mod columns;
+mod confirm;
+mod crossfade;
+mod delete;
 pub mod event;
+mod filter;
+mod help;
+mod link;
+mod lyrics;
+mod mark;
+mod menu;
+mod most_played;
+mod motion;
+mod mouse;
+#[cfg(all(feature = "mpris", unix))]
+pub mod mpris;
+mod now_playing;
+mod osd;
+mod output;
+mod panes;
+mod playlist_file;
+mod prefetch;
+mod queue;
+mod radio;
+mod recovery;
+#[cfg(feature = "scrobble")]
+mod scrobble;
+mod search_history;
+mod session;
+mod shuffle;
+mod sort;
+mod spinner;
+#[cfg(feature = "thumbnail")]
+mod thumbnail;
+mod toast;
 mod utils;
 use std::sync::Condvar;
 use tui::{backend::CrosstermBackend, Terminal};
@@ -9,8 +45,9 @@ mod shared_import {
     pub use serde::{Deserialize, Serialize};
     pub use std::convert::{From, Into, TryFrom, TryInto};
     pub use std::{
+        collections::{BTreeSet, HashMap, VecDeque},
         sync::{Arc, Mutex},
-        time::Duration,
+        time::{Duration, Instant},
     };
     pub use tui::{
         backend::Backend,
@@ -23,11 +60,16 @@ mod shared_import {
         },
     };
 }
+use config::initilize::CONFIG;
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use event::{MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX, MIDDLE_PLAYLIST_INDEX};
 use shared_import::*;
+use utils::ExtendBlock;
+pub use utils::LockRecover;
 
 // Following several state defines the layout of the ui
 // The ui is first splitted into 3 area arranged verticsally in order:
@@ -76,7 +118,7 @@ pub struct MainLayout {
 // TODO: Instead of having seperate struct to hold SideBar Rect define Rect directly in MainLayout
 // So that this struct is removed and type of `MainLayout::sidebar` is Rect
 pub struct SideBar {
-    layout: [Rect; 2],
+    layout: [Rect; 3],
 }
 
 // --------------------------------------
@@ -140,7 +182,7 @@ pub struct BottomLayout {
 
 // Sotres the position on which respective components (in which field is named after)
 // are to be rendered
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Position {
     pub search: Rect,
     pub status: Rect,
@@ -150,7 +192,11 @@ pub struct Position {
     pub artist: Rect,
     pub music_info: Rect,
     pub bottom_icons: Rect,
+    // Area for thumbnail of music being played. Empty unless built with thumbnail feature and
+    // terminal can show images. See thumbnail.rs
+    pub thumbnail: Rect,
     pub popup: Rect,
+    pub overlay: Rect,
 }
 
 // This function will:
@@ -162,11 +208,15 @@ pub struct Position {
 // else -> Update the ui
 // Ui is always updated when notified. No checkes are done to weather the ui is really updated or
 // not as algorithms defined in ternial backend is responsible for such checks.
-// Ui is also updated in every REFRESH_RATE specified which will then sync the states like
-// played duration to the ui. Also see documentation in __event.rs__ file
+// While music is playing, ui is also updated in every refresh_rate specified in config which will
+// then sync the states like played duration to the ui. Also see `State::poll_timeout`
+// Lists in middle area are only built again when they are marked dirty in state. Otherwise they
+// are copied from last frame. This saves the work in refresh while music is playing where only
+// the progress changes
 pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
     let mut stdout = std::io::stdout();
     execute!(stdout, EnterAlternateScreen).expect("Failed to enter alternate screen");
+    execute!(stdout, EnableMouseCapture).unwrap_or_else(|_| eprintln!("Failed to capture mouse"));
     terminal::enable_raw_mode().expect("Faild to enable raw mode");
 
     let backed = CrosstermBackend::new(stdout);
@@ -180,28 +230,75 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
         .unwrap_or_else(|_| eprintln!("Failed to hide cursor"));
 
     let mut previous_dimension: Rect = Rect::default();
-    let mut position = Position::caclulate(&previous_dimension);
+    // Hidden panes and size ratio of panes used in last layout
+    let mut previous_panes = ([false; 3], CONFIG.constants.pane_ratio);
+    let mut position = Position::caclulate(
+        &previous_dimension,
+        LayoutMode::Normal,
+        previous_panes.0,
+        previous_panes.1,
+    );
+    // Buffer of last drawn frame and weather popup was shown in that frame
+    let mut last_frame: Option<(tui::buffer::Buffer, bool)> = None;
+    #[cfg(feature = "thumbnail")]
+    let mut thumbnails = thumbnail::Thumbnails::new(Arc::clone(cvar));
     let mut paint_ui = || {
-        terminal
+        let mut has_popup = false;
+        // Music whose thumbnail is to be shown, weather anything is drawn over thumbnail and
+        // weather terminal was resized in this frame
+        let mut thumbnail_of: Option<String> = None;
+        let (mut thumbnail_covered, mut resized) = (false, false);
+        let completed_frame = terminal
             .draw(|screen| {
-                let mut state_unlocked = state.lock().unwrap();
+                let mut state_unlocked = state.lock_recover();
+                has_popup = state_unlocked.active.is_overlay() || state_unlocked.osd.is_some();
 
                 // As screen size doesn't change that often (is chaged when terminal window is
                 // resized) so it is unnecessary to calcuate position for components in every draw
-                // loop. Calculate once and recalculate when window size change
+                // loop. Calculate once and recalculate when window size change or pane is hidden
+                // or resized
                 let current_dimension = screen.size();
-                if previous_dimension != current_dimension {
-                    position = Position::caclulate(&current_dimension);
+                let current_panes = (state_unlocked.hidden, state_unlocked.pane_ratio);
+                if previous_dimension != current_dimension || previous_panes != current_panes {
+                    state_unlocked.layout_mode =
+                        if current_dimension.width < CONFIG.constants.min_layout_width {
+                            LayoutMode::Compact
+                        } else {
+                            LayoutMode::Normal
+                        };
+                    position = Position::caclulate(
+                        &current_dimension,
+                        state_unlocked.layout_mode,
+                        current_panes.0,
+                        current_panes.1,
+                    );
+                    state_unlocked.layout = position.clone();
+                    resized = previous_dimension != current_dimension;
                     previous_dimension = current_dimension;
+                    previous_panes = current_panes;
+                    state_unlocked.mark_lists_dirty();
+                }
+                // Popup is drawn over the lists so list area in last frame may contain popup
+                let cached_frame = match &last_frame {
+                    Some((buffer, false)) if !has_popup => Some(buffer),
+                    _ => None,
+                };
+
+                match &state_unlocked.toast {
+                    Some((text, level, _)) => {
+                        screen.render_widget(toast::get_toast(text, level), position.status)
+                    }
+                    None => screen
+                        .render_widget(TopLayout::get_statusbox(&state_unlocked), position.status),
                 }
-
-                screen.render_widget(TopLayout::get_statusbox(&state_unlocked), position.status);
                 screen.render_widget(TopLayout::get_searchbox(&state_unlocked), position.search);
-                screen.render_stateful_widget(
-                    SideBar::get_shortcuts(&state_unlocked),
-                    position.shortcut,
-                    &mut state_unlocked.sidebar,
-                );
+                if state_unlocked.is_visible(&Window::Sidebar) {
+                    screen.render_stateful_widget(
+                        SideBar::get_shortcuts(&state_unlocked),
+                        position.shortcut,
+                        &mut state_unlocked.sidebar,
+                    );
+                }
 
                 // each of below three state keeps data as reference to prevent unnecessaru copy
                 // i.e they holds immutable reference to internal field of state variable so state
@@ -220,21 +317,63 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
                     artist_state = &mut (*state_ptr).artistbar.1;
                 }
 
-                let music_table = MiddleLayout::get_music_container(&mut state_unlocked);
-                screen.render_stateful_widget(music_table, position.music, &mut music_state);
-                let playlist_table = MiddleBottom::get_playlist_container(&mut state_unlocked);
-                screen.render_stateful_widget(
-                    playlist_table,
-                    position.playlist,
-                    &mut playlist_state,
-                );
-                let artist_table = MiddleBottom::get_artist_container(&mut state_unlocked);
-                screen.render_stateful_widget(artist_table, position.artist, &mut artist_state);
+                let is_cached = |index: usize| -> Option<&tui::buffer::Buffer> {
+                    if state_unlocked.list_dirty[index] {
+                        None
+                    } else {
+                        cached_frame
+                    }
+                };
+                let cached_lists = [
+                    is_cached(MIDDLE_MUSIC_INDEX),
+                    is_cached(MIDDLE_PLAYLIST_INDEX),
+                    is_cached(MIDDLE_ARTIST_INDEX),
+                ];
+
+                if state_unlocked.is_visible(&Window::Musicbar) {
+                    if let Some(buffer) = cached_lists[MIDDLE_MUSIC_INDEX] {
+                        screen.render_widget(utils::CachedRegion(buffer), position.music);
+                    } else {
+                        let music_table = MiddleLayout::get_music_container(&mut state_unlocked);
+                        screen.render_stateful_widget(
+                            music_table,
+                            position.music,
+                            &mut music_state,
+                        );
+                    }
+                }
+                if state_unlocked.is_visible(&Window::Playlistbar) {
+                    if let Some(buffer) = cached_lists[MIDDLE_PLAYLIST_INDEX] {
+                        screen.render_widget(utils::CachedRegion(buffer), position.playlist);
+                    } else {
+                        let playlist_table =
+                            MiddleBottom::get_playlist_container(&mut state_unlocked);
+                        screen.render_stateful_widget(
+                            playlist_table,
+                            position.playlist,
+                            &mut playlist_state,
+                        );
+                    }
+                }
+                if state_unlocked.is_visible(&Window::Artistbar) {
+                    if let Some(buffer) = cached_lists[MIDDLE_ARTIST_INDEX] {
+                        screen.render_widget(utils::CachedRegion(buffer), position.artist);
+                    } else {
+                        let artist_table = MiddleBottom::get_artist_container(&mut state_unlocked);
+                        screen.render_stateful_widget(
+                            artist_table,
+                            position.artist,
+                            &mut artist_state,
+                        );
+                    }
+                }
+                state_unlocked.list_dirty = [false; 3];
+                state_unlocked.update_list_offsets();
 
                 state_unlocked.refresh_mpv_status();
 
                 screen.render_widget(
-                    BottomLayout::get_status_bar(&state_unlocked),
+                    BottomLayout::get_status_bar(&state_unlocked, position.music_info.width),
                     position.music_info,
                 );
                 screen.render_widget(
@@ -247,16 +386,74 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
                 // see some things like progress of music player
                 if let Window::Popup(title, ref content) = state_unlocked.active {
                     utils::show_pupop_text(screen, [title, content], &position.popup);
+                } else if let Window::Confirm(action) = state_unlocked.active {
+                    let message = state_unlocked.confirm_message(action);
+                    utils::show_pupop_text(screen, [confirm::TITLE, &message], &position.popup);
+                } else if state_unlocked.active == Window::Helpbar {
+                    let help = help::get_help(&mut state_unlocked, &position.overlay);
+                    screen.render_widget(widgets::Clear, position.overlay);
+                    screen.render_widget(help, position.overlay);
+                } else if state_unlocked.active == Window::Lyrics {
+                    let lyrics = lyrics::get_lyrics(&mut state_unlocked, &position.overlay);
+                    screen.render_widget(widgets::Clear, position.overlay);
+                    screen.render_widget(lyrics, position.overlay);
+                } else if state_unlocked.active == Window::Menu {
+                    let (menu, mut list_state) = menu::get_menu(&state_unlocked);
+                    screen.render_widget(widgets::Clear, position.popup);
+                    screen.render_stateful_widget(menu, position.popup, &mut list_state);
+                } else if state_unlocked.active == Window::Queuebar {
+                    let (queue, mut list_state) = queue::get_queue(&mut state_unlocked);
+                    screen.render_widget(widgets::Clear, position.overlay);
+                    screen.render_stateful_widget(queue, position.overlay, &mut list_state);
+                }
+                // Feedback of volume and seek is drawn over everything including the popup
+                if let Some((text, _)) = &state_unlocked.osd {
+                    let area = osd::osd_area(current_dimension);
+                    screen.render_widget(widgets::Clear, area);
+                    screen.render_widget(osd::get_osd(text), area);
+                    thumbnail_covered = area.intersects(position.thumbnail);
+                }
+
+                if position.thumbnail.height > 0 {
+                    screen
+                        .render_widget(Block::new(String::from("Thumbnail ")), position.thumbnail);
+                    thumbnail_of = state_unlocked
+                        .now_playing
+                        .as_ref()
+                        .map(|music| music.id.clone());
+                    thumbnail_covered |= match state_unlocked.active {
+                        Window::Popup(..) | Window::Confirm(_) | Window::Menu => {
+                            position.popup.intersects(position.thumbnail)
+                        }
+                        Window::Helpbar | Window::Lyrics | Window::Queuebar => {
+                            position.overlay.intersects(position.thumbnail)
+                        }
+                        _ => false,
+                    };
                 }
             })
             .unwrap();
+        last_frame = Some((completed_frame.buffer.clone(), has_popup));
+        // Image is written to terminal after tui is done with the frame as tui only knows text
+        #[cfg(feature = "thumbnail")]
+        thumbnails.draw(
+            thumbnail_of.as_deref(),
+            position.thumbnail,
+            thumbnail_covered,
+            resized,
+        );
     };
     paint_ui();
 
     'reactor: loop {
         // Use if instead of match because if will drop the mutex while going to else branch
         // but match keeps locking the mutex until match expression finished
-        if cvar.wait(state.lock().unwrap()).unwrap().active == Window::None {
+        if cvar
+            .wait(state.lock_recover())
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .active
+            == Window::None
+        {
             break 'reactor;
         } else {
             paint_ui();
@@ -269,6 +466,9 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
     crossterm::terminal::disable_raw_mode().unwrap_or_else(|_| {
         eprintln!("Failed to leave raw mode. You may need to restart the terminal")
     });
+    execute!(std::io::stdout(), DisableMouseCapture).unwrap_or_else(|_| {
+        eprintln!("Failed to release mouse. You may need to restart the terminal")
+    });
     execute!(std::io::stdout(), LeaveAlternateScreen).unwrap_or_else(|_| {
         eprintln!("Failed to leave alternate mode. You may need to restart the terminal")
     });
@@ -277,6 +477,16 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
         .unwrap_or_else(|_| eprintln!("Failed to show cursor. Try: stty sane"));
 }
 
+// Normal layout is as described above. Compact layout is used when terminal is narrower than
+// `min_layout_width` from config. In compact layout whole middle area is given to single section
+// which is the active one (or musicbar if active window is not from middle area) and icons set
+// is not shown
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LayoutMode {
+    Normal,
+    Compact,
+}
+
 #[derive(Clone)]
 pub enum SidebarOption {
     Trending = 0,
@@ -284,10 +494,12 @@ pub enum SidebarOption {
     Liked = 2,
     Saved = 3,
     Following = 4,
-    Search = 5,
+    RecentlyPlayed = 5,
+    MostPlayed = 6,
+    Search = 7,
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum Window {
     Searchbar,
     Sidebar,
@@ -296,9 +508,76 @@ pub enum Window {
     Artistbar,
     BottomControl,
     Popup(&'static str, String),
+    // Window showing list of all shortcuts. This is shown over everything else like Popup
+    Helpbar,
+    // Window listing the play queue. This is shown over everything else like Helpbar
+    Queuebar,
+    // Window showing lyrics of music being played. This is shown over everything else too
+    Lyrics,
+    // Asks user to confirm the action before it is done. This is shown over everything else too
+    Confirm(ConfirmAction),
+    // Actions for the item selected in list. This is shown over everything else like Queuebar
+    Menu,
     None,
 }
 
+// Action that needs to be confirmed by user. See confirm.rs
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfirmAction {
+    Quit,
+    ClearQueue,
+    ClearHistory,
+}
+
+// Order of mpv playlist before it was shuffled. See shuffle.rs
+pub struct ShuffleOrder {
+    // Seeded once for the session from shuffle_seed of config
+    rng: rand::rngs::StdRng,
+    // Path of every playlist entry before shuffling so that the order can be restored
+    original: Vec<String>,
+}
+
+// Progress of single download. See `DownloadJob`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadState {
+    // Waiting for downloader to be started
+    Queued,
+    Downloading,
+    // Downloader is stopped until downloads are resumed. See `State::toggle_pause_downloads`
+    Paused,
+    Done,
+    Failed,
+}
+
+// Music or playlist being downloaded. State is shared with the task running the downloader which
+// updates it as download progress
+pub struct DownloadJob {
+    pub url: String,
+    pub state: Arc<Mutex<DownloadState>>,
+}
+
+// Keeps playing music related to the last played one once there is nothing else to play.
+// See radio.rs
+#[derive(Default)]
+pub struct RadioMode {
+    pub enabled: bool,
+    // Music whose related music communicator should fetch and queue. Taken by communicator
+    seed: Option<String>,
+    // Last music related music was asked for so that it is only asked once
+    requested: Option<String>,
+    // Recently played music with latest at last
+    heard: VecDeque<String>,
+}
+
+// List being written to file in another thread. See playlist_file.rs
+pub struct PendingExport {
+    path: std::path::PathBuf,
+    // Number of music in the list
+    count: usize,
+    // Tells whether file could be written once it is done
+    written: std::sync::mpsc::Receiver<Result<(), String>>,
+}
+
 pub struct BottomState {
     music_duration: Duration,
     music_elapse: Duration,
@@ -309,40 +588,92 @@ pub struct BottomState {
     playing: Option<(String, bool)>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MusicbarSource {
     Search(String),
     Trending,
+    // Popular videos shown along with community channels
+    Community,
     RecentlyPlayed,
     Favourates,
     Playlist(String),
     Artist(String),
+    // Music in play history sorted by number of plays. See most_played.rs
+    MostPlayed,
 }
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum PlaylistbarSource {
     Search(String),
     RecentlyPlayed,
     Favourates,
     Artist(String),
 }
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum ArtistbarSource {
     Search(String),
     RecentlyPlayed,
     Favourates,
 }
 
+// Downloaders that are running. Shared with the tasks running them so that downloader which starts
+// while downloads are paused is paused right away. See `State::toggle_pause_downloads`
+#[derive(Default)]
+pub struct Downloaders {
+    // Process group of every running downloader. Downloader leads its own group so that ffmpeg it
+    // starts is paused and resumed along with it
+    groups: Vec<u32>,
+    paused: bool,
+}
+
+// Type of result to search for when query has no type prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchScope {
+    All,
+    Music,
+    Playlist,
+    Artist,
+    // Only the list that was focused last. See `State::focused_scope`
+    Focused,
+}
+
+// How serious a toast is. Decides its color and how long it is shown. See toast.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+// What to play when currently playing music ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatMode {
+    // Play next item from playlist and stop after last item
+    Nothing,
+    // Play all the items from playlist. If last item ends play first
+    All,
+    // Play currently playing item over and over again
+    One,
+}
+
 #[derive(Debug)]
 pub struct PlaybackBehaviour {
     // true if user wishes to suffle the playlist
     // false otherwise
     shuffle: bool,
-    // true if user wished to repeat all items from playlist. i.e when last music of playlist ends
-    // play the first music again
-    // flase if user wants to repeat the currently playing music over and over again
-    repeat: bool,
+    // Repeat mode choosen by user. See `RepeatMode`
+    repeat: RepeatMode,
     // Current volume level. This is store here instead of fecthing with get_prop everytime
     volume: u8,
+    // Volume level before muting. None if playback is not muted
+    muted_volume: Option<u8>,
+    // Playback speed where 1.0 is the normal speed. See `State::set_speed`
+    speed: f32,
+    // true if next item from playlist should be played when current track ends
+    // false if playback should stop at the end of current track
+    auto_advance: bool,
+    // true if queued music should be removed from queue once finished or skipped. See
+    // `State::consumes_queue`
+    consume_queue: bool,
 }
 
 pub struct State<'p> {
@@ -373,6 +704,11 @@ pub struct State<'p> {
     // second memebr is Some(result_of_this_query) (to send to fetcher)
     // second member is the string of searchbar when use pressed ENTER last time in searchbar
     pub search: (String, String),
+    // Type of result searched for query without prefix. See `SearchScope`
+    pub search_scope: SearchScope,
+    // List that was focused last. This is the list searched in SearchScope::Focused as searchbar
+    // itself is focused while query is typed
+    pub last_pane: Window,
 
     // Currently active window. In UI, this windows title is hilighted and keypress are evaluated
     // depending on active window
@@ -382,10 +718,221 @@ pub struct State<'p> {
     // Initially it is whenever `filled_source` is changed. And is inceremented/decremented by 1 on
     // next/prev respectively
     pub fetched_page: [Option<usize>; 3],
+    // True if fetched page in same index is the last one. That is when less than page_size items
+    // were returned for it. Reset when another page is being fetched
+    pub last_page: [bool; 3],
+    // True if last fetch of list in same index has failed. List then shows whatever it was showing
+    // before the fetch
+    pub fetch_failed: [bool; 3],
+    // Set to true to ask communicator to fetch list in same index again from same source and
+    // page. Item at same position stays selected after it. See `State::refresh_active`
+    pub refresh: [bool; 3],
+    // Attempt being made and total attempts allowed while a failed fetch is being retried
+    pub retrying: Option<(u32, u32)>,
+    // True while list in same index is being fetched by communicator. See spinner.rs
+    fetching: [bool; 3],
+    // When spinner shown while fetching started spinning. None when nothing is being fetched
+    pub spinning_since: Option<Instant>,
+
+    // Downloaders of `downloads` that are running and if they are paused
+    pub downloaders: Arc<Mutex<Downloaders>>,
 
     // Main handler for mpv player. This isw backed my libmpv library
     pub player: libmpv::Mpv,
 
     // See documentation for respective struct
     pub playback_behaviour: PlaybackBehaviour,
+
+    // Index of keybinding profile currently in use. See `Config::key_profile`
+    pub key_profile: usize,
+
+    // When true, youtube id is shown in place of title in music/playlist/artist list
+    pub show_ids: bool,
+
+    // Layout being used currently. This is updated whenever terminal is resized
+    pub layout_mode: LayoutMode,
+    // Panes of middle area (in index MIDDLE_*_INDEX) hidden by user. See panes.rs
+    pub hidden: [bool; 3],
+    // Size of panes in middle area. See `Constants::pane_ratio`
+    pub pane_ratio: [u16; 3],
+    // Width of columns of musicbar drawn in last frame. See columns.rs
+    music_columns: Vec<Constraint>,
+
+    // Music explicitly queued by user to be played. First member of tuple is the serial number
+    // of item in the order it was added to queue. This is used to restore the original order.
+    // Last member is true once music has been played but is kept in queue as consume is off or
+    // repeating all. Next music to play is the first one not played yet
+    pub queue: VecDeque<(usize, fetcher::MusicUnit, bool)>,
+
+    // data_version of storage when queue was last read from it. This is only used when queue is
+    // shared. See `State::sync_queue`
+    queue_version: Option<i64>,
+
+    // Filter applied to one of musicbar/playlistbar/artistbar. None if no list is filtered
+    pub filter: Option<filter::ListFilter>,
+    // Matching method used by filter
+    pub filter_mode: filter::FilterMode,
+    // Content of musicbar/playlistbar/artistbar before filter was applied. Filtered list is always
+    // built from this so that changing or removing filter do not lose any item
+    unfiltered: (
+        Vec<fetcher::MusicUnit>,
+        Vec<fetcher::PlaylistUnit>,
+        Vec<fetcher::ArtistUnit>,
+    ),
+    // Sort applied to musicbar/playlistbar/artistbar (in index MIDDLE_*_INDEX) and content of
+    // sorted lists in the order they were fetched. See sort.rs
+    sort: [Option<sort::Sort>; 3],
+    unsorted: (
+        Vec<fetcher::MusicUnit>,
+        Vec<fetcher::PlaylistUnit>,
+        Vec<fetcher::ArtistUnit>,
+    ),
+
+    // Number of cached responses and limit of cache in fetcher. Fetcher lives in communicator
+    // thread so this is only the copy updated by communicator every time it wakes up
+    pub cache_size: (usize, usize),
+    // Set to true to ask communicator to clear the cache of fetcher
+    pub clear_cache: bool,
+
+    // Some while bookmark popup is waiting for input. See bookmark.rs
+    pub bookmark: Option<bookmark::Prompt>,
+
+    // Set to true when something has changed without any key press and need to be handled in next
+    // refresh cycle. Key handlers notify the painter themselves so they need not set this.
+    // See `State::take_dirty`
+    pub dirty: bool,
+
+    // When true, music is played as soon as it is highlighted in musicbar
+    pub play_on_select: bool,
+    // Id of music highlighted in play on select mode and the time when it was highlighted.
+    // See `State::play_pending_selection`
+    pending_selection: Option<(String, Instant)>,
+    // Time when query in searchbar was last changed if it is yet to be searched in live search
+    // mode. See `State::take_pending_search`
+    pub search_typed_at: Option<Instant>,
+    // True when last search was made by live search. Lists fetched for it are not focused while
+    // searchbar is still active so that typing is not interrupted. See `State::focus_fetched`
+    pub live_searched: bool,
+    // Previously searched queries with latest at first. See search_history.rs
+    search_history: Vec<String>,
+    // Index of query from search_history being shown in searchbar and the query user was typing
+    // before starting to recall. None if user is typing their own query
+    search_recalled: Option<usize>,
+    search_draft: String,
+    // True if first music found for query given in command line is to be played once it is
+    // fetched. See `State::search_on_start`
+    play_first_result: bool,
+
+    // True if last key was ESC that left the searchbar and constants.esc_quit is enabled.
+    // Next key press always resets this to false
+    pub quit_armed: bool,
+
+    // Count and operator being typed for a motion in focused list. Count alone selects the item
+    // at that position (starting from 1) when ENTER is pressed. See motion.rs
+    pub motion: motion::Motion,
+
+    // Downloads started in this session that are not yet reported to be finished. See
+    // `State::report_downloads`
+    pub downloads: Vec<DownloadJob>,
+
+    // Index of duration in SLEEP_TIMER_STEPS chosen by user and the time when playback is to be
+    // stopped. None when sleep timer is off. See `State::cycle_sleep_timer`
+    sleep_timer: Option<(usize, Instant)>,
+
+    // Music being played. This is updated whenever mpv moves to another track. See
+    // now_playing.rs
+    pub now_playing: Option<fetcher::MusicUnit>,
+    // Content of musicbar when music was last played from it
+    played_from: Vec<fetcher::MusicUnit>,
+    // Time when now_playing was last changed if it is yet to be notified. See
+    // `State::notify_track_change`
+    track_changed_at: Option<Instant>,
+    // Time when now_playing was last changed. Unlike track_changed_at this is kept until track
+    // changes again. See `State::count_play`
+    track_started_at: Option<Instant>,
+    // true once play of now_playing is recorded in history. See `State::count_play`
+    play_counted: bool,
+    // Sends plays to last.fm. None unless last.fm account is given in config. See scrobble.rs
+    #[cfg(feature = "scrobble")]
+    scrobbler: Option<scrobble::Scrobbler>,
+    // Position in seconds to seek to once music restored from last session is loaded
+    resume_position: Option<f64>,
+    // Id of next music mpv was allowed to open ahead of time. See `State::prefetch_next`
+    prefetched: Option<String>,
+    // Volume set by crossfade while it is lowered from the one set by user. See
+    // `State::crossfade`
+    fade_volume: Option<u8>,
+    // Second player that plays start of next music over the end of current one. See
+    // `State::crossfade`
+    fader: Option<crossfade::Fader>,
+
+    // True if list of musicbar/playlistbar/artistbar (in index MIDDLE_*_INDEX) has changed since
+    // last frame. List that has not changed are copied from last frame instead of being built again
+    // See `draw_ui`. When not sure about what changed, simply mark all of them as dirty
+    pub list_dirty: [bool; 3],
+
+    shuffle: ShuffleOrder,
+
+    // Indices of marked items in list of musicbar/playlistbar/artistbar (in index MIDDLE_*_INDEX).
+    // Bulk actions apply to these instead of the selected item. See mark.rs
+    pub marked: [BTreeSet<usize>; 3],
+
+    pub radio: RadioMode,
+
+    // When true, starting playback do not change active window, filled source or selection
+    pub stay_on_source: bool,
+
+    // Window that was active before help or queue window was shown. This is made active again
+    // when that window is hidden
+    pub before_overlay: Window,
+    // Number of lines help or lyrics text is scrolled by
+    pub overlay_scroll: u16,
+    // Lyrics fetched in this session by id of music. See lyrics.rs
+    lyrics: HashMap<String, lyrics::Lyrics>,
+    // Music shown in lyrics window
+    lyrics_of: Option<fetcher::MusicUnit>,
+    // Music whose lyrics communicator should fetch. Taken by communicator
+    lyrics_request: Option<fetcher::MusicUnit>,
+    // Index of music selected in queue window
+    pub queue_selected: usize,
+    // Actions shown in context menu and the one selected. Menu is opened for the item selected in
+    // list of `before_overlay`. See menu.rs
+    pub menu: Vec<menu::MenuAction>,
+    pub menu_selected: usize,
+    // Id of first music of queue that has been put in mpv playlist to play next. See
+    // `State::play_queue`
+    queued_next: Option<String>,
+    // Music loaded last time until it starts playing. See recovery.rs
+    loading: Option<recovery::LoadAttempt>,
+    // Text shown in middle of screen for a moment and when it was shown. See osd.rs
+    pub osd: Option<(String, Instant)>,
+    // Error or notice shown in place of status until it expires. See toast.rs
+    pub toast: Option<(String, ToastLevel, Instant)>,
+    // Tells if browser opened last time could be started. See browser.rs
+    browser: Option<std::sync::mpsc::Receiver<bool>>,
+    // When last key or mouse input was seen. See `State::poll_timeout`
+    last_input: Instant,
+    // List being exported. See playlist_file.rs
+    export: Option<PendingExport>,
+
+    // Position where every component was drawn in last frame. Mouse events are matched against
+    // this to find what was clicked
+    pub layout: Position,
+    // Index of first item shown in sidebar and musicbar/playlistbar/artistbar (in index
+    // MIDDLE_*_INDEX) in last frame. See `State::update_list_offsets`
+    sidebar_offset: usize,
+    list_offset: [usize; 3],
+}
+
+// Music of given id which is its title too. Tests of every module build their lists from it
+#[cfg(test)]
+pub fn test_music(id: &str) -> fetcher::MusicUnit {
+    fetcher::MusicUnit {
+        artist: String::new(),
+        artist_id: String::new(),
+        name: id.to_string(),
+        duration: "00:00".to_string(),
+        id: id.to_string(),
+        views: String::new(),
+    }
 }
@@ -0,0 +1,94 @@
+use crate::ui::{self, shared_import::*};
+use config::initilize::{CONFIG, STORAGE, TB_HISTORY};
+
+// Live stream has no length to take percent of so it is counted after playing this long
+pub const LIVE_PLAY_SECS: u64 = 30;
+
+// Returns true once music has played far enough to be counted as played
+pub fn is_played(elapsed: Duration, duration: Duration, percent: u8) -> bool {
+    if duration.is_zero() {
+        return elapsed >= Duration::from_secs(LIVE_PLAY_SECS);
+    }
+    elapsed.as_secs_f64() >= duration.as_secs_f64() * f64::from(percent) / 100.0
+}
+
+impl ui::State<'_> {
+    // Record the play of music being played in history once it has actually started and played
+    // past play_count_threshold_pct of its length. Each music is counted once every time it
+    // starts. This is called every time mpv status is refreshed and flag is reset on track change
+    pub fn count_play(&mut self) {
+        if self.play_counted {
+            return;
+        }
+        let music = match &self.now_playing {
+            Some(music) => music,
+            None => return,
+        };
+        let elapsed = match self.player.get_property::<f64>("audio-pts") {
+            Ok(elapsed) => Duration::from_secs_f64(elapsed.max(0.0)),
+            // Nothing is heard yet
+            Err(_) => return,
+        };
+        #[cfg(feature = "scrobble")]
+        if let Some(scrobbler) = &mut self.scrobbler {
+            scrobbler.now_playing(music, self.track_started_at, self.bottom.music_duration);
+        }
+        if !is_played(
+            elapsed,
+            self.bottom.music_duration,
+            CONFIG.constants.play_count_threshold_pct,
+        ) {
+            return;
+        }
+        Self::add_to_history(music);
+        #[cfg(feature = "scrobble")]
+        if let Some(scrobbler) = self
+            .scrobbler
+            .as_ref()
+            .filter(|_| ui::scrobble::is_scrobbled(self.bottom.music_duration))
+        {
+            scrobbler.scrobble(music, elapsed, self.bottom.music_duration);
+        }
+        self.play_counted = true;
+    }
+
+    // Forget every play of given music so that it is no longer in most played list
+    pub fn forget_plays(&mut self, music: &fetcher::MusicUnit) -> bool {
+        let query = format!(
+            "
+            DELETE FROM {tb_name}
+            WHERE id = :id
+        ",
+            tb_name = TB_HISTORY
+        );
+        let args = [(":id", &music.id)];
+
+        match STORAGE.lock().unwrap().execute(&query, &args) {
+            Ok(_) => {
+                self.status = "Plays forgotten..";
+                true
+            }
+            Err(err) => {
+                log::error!("Error removing from history. Error: {err}", err = err);
+                self.status = "Err removing..";
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_after_threshold() {
+        let secs = Duration::from_secs;
+        assert!(is_played(secs(0), secs(200), 0));
+        assert!(!is_played(secs(59), secs(200), 30));
+        assert!(is_played(secs(60), secs(200), 30));
+        // Length of live stream is not known
+        assert!(!is_played(secs(10), Duration::ZERO, 50));
+        assert!(is_played(secs(LIVE_PLAY_SECS), Duration::ZERO, 50));
+    }
+}
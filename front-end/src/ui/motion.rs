@@ -0,0 +1,119 @@
+use crate::ui::{self, shared_import::*};
+
+// Count and operator typed for a motion are dropped if next key is not pressed within this long
+pub const MOTION_TIMEOUT: Duration = Duration::from_millis(1500);
+
+// Keys typed so far for a motion in focused list. eg: "5" of "5j", "d" of "dd" or "3g" of "3gg".
+// Count is also the number of item to jump to with <ENTER>
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Motion {
+    pub count: Option<usize>,
+    pub operator: Option<char>,
+    typed_at: Option<Instant>,
+}
+
+impl Motion {
+    pub fn is_empty(&self) -> bool {
+        self.count.is_none() && self.operator.is_none()
+    }
+
+    // Time left before motion is dropped. None if nothing is typed
+    pub fn remaining(&self) -> Option<Duration> {
+        self.typed_at
+            .map(|typed_at| MOTION_TIMEOUT.saturating_sub(typed_at.elapsed()))
+    }
+
+    // Digit extends the count. Operator typed before the digit is dropped
+    pub fn with_digit(&self, digit: u32) -> Self {
+        let count = self
+            .count
+            .unwrap_or_default()
+            .saturating_mul(10)
+            .saturating_add(digit as usize);
+        Motion {
+            count: Some(count),
+            operator: None,
+            typed_at: Some(Instant::now()),
+        }
+    }
+
+    // Keep the count and wait for the operator to be pressed again
+    pub fn with_operator(&self, operator: char) -> Self {
+        Motion {
+            count: self.count,
+            operator: Some(operator),
+            typed_at: Some(Instant::now()),
+        }
+    }
+
+    // Shown in place of status while motion is being typed
+    pub fn describe(&self) -> Option<String> {
+        match (self.count, self.operator) {
+            (Some(count), None) => Some(format!("Go to {}..", count)),
+            (count, Some(operator)) => Some(format!(
+                "{}{}..",
+                count.map(|count| count.to_string()).unwrap_or_default(),
+                operator
+            )),
+            (None, None) => None,
+        }
+    }
+}
+
+// Index reached by moving `count` rows down or up from `current`. Motion stops at the first row
+// instead of wrapping. End of list is clamped by the list itself
+pub fn moved_index(current: usize, count: usize, down: bool) -> usize {
+    if down {
+        current.saturating_add(count)
+    } else {
+        current.saturating_sub(count)
+    }
+}
+
+impl ui::State<'_> {
+    // Drop the motion once it has not been continued for MOTION_TIMEOUT. Returns true if it was
+    // dropped now
+    pub fn expire_motion(&mut self) -> bool {
+        if self.motion.remaining() == Some(Duration::ZERO) {
+            self.motion = Motion::default();
+            return true;
+        }
+        false
+    }
+
+    // Position of selection in list of focused window. Scroll offset is taken as position in
+    // help and lyrics
+    pub fn list_position(&self) -> Option<usize> {
+        match self.active {
+            ui::Window::Sidebar => self.sidebar.selected(),
+            ui::Window::Musicbar => self.musicbar.1.selected(),
+            ui::Window::Playlistbar => self.playlistbar.1.selected(),
+            ui::Window::Artistbar => self.artistbar.1.selected(),
+            ui::Window::Queuebar => Some(self.queue_selected),
+            ui::Window::Helpbar | ui::Window::Lyrics => Some(self.overlay_scroll as usize),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_and_operator() {
+        let motion = Motion::default().with_digit(1).with_digit(2);
+        assert_eq!(motion.count, Some(12));
+        assert_eq!(motion.describe().as_deref(), Some("Go to 12.."));
+
+        let motion = motion.with_operator('d');
+        assert_eq!(motion.count, Some(12));
+        assert_eq!(motion.describe().as_deref(), Some("12d.."));
+        // Digit after operator drops the operator
+        assert_eq!(motion.with_digit(3).operator, None);
+        assert!(Motion::default().is_empty());
+
+        assert_eq!(moved_index(4, 3, true), 7);
+        assert_eq!(moved_index(4, 10, false), 0);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::ui::{
+    self, shared_import::*, MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX, MIDDLE_PLAYLIST_INDEX,
+};
+
+// Index of first item of list that is shown when list is drawn in given height. This is the same
+// calculation tui does to keep selected item in view. tui do not expose the offset it
+// calculated so it is calculated again here from offset of previous frame.
+// Every row of lists in this app is of single line
+fn scrolled_offset(offset: usize, selected: Option<usize>, len: usize, height: usize) -> usize {
+    if len == 0 || height == 0 {
+        return 0;
+    }
+    let offset = offset.min(len - 1);
+    let selected = selected.unwrap_or(0).min(len - 1);
+    if selected < offset {
+        selected
+    } else if selected >= offset + height {
+        selected + 1 - height
+    } else {
+        offset
+    }
+}
+
+impl ui::State<'_> {
+    // Update the scroll offset of every visible list as it was drawn in this frame. This should
+    // be called after the lists are drawn
+    pub fn update_list_offsets(&mut self) {
+        // 2 for border. Tables also have a line of header
+        let height = |area: Rect, header: u16| area.height.saturating_sub(2 + header) as usize;
+
+        if self.is_visible(&ui::Window::Sidebar) {
+            self.sidebar_offset = scrolled_offset(
+                self.sidebar_offset,
+                self.sidebar.selected(),
+                ui::utils::SIDEBAR_LIST_COUNT,
+                height(self.layout.shortcut, 0),
+            );
+        }
+        let lists = [
+            (
+                ui::Window::Musicbar,
+                MIDDLE_MUSIC_INDEX,
+                self.layout.music,
+                self.musicbar.1.selected(),
+                self.musicbar.0.len(),
+            ),
+            (
+                ui::Window::Playlistbar,
+                MIDDLE_PLAYLIST_INDEX,
+                self.layout.playlist,
+                self.playlistbar.1.selected(),
+                self.playlistbar.0.len(),
+            ),
+            (
+                ui::Window::Artistbar,
+                MIDDLE_ARTIST_INDEX,
+                self.layout.artist,
+                self.artistbar.1.selected(),
+                self.artistbar.0.len(),
+            ),
+        ];
+        for (window, index, area, selected, len) in lists {
+            if self.is_visible(&window) {
+                // Selection of list that is not active is removed while drawing
+                let selected = selected.filter(|_| self.active == window);
+                self.list_offset[index] =
+                    scrolled_offset(self.list_offset[index], selected, len, height(area, 1));
+            }
+        }
+    }
+
+    // Find the list drawn at given cell of terminal. Second member is the index of item at that
+    // cell or None if cell is at border, header or empty part of list.
+    // Returns None if no list is drawn there
+    pub fn list_at(&self, column: u16, row: u16) -> Option<(ui::Window, Option<usize>)> {
+        let lists = [
+            (
+                ui::Window::Sidebar,
+                self.layout.shortcut,
+                0,
+                self.sidebar_offset,
+                ui::utils::SIDEBAR_LIST_COUNT,
+            ),
+            (
+                ui::Window::Musicbar,
+                self.layout.music,
+                1,
+                self.list_offset[MIDDLE_MUSIC_INDEX],
+                self.musicbar.0.len(),
+            ),
+            (
+                ui::Window::Playlistbar,
+                self.layout.playlist,
+                1,
+                self.list_offset[MIDDLE_PLAYLIST_INDEX],
+                self.playlistbar.0.len(),
+            ),
+            (
+                ui::Window::Artistbar,
+                self.layout.artist,
+                1,
+                self.list_offset[MIDDLE_ARTIST_INDEX],
+                self.artistbar.0.len(),
+            ),
+        ];
+
+        let (window, area, header, offset, len) =
+            lists.into_iter().find(|(window, area, ..)| {
+                self.is_visible(window)
+                    && (area.left()..area.right()).contains(&column)
+                    && (area.top()..area.bottom()).contains(&row)
+            })?;
+
+        // First item is after the top border and header
+        let first_row = area.top() + 1 + header;
+        let is_inside = column > area.left()
+            && column + 1 < area.right()
+            && row >= first_row
+            && row + 1 < area.bottom();
+        let index = Some(offset + row.saturating_sub(first_row) as usize)
+            .filter(|index| is_inside && *index < len);
+
+        Some((window, index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_follows_selection() {
+        // Selection already in view
+        assert_eq!(scrolled_offset(0, Some(3), 20, 5), 0);
+        // Selection below the view scrolls to keep it at bottom
+        assert_eq!(scrolled_offset(0, Some(7), 20, 5), 3);
+        // Selection above the view scrolls to keep it at top
+        assert_eq!(scrolled_offset(6, Some(2), 20, 5), 2);
+        // Nothing selected is same as first item selected
+        assert_eq!(scrolled_offset(6, None, 20, 5), 0);
+        assert_eq!(scrolled_offset(4, Some(1), 0, 5), 0);
+    }
+}
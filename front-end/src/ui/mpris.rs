@@ -0,0 +1,473 @@
+use crate::ui::{self, shared_import::*, utils::LockRecover};
+use std::collections::HashMap;
+use std::sync::Condvar;
+use zbus::{
+    blocking, fdo, interface,
+    object_server::SignalEmitter,
+    zvariant::{ObjectPath, Value},
+};
+
+// Name this app is known as on session bus. Only the first running instance gets it
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.ytui_music";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+// Track id when nothing is being played as defined by mpris
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+// How often to check if app is quitting or if something has changed to be announced
+const POLL: Duration = Duration::from_millis(250);
+
+// Object path that identifies the music in mpris. Path may only have ascii letters, digits and
+// '_' so every other byte of youtube id is written as _xx
+pub fn track_id(music_id: &str) -> String {
+    let mut path = String::from("/org/ytui_music/track/");
+    for byte in music_id.bytes() {
+        if byte.is_ascii_alphanumeric() {
+            path.push(byte as char);
+        } else {
+            path.push_str(&format!("_{:02x}", byte));
+        }
+    }
+    path
+}
+
+fn micros(secs: f64) -> i64 {
+    (secs * 1_000_000.0) as i64
+}
+
+// Music being played as told to mpris clients
+#[derive(Clone, Debug, PartialEq)]
+struct Track {
+    id: String,
+    title: String,
+    artist: String,
+    // In microseconds. Zero if not known, eg: for live stream
+    length: i64,
+}
+
+// Properties of player that are announced with PropertiesChanged when they change. Position
+// changes all the time so it is only given when asked and is never announced
+#[derive(Clone, Debug, PartialEq)]
+struct Status {
+    playback: &'static str,
+    repeat: &'static str,
+    rate: f64,
+    shuffle: bool,
+    volume: f64,
+    track: Option<Track>,
+}
+
+impl Status {
+    fn metadata(&self) -> HashMap<&'static str, Value<'static>> {
+        let track = match &self.track {
+            Some(track) => track,
+            None => {
+                let no_track = ObjectPath::from_static_str_unchecked(NO_TRACK);
+                return HashMap::from([("mpris:trackid", Value::from(no_track))]);
+            }
+        };
+        let mut metadata = HashMap::from([
+            (
+                "mpris:trackid",
+                Value::from(ObjectPath::from_string_unchecked(track_id(&track.id))),
+            ),
+            ("xesam:title", Value::from(track.title.clone())),
+            (
+                "xesam:url",
+                Value::from(format!("https://www.youtube.com/watch?v={}", track.id)),
+            ),
+            (
+                "mpris:artUrl",
+                Value::from(format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", track.id)),
+            ),
+        ]);
+        if !track.artist.is_empty() {
+            metadata.insert("xesam:artist", Value::from(vec![track.artist.clone()]));
+        }
+        if track.length > 0 {
+            metadata.insert("mpris:length", Value::from(track.length));
+        }
+        metadata
+    }
+}
+
+// Names of properties that are not same in `status` and `announced`
+fn changed_properties(status: &Status, announced: &Status) -> Vec<&'static str> {
+    let changes = [
+        ("PlaybackStatus", status.playback != announced.playback),
+        ("LoopStatus", status.repeat != announced.repeat),
+        ("Rate", status.rate != announced.rate),
+        ("Shuffle", status.shuffle != announced.shuffle),
+        ("Volume", status.volume != announced.volume),
+        ("Metadata", status.track != announced.track),
+    ];
+    changes
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect()
+}
+
+impl ui::State<'_> {
+    fn mpris_status(&self) -> Status {
+        let playback = match self.bottom.playing {
+            None => "Stopped",
+            Some((_, true)) => "Playing",
+            Some((_, false)) => "Paused",
+        };
+        let repeat = match self.playback_behaviour.repeat {
+            ui::RepeatMode::All => "Playlist",
+            ui::RepeatMode::One => "Track",
+            ui::RepeatMode::Nothing => "None",
+        };
+        let track = match (&self.bottom.playing, &self.now_playing) {
+            (Some(_), Some(music)) => Some(Track {
+                id: music.id.clone(),
+                title: music.name.clone(),
+                artist: music.artist.clone(),
+                length: micros(self.bottom.music_duration.as_secs_f64()),
+            }),
+            _ => None,
+        };
+        Status {
+            playback,
+            repeat,
+            rate: f64::from(self.playback_behaviour.speed),
+            shuffle: self.playback_behaviour.shuffle,
+            volume: f64::from(self.playback_behaviour.volume) / 100.0,
+            track,
+        }
+    }
+
+    fn mpris_position(&self) -> i64 {
+        let position = self
+            .player
+            .get_property::<f64>("time-pos")
+            .unwrap_or_default();
+        micros(position)
+    }
+}
+
+// org.mpris.MediaPlayer2. Terminal cannot be raised and app is only quit from it
+struct Root;
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> &str {
+        "ytui-music"
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// org.mpris.MediaPlayer2.Player. Playback is controlled with same methods as shortcut keys do
+struct Player {
+    state: Arc<Mutex<ui::State<'static>>>,
+    notifier: Arc<Condvar>,
+}
+
+impl Player {
+    // Do the action on state and wake up the painter so that the change is shown right away
+    fn control<T>(&self, action: impl FnOnce(&mut ui::State) -> T) -> T {
+        let result = action(&mut self.state.lock_recover());
+        self.notifier.notify_all();
+        result
+    }
+
+    fn status(&self) -> Status {
+        self.state.lock_recover().mpris_status()
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    fn play_pause(&self) {
+        self.control(|state| state.toggle_pause());
+    }
+
+    fn play(&self) {
+        self.control(|state| {
+            if let Some((_, false)) = state.bottom.playing {
+                state.toggle_pause();
+            }
+        });
+    }
+
+    fn pause(&self) {
+        self.control(|state| {
+            if let Some((_, true)) = state.bottom.playing {
+                state.toggle_pause();
+            }
+        });
+    }
+
+    // Stop only pauses so that playback can be resumed with Play
+    fn stop(&self) {
+        self.pause();
+    }
+
+    fn next(&self) {
+        self.control(|state| state.change_track(true));
+    }
+
+    fn previous(&self) {
+        self.control(|state| state.change_track(false));
+    }
+
+    async fn seek(
+        &self,
+        offset: i64,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let position = self.control(|state| {
+            state.seek(offset as f64 / 1_000_000.0);
+            state.mpris_position()
+        });
+        Ok(Self::seeked(&emitter, position).await?)
+    }
+
+    // Position asked for music that is no longer playing is ignored
+    async fn set_position(
+        &self,
+        track: ObjectPath<'_>,
+        position: i64,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let seeked = self.control(|state| {
+            let playing = state.now_playing.as_ref().map(|music| track_id(&music.id));
+            if playing.as_deref() != Some(track.as_str()) {
+                return None;
+            }
+            let current = state.mpris_position();
+            state.seek((position - current) as f64 / 1_000_000.0);
+            Some(state.mpris_position())
+        });
+        if let Some(position) = seeked {
+            Self::seeked(&emitter, position).await?;
+        }
+        Ok(())
+    }
+
+    fn open_uri(&self, _uri: &str) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported(
+            "Opening uri is not supported".to_string(),
+        ))
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &str {
+        self.status().playback
+    }
+
+    #[zbus(property)]
+    fn loop_status(&self) -> &str {
+        self.status().repeat
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        self.status().rate
+    }
+
+    #[zbus(property)]
+    fn shuffle(&self) -> bool {
+        self.status().shuffle
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<&'static str, Value<'static>> {
+        self.status().metadata()
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.status().volume
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        self.state.lock_recover().mpris_position()
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        f64::from(ui::utils::MIN_SPEED)
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        f64::from(ui::utils::MAX_SPEED)
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+// Announce the property of given name as changed
+fn announce(
+    player: &blocking::object_server::InterfaceRef<Player>,
+    name: &str,
+) -> zbus::Result<()> {
+    let emitter = player.signal_emitter();
+    let player = player.get();
+    zbus::block_on(async {
+        match name {
+            "PlaybackStatus" => player.playback_status_changed(emitter).await,
+            "LoopStatus" => player.loop_status_changed(emitter).await,
+            "Rate" => player.rate_changed(emitter).await,
+            "Shuffle" => player.shuffle_changed(emitter).await,
+            "Volume" => player.volume_changed(emitter).await,
+            "Metadata" => player.metadata_changed(emitter).await,
+            _ => Ok(()),
+        }
+    })
+}
+
+// Serve the mpris interfaces on session bus until app quits. Media keys and desktop widgets then
+// control playback and show the music being played. Nothing is done if session bus is not there
+pub fn serve(state_original: &Arc<Mutex<ui::State<'static>>>, notifier: &Arc<Condvar>) {
+    let player = Player {
+        state: Arc::clone(state_original),
+        notifier: Arc::clone(notifier),
+    };
+    let connection = blocking::connection::Builder::session()
+        .and_then(|builder| builder.name(BUS_NAME))
+        .and_then(|builder| builder.serve_at(OBJECT_PATH, Root))
+        .and_then(|builder| builder.serve_at(OBJECT_PATH, player))
+        .and_then(|builder| builder.build());
+    let connection = match connection {
+        Ok(connection) => connection,
+        Err(err) => {
+            log::warn!("Cannot serve mpris on session bus. Error: {}", err);
+            return;
+        }
+    };
+    let player = match connection
+        .object_server()
+        .interface::<_, Player>(OBJECT_PATH)
+    {
+        Ok(player) => player,
+        Err(err) => {
+            log::warn!("Cannot serve mpris on session bus. Error: {}", err);
+            return;
+        }
+    };
+    log::info!("Serving mpris as {}", BUS_NAME);
+
+    let mut announced = state_original.lock_recover().mpris_status();
+    loop {
+        std::thread::sleep(POLL);
+        let status = {
+            let state = state_original.lock_recover();
+            if state.active == ui::Window::None {
+                break;
+            }
+            state.mpris_status()
+        };
+        for name in changed_properties(&status, &announced) {
+            if let Err(err) = announce(&player, name) {
+                log::warn!(
+                    "Cannot announce change of {} to mpris. Error: {}",
+                    name,
+                    err
+                );
+            }
+        }
+        announced = status;
+    }
+    // Bus releases the name once connection is dropped here
+    log::info!("Stopped serving mpris");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_id_is_object_path() {
+        assert_eq!(track_id("dQw4w9WgXcQ"), "/org/ytui_music/track/dQw4w9WgXcQ");
+        assert_eq!(track_id("a-b_c"), "/org/ytui_music/track/a_2db_5fc");
+        assert!(ObjectPath::try_from(track_id("a-b_c")).is_ok());
+    }
+
+    #[test]
+    fn only_changes_are_announced() {
+        let announced = Status {
+            playback: "Playing",
+            repeat: "None",
+            rate: 1.0,
+            shuffle: false,
+            volume: 1.0,
+            track: None,
+        };
+        let mut status = announced.clone();
+        assert!(changed_properties(&status, &announced).is_empty());
+        status.shuffle = true;
+        status.track = Some(Track {
+            id: "a".to_string(),
+            title: "a".to_string(),
+            artist: String::new(),
+            length: 0,
+        });
+        assert_eq!(
+            changed_properties(&status, &announced),
+            ["Shuffle", "Metadata"]
+        );
+        assert!(!status.metadata().contains_key("xesam:artist"));
+    }
+}
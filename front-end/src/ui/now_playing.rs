@@ -0,0 +1,228 @@
+use crate::ui;
+use config::initilize::CONFIG;
+use fetcher::ExtendDuration;
+use std::time::{Duration, Instant};
+
+pub const PLACEHOLDER: &str = ">> Play some Music <<";
+// Status when music being played is not in musicbar. See `State::select_now_playing`
+const NOT_IN_VIEW: &str = "Now playing not in view..";
+
+// Music should keep playing for this long before it is notified. This keeps skipping through
+// tracks from sending notification for every one of them
+pub const NOTIFY_DELAY: Duration = Duration::from_secs(2);
+// Thumbnail shown as icon of notification
+#[cfg(all(unix, not(target_os = "macos")))]
+const ICON_URL: &str = "https://i.ytimg.com/vi/{id}/mqdefault.jpg";
+// Notification is sent without icon if thumbnail is not downloaded in this time
+#[cfg(all(unix, not(target_os = "macos")))]
+const ICON_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Shorten the text to at most given number of characters. Shortened text ends with ellipsis
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated = text
+        .chars()
+        .take(width.saturating_sub(1))
+        .collect::<String>();
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+// Download thumbnail of the music to be used as icon of notification. Only the icon of latest
+// notification is kept in temporary directory. Returns None if it could not be downloaded
+#[cfg(all(unix, not(target_os = "macos")))]
+fn download_icon(music_id: &str) -> Option<std::path::PathBuf> {
+    let directory = std::env::temp_dir().join("ytui-music-icons");
+    let path = directory.join(format!("{}.jpg", music_id));
+    if path.exists() {
+        return Some(path);
+    }
+    let fetch = async {
+        let client = fetcher::utils::with_proxy(reqwest::ClientBuilder::new())
+            .timeout(ICON_TIMEOUT)
+            .build()
+            .ok()?;
+        let response = client
+            .get(ICON_URL.replace("{id}", music_id))
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.bytes().await.ok()
+    };
+    let jpeg = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .ok()?
+        .block_on(fetch)?;
+
+    // Icons of earlier notifications are already shown by now
+    if let Ok(entries) = std::fs::read_dir(&directory) {
+        entries.flatten().for_each(|entry| {
+            std::fs::remove_file(entry.path()).ok();
+        });
+    }
+    std::fs::create_dir_all(&directory).ok()?;
+    std::fs::write(&path, jpeg).ok()?;
+    Some(path)
+}
+
+// Show desktop notification about the music with its thumbnail as icon. This needs notify-send
+// (from libnotify) which is run in another thread so that ui do not wait for it or for the
+// thumbnail. Nothing is done if notify-send is not available
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn notify_track(music: &fetcher::MusicUnit) {
+    let music = music.clone();
+    std::thread::spawn(move || {
+        let mut command = std::process::Command::new("notify-send");
+        command.args(["--app-name", "ytui-music"]);
+        if let Some(icon) = download_icon(&music.id) {
+            command.arg("--icon").arg(icon);
+        }
+        let status = command
+            .arg(&music.name)
+            .arg(&music.artist)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+        if status.is_err() {
+            log::warn!("Cannot show notification. Is notify-send installed?");
+        }
+    });
+}
+
+// Notification is not supported on other platforms
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn notify_track(_music: &fetcher::MusicUnit) {}
+
+impl ui::State<'_> {
+    // Remember the music of musicbar being played along with rest of musicbar which is appended
+    // to mpv playlist. Music mpv advances to is then looked up from them.
+    // See `State::update_now_playing`
+    pub fn set_now_playing(&mut self, music_id: &str) {
+        self.track_changed_at = Some(Instant::now());
+        self.track_started_at = self.track_changed_at;
+        self.play_counted = false;
+        self.radio.heard(music_id);
+        self.now_playing = self
+            .musicbar
+            .0
+            .iter()
+            .find(|music| music.id == music_id)
+            .cloned();
+        self.played_from = self.musicbar.0.clone();
+    }
+
+    // Find the music being played after mpv moves to another track. When the music is not known,
+    // title and duration are taken from mpv and artist is left empty
+    pub fn update_now_playing(&mut self, title: &str, duration: Duration) {
+        let playing_id = match self.playing_id() {
+            Some(id) => id,
+            None => return,
+        };
+        if let Some(music) = &mut self.now_playing {
+            if music.id == playing_id {
+                // Music not found in any list is described by mpv. Title is only known after mpv
+                // has loaded the track so keep it updated
+                if music.artist.is_empty() {
+                    music.name = title.to_string();
+                    music.duration = duration.to_string();
+                }
+                return;
+            }
+        }
+
+        self.track_changed_at = Some(Instant::now());
+        self.track_started_at = self.track_changed_at;
+        self.play_counted = false;
+        self.radio.heard(&playing_id);
+        let known = self
+            .played_from
+            .iter()
+            .chain(self.queue.iter().map(|(_, music, _)| music))
+            .chain(self.musicbar.0.iter())
+            .find(|music| music.id == playing_id)
+            .cloned();
+        self.now_playing = Some(known.unwrap_or_else(|| fetcher::MusicUnit {
+            artist: String::new(),
+            artist_id: String::new(),
+            name: title.to_string(),
+            duration: duration.to_string(),
+            id: playing_id,
+            views: String::new(),
+        }));
+    }
+
+    // Send notification about music being played if it has been playing for NOTIFY_DELAY since
+    // track was changed. Nothing is done if notify_track is disabled in config
+    pub fn notify_track_change(&mut self) {
+        match self.track_changed_at {
+            Some(changed_at) if changed_at.elapsed() >= NOTIFY_DELAY => {}
+            _ => return,
+        }
+        self.track_changed_at = None;
+        if !CONFIG.constants.notify_track {
+            return;
+        }
+        if let Some(music) = &self.now_playing {
+            notify_track(music);
+        }
+    }
+
+    // Focus musicbar and select the music being played in it. Returns false if nothing had to be
+    // changed for that
+    pub fn select_now_playing(&mut self) -> bool {
+        let index = self.now_playing.as_ref().and_then(|playing| {
+            self.musicbar
+                .0
+                .iter()
+                .position(|music| music.id == playing.id)
+        });
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let changed = self.status != NOT_IN_VIEW;
+                self.status = NOT_IN_VIEW;
+                return changed;
+            }
+        };
+
+        let changed =
+            self.active != ui::Window::Musicbar || self.musicbar.1.selected() != Some(index);
+        self.focus(ui::Window::Musicbar);
+        self.musicbar.1.select(Some(index));
+        changed
+    }
+
+    // Line shown in status bar for the music being played. Text is truncated to fit in width
+    pub fn now_playing_line(&self, width: usize) -> String {
+        let line = match (&self.bottom.playing, &self.now_playing) {
+            (None, _) => PLACEHOLDER.to_string(),
+            (Some(_), Some(music)) if music.artist.is_empty() => music.name.clone(),
+            (Some(_), Some(music)) => format!("{} - {}", music.name, music.artist),
+            (Some((title, _)), None) if !title.is_empty() => title.clone(),
+            (Some(_), None) => PLACEHOLDER.to_string(),
+        };
+        truncate(&line, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_long_title() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly", 7), "exactly");
+        assert_eq!(truncate("a long title", 6), "a lon…");
+        assert_eq!(truncate("ümlaut title", 4), "üml…");
+        assert_eq!(truncate("anything", 0), "");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::ui::{
+    self,
+    shared_import::*,
+    utils::{ExtendBlock, ExtendStyle},
+};
+
+// On screen display is hidden after this long since it was last changed
+pub const OSD_DURATION: Duration = Duration::from_millis(1500);
+// Size of box including the border
+const WIDTH: u16 = 28;
+const HEIGHT: u16 = 3;
+
+// Area in center of screen where on screen display is drawn. Area is shrunk to fit in screen
+// smaller than the box
+pub fn osd_area(screen: Rect) -> Rect {
+    let width = WIDTH.min(screen.width);
+    let height = HEIGHT.min(screen.height);
+    Rect {
+        x: screen.x + (screen.width - width) / 2,
+        y: screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+pub fn get_osd(text: &str) -> Paragraph<'_> {
+    Paragraph::new(Span::styled(
+        text,
+        Style::list_title().add_modifier(Modifier::BOLD),
+    ))
+    .alignment(Alignment::Center)
+    .block(Block::active(String::new()))
+}
+
+impl ui::State<'_> {
+    // Show the text over everything else for OSD_DURATION. Text shown before is replaced and its
+    // timer starts again so that quick changes only show the latest value
+    pub fn show_osd(&mut self, text: String) {
+        self.osd = Some((text, Instant::now()));
+    }
+
+    // Hide on screen display once it has been shown for OSD_DURATION. Returns true if it was
+    // hidden now
+    pub fn expire_osd(&mut self) -> bool {
+        match &self.osd {
+            Some((_, shown_at)) if shown_at.elapsed() >= OSD_DURATION => {
+                self.osd = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn area_centered_in_screen() {
+        assert_eq!(
+            osd_area(Rect::new(0, 0, 100, 41)),
+            Rect::new(36, 19, WIDTH, HEIGHT)
+        );
+        // Box never goes outside of small screen
+        assert_eq!(osd_area(Rect::new(0, 0, 10, 2)), Rect::new(0, 0, 10, 2));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::ui::{self, utils::ExtendMpv};
+
+// Name mpv gives to its default device. This is always present in device list
+pub const DEFAULT_DEVICE: &str = "auto";
+
+// Index of device after the given one in list of (name, description) of devices. First device is
+// used if given one is not in list. None if there is no device at all
+pub fn next_device(devices: &[(String, String)], current: &str) -> Option<usize> {
+    if devices.is_empty() {
+        return None;
+    }
+    let next = devices
+        .iter()
+        .position(|(name, _)| name == current)
+        .map_or(0, |index| (index + 1) % devices.len());
+    Some(next)
+}
+
+// Returns true if chosen device is no longer in the list. Empty list means mpv could not list
+// the devices at all so it is not taken as device being removed
+pub fn device_gone(devices: &[(String, String)], current: &str) -> bool {
+    current != DEFAULT_DEVICE
+        && !devices.is_empty()
+        && !devices.iter().any(|(name, _)| name == current)
+}
+
+impl ui::State<'_> {
+    // Description of device mpv is playing to. Name of device is used if mpv do not describe it
+    pub fn output_label(&self) -> String {
+        let current = self.player.output_device();
+        self.player
+            .audio_devices()
+            .into_iter()
+            .find(|(name, _)| *name == current)
+            .map_or(current, |(_, description)| description)
+    }
+
+    // Play to the next audio output device. Mpv opens the new device itself and continues the
+    // music being played from where it was
+    pub fn cycle_output_device(&mut self) {
+        let devices = self.player.audio_devices();
+        let next = match next_device(&devices, &self.player.output_device()) {
+            Some(next) => &devices[next],
+            None => {
+                self.status = "No output device..";
+                return;
+            }
+        };
+        if self.player.set_output_device(&next.0) {
+            self.status = "Output changed..";
+            self.show_osd(next.1.clone());
+        } else {
+            log::warn!("Cannot switch audio output to {}", next.0);
+            self.show_toast(ui::ToastLevel::Error, "Cannot switch output".to_string());
+        }
+    }
+
+    // Go back to default device once chosen device is removed (eg: headphone unplugged). Returns
+    // true if device was changed
+    pub fn check_output_device(&mut self) -> bool {
+        let current = self.player.output_device();
+        if !device_gone(&self.player.audio_devices(), &current) {
+            return false;
+        }
+        log::warn!("Audio output {} is gone. Using default device", current);
+        self.player.set_output_device(DEFAULT_DEVICE);
+        self.show_toast(
+            ui::ToastLevel::Warning,
+            "Output gone, using default".to_string(),
+        );
+        self.show_osd(self.output_label());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_and_detect_removed_device() {
+        let devices = vec![
+            (DEFAULT_DEVICE.to_string(), "Autoselect device".to_string()),
+            ("pulse/headphone".to_string(), "Headphone".to_string()),
+        ];
+        assert_eq!(next_device(&devices, DEFAULT_DEVICE), Some(1));
+        assert_eq!(next_device(&devices, "pulse/headphone"), Some(0));
+        assert_eq!(next_device(&devices, "alsa/gone"), Some(0));
+        assert_eq!(next_device(&[], DEFAULT_DEVICE), None);
+
+        assert!(device_gone(&devices, "alsa/gone"));
+        assert!(!device_gone(&devices, "pulse/headphone"));
+        assert!(!device_gone(&devices, DEFAULT_DEVICE));
+        assert!(!device_gone(&[], "alsa/gone"));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::ui::{
+    self, shared_import::*, MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX, MIDDLE_PLAYLIST_INDEX,
+};
+use config::{initilize::CONFIG, keymap::Action, Constants};
+
+// Name under which hidden panes and size of panes are kept in settings table of storage
+const SETTING_NAME: &str = "hidden_panes";
+const RATIO_SETTING_NAME: &str = "pane_ratio";
+const SEARCH_SETTING_NAME: &str = "search_focused";
+
+// Panes are not made smaller than this by their ratio unless middle area itself is too small.
+// Height includes border and header of list
+pub const MIN_PANE_HEIGHT: u16 = 5;
+pub const MIN_PANE_WIDTH: u16 = 16;
+// Change in pane ratio for each press of grow_pane/shrink_pane key
+const RATIO_STEP: u16 = 5;
+
+// Index in MIDDLE_*_INDEX of pane shown in given window. None if window is not a pane that can
+// be hidden
+fn pane_index(window: &ui::Window) -> Option<usize> {
+    match window {
+        ui::Window::Musicbar => Some(MIDDLE_MUSIC_INDEX),
+        ui::Window::Playlistbar => Some(MIDDLE_PLAYLIST_INDEX),
+        ui::Window::Artistbar => Some(MIDDLE_ARTIST_INDEX),
+        _ => None,
+    }
+}
+
+// Window focused by given action. None if it is not one of focus actions
+pub fn focus_window_of(action: Action) -> Option<ui::Window> {
+    match action {
+        Action::FocusSidebar => Some(ui::Window::Sidebar),
+        Action::FocusMusicbar => Some(ui::Window::Musicbar),
+        Action::FocusPlaylistbar => Some(ui::Window::Playlistbar),
+        Action::FocusArtistbar => Some(ui::Window::Artistbar),
+        Action::FocusSearchbar => Some(ui::Window::Searchbar),
+        _ => None,
+    }
+}
+
+// Hidden panes as saved in storage. Each pane is one character, '1' if hidden. Value not saved
+// by this version is taken as every pane being shown
+pub fn hidden_from_setting(value: &str) -> [bool; 3] {
+    let mut hidden = [false; 3];
+    if value.len() != hidden.len() {
+        return hidden;
+    }
+    for (pane, ch) in hidden.iter_mut().zip(value.chars()) {
+        *pane = ch == '1';
+    }
+    hidden
+}
+
+pub fn setting_of(hidden: &[bool; 3]) -> String {
+    hidden
+        .iter()
+        .map(|&pane| if pane { '1' } else { '0' })
+        .collect()
+}
+
+// Returns true if pane in given index can be hidden. Last shown pane is always kept so that
+// there is something left in middle area
+pub fn can_hide(hidden: &[bool; 3], index: usize) -> bool {
+    hidden
+        .iter()
+        .enumerate()
+        .any(|(other, &pane)| other != index && !pane)
+}
+
+// Length of the first part when `total` is split at given percent. Both parts are kept at least
+// `min` long when there is space for both
+pub fn split_length(total: u16, percent: u16, min: u16) -> u16 {
+    let length = (u32::from(total) * u32::from(percent) / 100) as u16;
+    if total < min * 2 {
+        return length;
+    }
+    length.clamp(min, total - min)
+}
+
+// Scope that searches only the list shown in given pane
+pub fn scope_of(window: &ui::Window) -> Option<ui::SearchScope> {
+    match window {
+        ui::Window::Musicbar => Some(ui::SearchScope::Music),
+        ui::Window::Playlistbar => Some(ui::SearchScope::Playlist),
+        ui::Window::Artistbar => Some(ui::SearchScope::Artist),
+        _ => None,
+    }
+}
+
+// Pane ratio after pane in given index is grown or shrunk by RATIO_STEP. Playlistbar and
+// artistbar share the same width so one shrinks by what other grows. Ratio is returned as it is
+// if any value would go out of the range allowed in config
+pub fn resized(ratio: [u16; 3], index: usize, grow: bool) -> [u16; 3] {
+    let change = |value: u16, grow: bool| {
+        if grow {
+            value + RATIO_STEP
+        } else {
+            value.saturating_sub(RATIO_STEP)
+        }
+    };
+    let mut resized = ratio;
+    resized[index] = change(ratio[index], grow);
+    if index != MIDDLE_MUSIC_INDEX {
+        let other = MIDDLE_PLAYLIST_INDEX + MIDDLE_ARTIST_INDEX - index;
+        resized[other] = change(ratio[other], !grow);
+    }
+    let in_range =
+        |value: &u16| (Constants::MIN_PANE_RATIO..=Constants::MAX_PANE_RATIO).contains(value);
+    if resized.iter().all(in_range) {
+        resized
+    } else {
+        ratio
+    }
+}
+
+impl ui::State<'_> {
+    // Pane ratio changed in last run. Ratio from config is used if there was none
+    pub fn saved_pane_ratio() -> [u16; 3] {
+        let saved = Self::saved_setting(RATIO_SETTING_NAME).and_then(|value| {
+            let ratio: Vec<u16> = value.split(',').filter_map(|v| v.parse().ok()).collect();
+            <[u16; 3]>::try_from(ratio).ok()
+        });
+        match saved {
+            Some(ratio) => {
+                ratio.map(|value| value.clamp(Constants::MIN_PANE_RATIO, Constants::MAX_PANE_RATIO))
+            }
+            None => CONFIG.constants.pane_ratio,
+        }
+    }
+
+    // Grow or shrink the focused pane. Choice is kept for next run
+    pub fn resize_pane(&mut self, grow: bool) {
+        let index = match pane_index(&self.active) {
+            Some(index) => index,
+            None => {
+                self.status = "Focus a list to resize..";
+                return;
+            }
+        };
+        let ratio = resized(self.pane_ratio, index, grow);
+        if ratio == self.pane_ratio {
+            self.status = "Cannot resize more..";
+            return;
+        }
+        self.pane_ratio = ratio;
+        let value: Vec<String> = ratio.iter().map(u16::to_string).collect();
+        Self::save_setting(RATIO_SETTING_NAME, &value.join(","));
+        self.status = if grow {
+            "Pane grown.."
+        } else {
+            "Pane shrunk.."
+        };
+        self.mark_lists_dirty();
+    }
+
+    // Search scope chosen with search_focused key in last run. Every list is searched if there
+    // was none
+    pub fn saved_search_scope() -> ui::SearchScope {
+        match Self::saved_setting(SEARCH_SETTING_NAME).as_deref() {
+            Some("1") => ui::SearchScope::Focused,
+            _ => ui::SearchScope::All,
+        }
+    }
+
+    // Keep the active window if it is a pane so that it can be searched after focus moves to
+    // searchbar. This is called before every event is handled
+    pub fn remember_pane(&mut self) {
+        if pane_index(&self.active).is_some() {
+            self.last_pane = self.active.clone();
+        }
+    }
+
+    // Scope searched in SearchScope::Focused. Every list is searched if last focused pane is
+    // hidden since then as its result could not be seen
+    pub fn focused_scope(&self) -> ui::SearchScope {
+        match scope_of(&self.last_pane) {
+            Some(scope) if !self.is_hidden(&self.last_pane) => scope,
+            _ => ui::SearchScope::All,
+        }
+    }
+
+    // Switch between searching every list and searching only the focused list. Choice is kept
+    // for next run
+    pub fn toggle_search_focused(&mut self) {
+        let focused = self.search_scope != ui::SearchScope::Focused;
+        if focused {
+            self.search_scope = ui::SearchScope::Focused;
+            self.status = "Search focused list..";
+        } else {
+            self.search_scope = ui::SearchScope::All;
+            self.status = "Search all..";
+        }
+        Self::save_setting(SEARCH_SETTING_NAME, if focused { "1" } else { "0" });
+    }
+
+    // Panes hidden in last run. Nothing is hidden if there was none
+    pub fn saved_hidden() -> [bool; 3] {
+        Self::saved_setting(SETTING_NAME)
+            .map(|value| hidden_from_setting(&value))
+            .unwrap_or_default()
+    }
+
+    // Returns true if given window is a pane hidden by user
+    pub fn is_hidden(&self, window: &ui::Window) -> bool {
+        pane_index(window).is_some_and(|index| self.hidden[index])
+    }
+
+    // Hide the pane in given window or show it again if it is hidden. Focus moves to next shown
+    // window when active pane is hidden. Choice is kept for next run
+    pub fn toggle_pane(&mut self, window: ui::Window) {
+        let index = match pane_index(&window) {
+            Some(index) => index,
+            None => return,
+        };
+        if self.hidden[index] {
+            self.hidden[index] = false;
+            self.status = "Pane shown..";
+        } else if can_hide(&self.hidden, index) {
+            self.hidden[index] = true;
+            self.status = "Pane hidden..";
+        } else {
+            self.status = "Cannot hide every list..";
+            return;
+        }
+
+        if self.is_hidden(&self.active) {
+            let mut next = self.active.next();
+            while self.is_hidden(&next) {
+                next = next.next();
+            }
+            self.focus(next);
+        }
+        Self::save_setting(SETTING_NAME, &setting_of(&self.hidden));
+        self.mark_lists_dirty();
+    }
+
+    // Focus given window without cycling through the others. Hidden pane is left hidden and
+    // focus stays where it is
+    pub fn focus_pane(&mut self, window: ui::Window) {
+        if self.is_hidden(&window) {
+            self.status = "Pane is hidden..";
+        } else {
+            self.focus(window);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_one_pane_shown() {
+        let hidden = [false, true, false];
+        assert!(can_hide(&hidden, MIDDLE_MUSIC_INDEX));
+        assert!(can_hide(&[true, true, false], MIDDLE_PLAYLIST_INDEX));
+        assert!(!can_hide(&[true, true, false], MIDDLE_ARTIST_INDEX));
+
+        assert_eq!(setting_of(&hidden), "010");
+        assert_eq!(hidden_from_setting("010"), hidden);
+        // Value that was not saved by this version shows every pane
+        assert_eq!(hidden_from_setting("01"), [false; 3]);
+    }
+
+    #[test]
+    fn scope_of_each_pane() {
+        assert_eq!(
+            scope_of(&ui::Window::Playlistbar),
+            Some(ui::SearchScope::Playlist)
+        );
+        assert_eq!(
+            scope_of(&ui::Window::Artistbar),
+            Some(ui::SearchScope::Artist)
+        );
+        assert_eq!(scope_of(&ui::Window::Searchbar), None);
+    }
+
+    #[test]
+    fn window_of_focus_key() {
+        assert_eq!(
+            focus_window_of(Action::FocusPlaylistbar),
+            Some(ui::Window::Playlistbar)
+        );
+        assert_eq!(
+            focus_window_of(Action::FocusSearchbar),
+            Some(ui::Window::Searchbar)
+        );
+        assert_eq!(focus_window_of(Action::TogglePlay), None);
+    }
+
+    #[test]
+    fn resize_within_limits() {
+        assert_eq!(split_length(40, 50, MIN_PANE_HEIGHT), 20);
+        assert_eq!(split_length(40, 10, MIN_PANE_HEIGHT), MIN_PANE_HEIGHT);
+        assert_eq!(split_length(40, 95, MIN_PANE_HEIGHT), 35);
+        // Middle area too small for both is split as it is
+        assert_eq!(split_length(6, 50, MIN_PANE_HEIGHT), 3);
+
+        let ratio = [50, 65, 35];
+        assert_eq!(resized(ratio, MIDDLE_MUSIC_INDEX, true), [55, 65, 35]);
+        assert_eq!(resized(ratio, MIDDLE_ARTIST_INDEX, true), [50, 60, 40]);
+        assert_eq!(resized(ratio, MIDDLE_PLAYLIST_INDEX, false), [50, 60, 40]);
+        let ratio = [Constants::MAX_PANE_RATIO, 50, 50];
+        assert_eq!(resized(ratio, MIDDLE_MUSIC_INDEX, true), ratio);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::ui::{self, bookmark, shared_import::*};
+use config::{initilize::CONFIG, ExportFormat};
+use fetcher::ExtendDuration;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{path::Path, path::PathBuf, sync::mpsc};
+
+// Every exported file is named with this prefix. Latest of them is what import_list key reads
+const FILE_PREFIX: &str = "ytui-";
+
+// Music as written in json file. Url is only there for whoever reads the file, id is what is
+// read back
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ExportedMusic {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    artist: String,
+    #[serde(default)]
+    duration: String,
+    #[serde(default)]
+    url: String,
+}
+
+fn music_url(id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", id)
+}
+
+// Seconds in duration shown in list. eg: 03:20 or 1:02:03. None for anything else like live
+// stream that has no duration
+fn duration_secs(duration: &str) -> Option<u64> {
+    duration.split(':').try_fold(0, |secs, part| {
+        part.trim().parse::<u64>().ok().map(|part| secs * 60 + part)
+    })
+}
+
+// Extended m3u with length and "artist - title" of each music before its url. Length is -1 when
+// it is not known as m3u expects
+pub fn to_m3u(list: &[fetcher::MusicUnit]) -> String {
+    let mut content = String::from("#EXTM3U\n");
+    for music in list {
+        let secs = duration_secs(&music.duration).map_or(-1, |secs| secs as i64);
+        content.push_str(&format!(
+            "#EXTINF:{},{} - {}\n{}\n",
+            secs,
+            music.artist,
+            music.name,
+            music_url(&music.id)
+        ));
+    }
+    content
+}
+
+// Music of m3u file. Url that is not of youtube music is skipped. Title and artist are taken from
+// #EXTINF line before the url if there is one
+pub fn from_m3u(content: &str) -> Vec<fetcher::MusicUnit> {
+    let mut list = Vec::new();
+    let mut info = None;
+    for line in content.lines().map(str::trim) {
+        if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            info = extinf.split_once(',');
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let id = match bookmark::id_from_url(line) {
+            Some(id) => id.to_string(),
+            None => continue,
+        };
+        let (secs, name) = info.take().unwrap_or(("-1", ""));
+        let (artist, name) = name.split_once(" - ").unwrap_or(("", name));
+        let duration = match secs.trim().parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs).to_string(),
+            Err(_) => String::new(),
+        };
+        list.push(fetcher::MusicUnit {
+            artist: artist.to_string(),
+            artist_id: String::new(),
+            name: if name.is_empty() {
+                id.clone()
+            } else {
+                name.to_string()
+            },
+            duration,
+            id,
+            views: String::new(),
+        });
+    }
+    list
+}
+
+pub fn to_json(list: &[fetcher::MusicUnit]) -> Result<String, String> {
+    let exported = list
+        .iter()
+        .map(|music| ExportedMusic {
+            id: music.id.clone(),
+            title: music.name.clone(),
+            artist: music.artist.clone(),
+            duration: music.duration.clone(),
+            url: music_url(&music.id),
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&exported).map_err(|err| err.to_string())
+}
+
+pub fn from_json(content: &str) -> Result<Vec<fetcher::MusicUnit>, String> {
+    let exported =
+        serde_json::from_str::<Vec<ExportedMusic>>(content).map_err(|err| err.to_string())?;
+    Ok(exported
+        .into_iter()
+        .map(|music| fetcher::MusicUnit {
+            artist: music.artist,
+            artist_id: String::new(),
+            name: music.title,
+            duration: music.duration,
+            id: music.id,
+            views: String::new(),
+        })
+        .collect())
+}
+
+// Date and time in UTC of given unix time in seconds. eg: 20261014-093005
+fn timestamp(secs: u64) -> String {
+    let (days, secs) = (secs / 86400, secs % 86400);
+    // Civil date from days since 1970-01-01 as in
+    // http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719468;
+    let (era, day_of_era) = (days / 146097, days % 146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+// File in download path the list is exported to at given unix time. Time is in the name so that
+// earlier export is not overwritten. eg: ytui-queue-20261014-093005.m3u
+pub fn export_path(list_name: &str, format: ExportFormat, secs: u64) -> PathBuf {
+    let extension = match format {
+        ExportFormat::M3u => "m3u",
+        ExportFormat::Json => "json",
+    };
+    let name = format!(
+        "{}{}-{}.{}",
+        FILE_PREFIX,
+        list_name,
+        timestamp(secs),
+        extension
+    );
+    Path::new(&CONFIG.download.path).join(name)
+}
+
+// File in given directory that was exported last, if there is any
+fn latest_export(dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let path = entry.path();
+            let is_list = path.extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("m3u") || ext.eq_ignore_ascii_case("json")
+            });
+            is_list && entry.file_name().to_string_lossy().starts_with(FILE_PREFIX)
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max()
+        .map(|(_, path)| path)
+}
+
+// File is read as json if the path ends with .json and as m3u otherwise
+fn read_list(path: &Path) -> Result<Vec<fetcher::MusicUnit>, String> {
+    let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let is_json = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+    if is_json {
+        from_json(&content)
+    } else {
+        Ok(from_m3u(&content))
+    }
+}
+
+fn write_list(
+    path: &Path,
+    list: &[fetcher::MusicUnit],
+    format: ExportFormat,
+) -> Result<(), String> {
+    let content = match format {
+        ExportFormat::M3u => to_m3u(list),
+        ExportFormat::Json => to_json(list)?,
+    };
+    std::fs::write(path, content).map_err(|err| err.to_string())
+}
+
+impl ui::State<'_> {
+    // Export the queue while it is shown and the musicbar otherwise. File is written in another
+    // thread so that long list does not hold the ui. Whether it was written is told afterwards by
+    // `State::report_export`
+    pub fn export_list(&mut self) {
+        let (list_name, list) = if self.active == ui::Window::Queuebar {
+            let queue = self.queue.iter().map(|(_, music, _)| music.clone());
+            ("queue", queue.collect::<Vec<_>>())
+        } else {
+            ("music", self.musicbar.0.clone())
+        };
+        if list.is_empty() {
+            self.status = "Nothing to export..";
+            return;
+        }
+
+        let format = CONFIG.constants.export_format;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let path = export_path(list_name, format, now.as_secs());
+        let (sender, written) = mpsc::channel();
+        let (count, written_to) = (list.len(), path.clone());
+        std::thread::spawn(move || {
+            sender.send(write_list(&written_to, &list, format)).ok();
+        });
+        self.export = Some(ui::PendingExport {
+            path,
+            count,
+            written,
+        });
+        self.status = "Exporting..";
+    }
+
+    // Tell where the list was exported once the file is written. Returns true if anything was
+    // shown
+    pub fn report_export(&mut self) -> bool {
+        let written = match &self.export {
+            Some(export) => match export.written.try_recv() {
+                Ok(written) => written,
+                Err(mpsc::TryRecvError::Empty) => return false,
+                Err(mpsc::TryRecvError::Disconnected) => Err("Export stopped".to_string()),
+            },
+            None => return false,
+        };
+        let ui::PendingExport { path, count, .. } = match self.export.take() {
+            Some(export) => export,
+            None => return false,
+        };
+        match written {
+            Ok(()) => {
+                let text = format!("Exported {} music to {}", count, path.display());
+                self.show_toast(ui::ToastLevel::Info, text);
+            }
+            Err(err) => {
+                log::error!(
+                    "Cannot export to {path}. Error: {err}",
+                    path = path.display(),
+                    err = err
+                );
+                let text = format!("Cannot export to {}: {}", path.display(), err);
+                self.show_toast(ui::ToastLevel::Error, text);
+            }
+        }
+        true
+    }
+
+    // Add music of the file exported last from download path to the end of queue
+    pub fn import_latest(&mut self) {
+        match latest_export(Path::new(&CONFIG.download.path)) {
+            Some(path) => self.import_queue(&path),
+            None => self.status = "Nothing to import..",
+        }
+    }
+
+    // Add music of exported file to the end of queue. Queue stored by other instance is read first
+    // if queue is shared so that it is added to and not replaced
+    pub fn import_queue(&mut self, path: &Path) {
+        let list = match read_list(path) {
+            Ok(list) => list,
+            Err(err) => {
+                log::error!(
+                    "Cannot import {path}. Error: {err}",
+                    path = path.display(),
+                    err = err
+                );
+                let text = format!("Cannot import {}: {}", path.display(), err);
+                self.show_toast(ui::ToastLevel::Error, text);
+                return;
+            }
+        };
+        if list.is_empty() {
+            let text = format!("No music in {}", path.display());
+            self.show_toast(ui::ToastLevel::Warning, text);
+            return;
+        }
+
+        self.sync_queue();
+        let serial = self.queue.iter().map(|(added, ..)| added + 1).max();
+        let count = list.len();
+        self.queue.extend(
+            list.into_iter()
+                .enumerate()
+                .map(|(index, music)| (serial.unwrap_or_default() + index, music, false)),
+        );
+        if self.flush_queue() {
+            let text = format!("Queued {} music from {}", count, path.display());
+            self.show_toast(ui::ToastLevel::Info, text);
+        } else {
+            self.status = "Err queueing..";
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn music(id: &str, duration: &str) -> fetcher::MusicUnit {
+        fetcher::MusicUnit {
+            artist: "artist".to_string(),
+            name: format!("title {}", id),
+            duration: duration.to_string(),
+            ..ui::test_music(id)
+        }
+    }
+
+    #[test]
+    fn list_read_back() {
+        let list = vec![
+            music("aaaaaaaaaaa", "03:20"),
+            music("bbbbbbbbbbb", "1:02:03"),
+        ];
+
+        let m3u = to_m3u(&list);
+        assert!(m3u.contains("#EXTINF:200,artist - title aaaaaaaaaaa\n"));
+        assert_eq!(from_m3u(&m3u), list);
+        assert_eq!(from_json(&to_json(&list).unwrap()).unwrap(), list);
+
+        // Url not of youtube music is skipped and music without #EXTINF is titled by its id
+        let read = from_m3u("https://example.com/song.mp3\nhttps://youtu.be/ccccccccccc\n");
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].name, "ccccccccccc");
+        assert_eq!(duration_secs("LIVE"), None);
+    }
+
+    #[test]
+    fn export_named_by_time() {
+        assert_eq!(timestamp(0), "19700101-000000");
+        assert_eq!(timestamp(951782400), "20000229-000000");
+        assert_eq!(timestamp(1791970205), "20261014-093005");
+    }
+}
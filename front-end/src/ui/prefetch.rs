@@ -0,0 +1,45 @@
+use crate::ui::{self, crossfade, shared_import::*};
+use config::initilize::CONFIG;
+
+impl ui::State<'_> {
+    // Let mpv open the next entry of playlist ahead of time when music being played is about to
+    // end so that there is no gap while next music is being loaded. This is only allowed in last
+    // prefetch_secs of music as opening the stream early costs bandwidth even if next music is
+    // never played. Prefetch is asked again whenever next entry changes, eg: when queue changes.
+    // With crossfade, next music is prefetched early enough for fader to have opened it when
+    // current one starts fading out. See `State::crossfade`
+    pub fn prefetch_next(&mut self) {
+        let crossfade = CONFIG.constants.crossfade_secs;
+        let lookahead = if crossfade == 0 {
+            CONFIG.constants.prefetch_secs
+        } else {
+            CONFIG
+                .constants
+                .prefetch_secs
+                .max(crossfade + crossfade::LOAD_AHEAD_SECS)
+        };
+        if lookahead == 0 {
+            return;
+        }
+
+        let duration = self.bottom.music_duration;
+        let remaining = duration.saturating_sub(self.bottom.music_elapse);
+        let next = if duration.is_zero() || remaining > Duration::from_secs(lookahead) {
+            None
+        } else {
+            self.player
+                .get_property::<i64>("playlist-pos")
+                .ok()
+                .filter(|position| *position >= 0)
+                .and_then(|position| self.playlist_entry_id(position + 1))
+        };
+        if next == self.prefetched {
+            return;
+        }
+
+        self.player
+            .set_property("prefetch-playlist", next.is_some())
+            .ok();
+        self.prefetched = next;
+    }
+}
@@ -0,0 +1,297 @@
+use crate::ui::{
+    self, bookmark,
+    shared_import::*,
+    utils::{ExtendBlock, ExtendStyle},
+};
+use config::initilize::CONFIG;
+
+pub const TITLE: &str = "Play queue (<CTRL>+Up/Down to move, <DEL> to remove) ";
+
+// Position music at current position of queue of given length moves to when moved one step
+// towards the end (down) or the start (up). Moving past either end goes to the other end if wrap
+// is true, otherwise None is returned as music cannot be moved
+fn moved_position(current: usize, len: usize, down: bool, wrap: bool) -> Option<usize> {
+    let last = len.checked_sub(1)?;
+    match (down, wrap) {
+        (true, _) if current < last => Some(current + 1),
+        (false, _) if current > 0 => Some(current - 1),
+        (true, true) => Some(0),
+        (false, true) => Some(last),
+        (_, false) => None,
+    }
+}
+
+// Music of queue that is played next. That is the first one which has not been played yet.
+// Played music stays in queue until it is consumed, see `State::consumes_queue`
+pub fn next_queued(
+    queue: &VecDeque<(usize, fetcher::MusicUnit, bool)>,
+) -> Option<&fetcher::MusicUnit> {
+    queue
+        .iter()
+        .find(|(.., played)| !played)
+        .map(|(_, music, _)| music)
+}
+
+// Build the queue window. Selected entry in state is clamped here as queue may have been changed
+// by another instance since it was selected
+pub fn get_queue<'a>(state: &mut ui::State) -> (List<'a>, ListState) {
+    let mut list_state = ListState::default();
+    let block = Block::active(TITLE.to_string());
+
+    if state.queue.is_empty() {
+        let list = List::new(vec![ListItem::new(" Queue is empty")]).block(block);
+        return (list, list_state);
+    }
+
+    state.queue_selected = state.queue_selected.min(state.queue.len() - 1);
+    list_state.select(Some(state.queue_selected));
+
+    let items = state
+        .queue
+        .iter()
+        .enumerate()
+        .map(|(index, (_, music, _))| {
+            ListItem::new(format!(
+                " {}. {} - {} [{}]",
+                index + 1,
+                music.name,
+                music.artist,
+                music.duration
+            ))
+        })
+        .collect::<Vec<_>>();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::list_highlight());
+
+    (list, list_state)
+}
+
+impl ui::State<'_> {
+    // Move the selected music of queue one step towards the end (down) or the start (up) of queue.
+    // Selection moves along with the music. Music at either end is moved to the other end if
+    // wrap_navigation is set in config, otherwise it stays there
+    pub fn move_queued(&mut self, down: bool) {
+        self.sync_queue();
+        let current = self.queue_selected.min(self.queue.len().saturating_sub(1));
+        let target = match moved_position(
+            current,
+            self.queue.len(),
+            down,
+            CONFIG.constants.wrap_navigation,
+        ) {
+            Some(target) => target,
+            None => return,
+        };
+
+        // Wrapping around moves the music to other end instead of swapping the first and last
+        if let Some(music) = self.queue.remove(current) {
+            self.queue.insert(target, music);
+        }
+        self.queue_selected = target;
+        if !self.flush_queue() {
+            self.status = "Err queueing..";
+        }
+    }
+
+    // Remove every music from queue. Music already put next in mpv playlist is removed from there
+    // by `State::play_queue`
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+        self.queue_selected = 0;
+        self.status = if self.flush_queue() {
+            "Queue cleared.."
+        } else {
+            "Err queueing.."
+        };
+    }
+
+    // Remove the selected music from queue. Music being played keeps playing even if it is the
+    // one removed. If removed music was already put next in mpv playlist, it is replaced there by
+    // `State::play_queue`
+    pub fn remove_queued(&mut self) {
+        self.sync_queue();
+        let index = self.queue_selected.min(self.queue.len().saturating_sub(1));
+        if self.queue.remove(index).is_none() {
+            self.status = "Queue is empty..";
+            return;
+        }
+        self.queue_selected = index.min(self.queue.len().saturating_sub(1));
+        self.status = if self.flush_queue() {
+            "Removed from queue.."
+        } else {
+            "Err queueing.."
+        };
+    }
+
+    // Queued music is removed once finished or skipped only if consume is on. Repeating all
+    // needs the played music to play the queue again so consume is off while repeating all
+    pub fn consumes_queue(&self) -> bool {
+        self.playback_behaviour.consume_queue
+            && self.playback_behaviour.repeat != ui::RepeatMode::All
+    }
+
+    pub fn toggle_consume_queue(&mut self) {
+        let enable = !self.playback_behaviour.consume_queue;
+        self.playback_behaviour.consume_queue = enable;
+        self.status = match (enable, self.consumes_queue()) {
+            (true, true) => "Consume on..",
+            (true, false) => "Consume on after repeat all..",
+            (false, _) => "Consume off..",
+        };
+    }
+
+    // Mark first music of queue with given id that is yet to be played as played. Mark is kept in
+    // storage along with the queue. Returns the music if it was found
+    pub fn queued_played(&mut self, music_id: &str) -> Option<fetcher::MusicUnit> {
+        self.sync_queue();
+        let (_, music, played) = self
+            .queue
+            .iter_mut()
+            .find(|(_, music, played)| music.id == music_id && !*played)?;
+        *played = true;
+        let music = music.clone();
+        self.flush_queue();
+        Some(music)
+    }
+
+    // Played music is removed from queue once it is no longer the one being played, that is when
+    // it was finished or skipped, if queue is consumed. Returns true if queue was changed
+    fn consume_played(&mut self, playing: Option<&str>) -> bool {
+        self.sync_queue();
+        if !self.consumes_queue() {
+            return false;
+        }
+
+        let len = self.queue.len();
+        self.queue
+            .retain(|(_, music, played)| !played || Some(music.id.as_str()) == playing);
+        if self.queue.len() == len {
+            return false;
+        }
+        self.flush_queue();
+        true
+    }
+
+    // Once every music of queue is played, queue plays again from the start if repeating all.
+    // Music being played is not put right after itself as that is what repeat one does
+    fn replay_queue(&mut self, playing: Option<&str>) -> Option<fetcher::MusicUnit> {
+        if self.playback_behaviour.repeat != ui::RepeatMode::All
+            || !self.queue.iter().any(|(.., played)| *played)
+        {
+            return None;
+        }
+        let (_, first, _) = self.queue.front()?;
+        if Some(first.id.as_str()) == playing {
+            return None;
+        }
+        let first = first.clone();
+        for (.., played) in self.queue.iter_mut() {
+            *played = false;
+        }
+        self.flush_queue();
+        Some(first)
+    }
+
+    // Playback only ever goes through mpv playlist. So to play the queue, next music of queue is
+    // kept as the entry right after the one being played. When that entry starts playing it is
+    // marked as played and the next one is put in its place. Played music is removed from queue
+    // when consume is on, otherwise it stays there. Once every music of queue is played mpv
+    // continues with rest of the playlist as before, or the queue plays again if repeating all.
+    // This is called periodically. Returns true if queue was changed
+    pub fn play_queue(&mut self) -> bool {
+        if self.bottom.playing.is_none() {
+            return false;
+        }
+        let position = match self.player.get_property::<i64>("playlist-pos") {
+            Ok(position) if position >= 0 => position,
+            _ => return false,
+        };
+        let playing = self.playlist_entry_id(position);
+
+        let mut changed = self.consume_played(playing.as_deref());
+        if let Some(queued) = self.queued_next.take() {
+            if playing.as_deref() == Some(queued.as_str()) {
+                // Queued music is now playing
+                if let Some(music) = self.queued_played(&queued) {
+                    Self::add_to_recents(&music);
+                    changed = true;
+                }
+            } else if self.playlist_entry_id(position + 1).as_deref() == Some(queued.as_str()) {
+                // Still waiting to be played. Keep it if it is still the next of queue,
+                // otherwise queue was reordered or changed and it is replaced below
+                let next = next_queued(&self.queue);
+                if next.map(|music| music.id.as_str()) == Some(queued.as_str()) {
+                    self.queued_next = Some(queued);
+                    return changed;
+                }
+                self.player
+                    .command("playlist-remove", &[(position + 1).to_string().as_str()])
+                    .ok();
+            }
+            // If neither of above, playlist was replaced by playing something else. Queue
+            // is scheduled again after the new entry
+        }
+
+        let next = match next_queued(&self.queue) {
+            Some(music) => Some(music.clone()),
+            None => self.replay_queue(playing.as_deref()),
+        };
+        if let Some(music) = next {
+            let count = self
+                .player
+                .get_property::<i64>("playlist-count")
+                .unwrap_or_default();
+            let url = format!("https://www.youtube.com/watch?v={}", music.id);
+            let appended = self
+                .player
+                .command("loadfile", &[url.as_str(), "append"])
+                .is_ok();
+            if appended {
+                let (from, to) = (count.to_string(), (position + 1).to_string());
+                self.player
+                    .command("playlist-move", &[from.as_str(), to.as_str()])
+                    .ok();
+                self.queued_next = Some(music.id.clone());
+            }
+        }
+        changed
+    }
+
+    pub fn playlist_entry_id(&self, position: i64) -> Option<String> {
+        let path = self
+            .player
+            .get_property::<String>(&format!("playlist/{}/filename", position))
+            .ok()?;
+        bookmark::id_from_url(&path).map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_with_and_without_wrap() {
+        assert_eq!(moved_position(1, 3, true, false), Some(2));
+        assert_eq!(moved_position(1, 3, false, false), Some(0));
+        assert_eq!(moved_position(2, 3, true, false), None);
+        assert_eq!(moved_position(0, 3, false, false), None);
+
+        assert_eq!(moved_position(2, 3, true, true), Some(0));
+        assert_eq!(moved_position(0, 3, false, true), Some(2));
+        assert_eq!(moved_position(0, 0, true, true), None);
+    }
+
+    #[test]
+    fn next_skips_played() {
+        let music = ui::test_music;
+        let mut queue = VecDeque::from(vec![(0, music("a"), false), (1, music("b"), false)]);
+        assert_eq!(next_queued(&queue).map(|m| m.id.as_str()), Some("a"));
+
+        queue[0].2 = true;
+        assert_eq!(next_queued(&queue).map(|m| m.id.as_str()), Some("b"));
+        queue[1].2 = true;
+        assert_eq!(next_queued(&queue), None);
+    }
+}
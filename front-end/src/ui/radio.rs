@@ -0,0 +1,142 @@
+use crate::ui::{self, shared_import::*};
+
+// Number of related music added to queue every time radio runs out of music
+pub const BATCH: usize = 5;
+// Number of recently played music that radio tries not to play again
+const HEARD_LIMIT: usize = 50;
+
+// Pick at most count of the related music. Music not heard recently comes first so that radio do
+// not keep circling between the same few tracks. Music related music was fetched for is never
+// picked
+pub fn pick_related(
+    related: Vec<fetcher::MusicUnit>,
+    heard: &VecDeque<String>,
+    seed: &str,
+    count: usize,
+) -> Vec<fetcher::MusicUnit> {
+    let (mut unheard, heard): (Vec<_>, Vec<_>) = related
+        .into_iter()
+        .filter(|music| music.id != seed)
+        .partition(|music| !heard.contains(&music.id));
+    unheard.extend(heard);
+    unheard.truncate(count);
+    unheard
+}
+
+impl ui::RadioMode {
+    // Remember the music as recently played. Only HEARD_LIMIT of them are kept
+    pub fn heard(&mut self, music_id: &str) {
+        if self.heard.back().map(String::as_str) == Some(music_id) {
+            return;
+        }
+        self.heard.push_back(music_id.to_string());
+        if self.heard.len() > HEARD_LIMIT {
+            self.heard.pop_front();
+        }
+    }
+}
+
+impl ui::State<'_> {
+    pub fn toggle_radio(&mut self) {
+        self.radio.enabled = !self.radio.enabled;
+        self.radio.seed = None;
+        self.status = if self.radio.enabled {
+            "Radio on.."
+        } else {
+            "Radio off.."
+        };
+    }
+
+    // Ask communicator for music related to the one being played once there is nothing to play
+    // after it. That is when mpv is at last entry of playlist and queue has nothing left to play.
+    // Repeating playlist or track never runs out of music so radio only starts when repeat is off.
+    // Related music is only asked once for each music. Returns true if it was asked now
+    pub fn check_radio(&mut self) -> bool {
+        if !self.radio.enabled
+            || !self.playback_behaviour.auto_advance
+            || self.playback_behaviour.repeat != ui::RepeatMode::Nothing
+            || ui::queue::next_queued(&self.queue).is_some()
+            || self.queued_next.is_some()
+        {
+            return false;
+        }
+        let position = self.player.get_property::<i64>("playlist-pos").ok();
+        let count = self.player.get_property::<i64>("playlist-count").ok();
+        match (position, count) {
+            (Some(position), Some(count)) if position >= 0 && position + 1 >= count => {}
+            _ => return false,
+        }
+        let music_id = match self.playing_id() {
+            Some(music_id) => music_id,
+            None => return false,
+        };
+        if self.radio.requested.as_deref() == Some(music_id.as_str()) {
+            return false;
+        }
+
+        self.radio.requested = Some(music_id.clone());
+        self.radio.seed = Some(music_id);
+        true
+    }
+
+    // Music whose related music is to be fetched for radio. See `State::check_radio`
+    pub fn take_radio_seed(&mut self) -> Option<String> {
+        self.radio.seed.take()
+    }
+
+    // Queue the related music fetched by communicator. When nothing could be fetched, nothing is
+    // queued and playback simply stops at end of current music
+    pub fn fill_radio(
+        &mut self,
+        seed: &str,
+        related: Result<Vec<fetcher::MusicUnit>, fetcher::ReturnAction>,
+    ) {
+        if !self.radio.enabled {
+            return;
+        }
+        let picked = match related {
+            Ok(related) => pick_related(related, &self.radio.heard, seed, BATCH),
+            Err(_) => Vec::new(),
+        };
+        if picked.is_empty() {
+            self.status = "Radio stopped..";
+            return;
+        }
+        for music in &picked {
+            self.enqueue_music(music);
+        }
+        self.status = "Radio queued..";
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ui::test_music as music;
+
+    #[test]
+    fn related_prefer_unheard() {
+        let related = vec![
+            music("a"),
+            music("seed"),
+            music("b"),
+            music("c"),
+            music("d"),
+        ];
+        let heard = VecDeque::from(vec!["a".to_string(), "c".to_string()]);
+        let ids = |picked: Vec<fetcher::MusicUnit>| {
+            picked.into_iter().map(|music| music.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(pick_related(related.clone(), &heard, "seed", 3)),
+            vec!["b", "d", "a"]
+        );
+        // Heard music is still played when nothing else is suggested
+        assert_eq!(
+            ids(pick_related(related, &heard, "seed", 10)),
+            vec!["b", "d", "a", "c"]
+        );
+        assert!(pick_related(vec![music("seed")], &heard, "seed", 3).is_empty());
+    }
+}
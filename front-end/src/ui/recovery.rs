@@ -0,0 +1,301 @@
+use crate::ui::{self, shared_import::*};
+use config::{initilize::CONFIG, OnPlayError};
+
+// mpv goes idle when music could not be loaded. It may also still be idle right after it is asked
+// to load so loading is only taken as failed after this long
+const FAILURE_GRACE: Duration = Duration::from_secs(2);
+// Music that could not be played stays in statusbox for this long before next one is played
+const SKIP_DELAY: Duration = Duration::from_secs(2);
+
+// Music loaded with `State::load_music` that has not started playing yet
+pub struct LoadAttempt {
+    music_id: String,
+    started_at: Instant,
+    // Music is loaded again once before it is given up
+    retried: bool,
+    // Set once music is given up and next one is to be played after SKIP_DELAY
+    failed_at: Option<Instant>,
+}
+
+impl LoadAttempt {
+    pub fn new(music_id: &str) -> Self {
+        LoadAttempt {
+            music_id: music_id.to_string(),
+            started_at: Instant::now(),
+            retried: false,
+            failed_at: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Recovery {
+    Retry,
+    // Play the next music
+    Skip,
+    GiveUp,
+}
+
+// What to do when music could not be loaded as per policy of the context it was played in
+pub fn recovery_for(policy: OnPlayError, retried: bool) -> Recovery {
+    match (policy, retried) {
+        (OnPlayError::Retry, false) => Recovery::Retry,
+        (OnPlayError::SkipNext, _) => Recovery::Skip,
+        (OnPlayError::Retry, true) | (OnPlayError::Stop, _) => Recovery::GiveUp,
+    }
+}
+
+// State of player needed to tell if loaded music has started. This is mpv except in tests
+pub trait LoadProbe {
+    // Audio of loaded music has started
+    fn has_started(&self) -> bool;
+    // Player has nothing loaded, which is what mpv does when it fails to load
+    fn is_idle(&self) -> bool;
+}
+
+impl LoadProbe for libmpv::Mpv {
+    fn has_started(&self) -> bool {
+        self.get_property::<i64>("audio-pts").is_ok()
+    }
+
+    fn is_idle(&self) -> bool {
+        self.get_property::<bool>("idle-active").unwrap_or_default()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoadState {
+    // Not known yet weather music will start
+    Waiting,
+    Started,
+    Failed(Recovery),
+}
+
+// Check the music being loaded. Failed music is recovered as per given policy
+pub fn check_load(
+    attempt: &LoadAttempt,
+    player: &impl LoadProbe,
+    policy: OnPlayError,
+) -> LoadState {
+    if player.has_started() {
+        return LoadState::Started;
+    }
+    if !player.is_idle() || attempt.started_at.elapsed() < FAILURE_GRACE {
+        return LoadState::Waiting;
+    }
+    LoadState::Failed(recovery_for(policy, attempt.retried))
+}
+
+// Music to play after the one that could not be played. Next music of queue comes first as it
+// would have been played next anyway, then the music after the failed one in list it was played
+// from
+pub fn next_after(
+    failed: &str,
+    queued: Option<&fetcher::MusicUnit>,
+    played_from: &[fetcher::MusicUnit],
+) -> Option<String> {
+    if let Some(music) = queued {
+        return Some(music.id.clone());
+    }
+    played_from
+        .iter()
+        .skip_while(|music| music.id != failed)
+        .nth(1)
+        .map(|music| music.id.clone())
+}
+
+impl ui::State<'_> {
+    // Title of music to tell user about. Id is used if the music is not in any list
+    pub fn music_title(&self, music_id: &str) -> String {
+        self.played_from
+            .iter()
+            .chain(self.queue.iter().map(|(_, music, _)| music))
+            .chain(self.musicbar.0.iter())
+            .find(|music| music.id == music_id)
+            .map_or_else(|| music_id.to_string(), |music| music.name.clone())
+    }
+
+    // Check if music loaded last time has started playing. Music mpv could not load is retried,
+    // skipped or playback is stopped as per on_play_error policy of autoplay if auto advance is
+    // on and of manual play otherwise. Returns true if anything on screen changed
+    pub fn check_playback(&mut self) -> bool {
+        let attempt = match &mut self.loading {
+            Some(attempt) => attempt,
+            None => return false,
+        };
+
+        if let Some(failed_at) = attempt.failed_at {
+            if failed_at.elapsed() < SKIP_DELAY {
+                return false;
+            }
+            let failed = attempt.music_id.clone();
+            self.loading = None;
+            self.skip_failed(&failed);
+            return true;
+        }
+
+        let policy = CONFIG
+            .constants
+            .on_play_error
+            .for_context(self.playback_behaviour.auto_advance);
+        let recovery = match check_load(attempt, &self.player, policy) {
+            LoadState::Waiting => return false,
+            LoadState::Started => {
+                self.loading = None;
+                return false;
+            }
+            LoadState::Failed(recovery) => recovery,
+        };
+
+        let music_id = attempt.music_id.clone();
+        match recovery {
+            Recovery::Retry => {
+                log::info!("Music {} did not start. Retrying..", music_id);
+                if self.load_music(&music_id).is_ok() {
+                    if let Some(attempt) = &mut self.loading {
+                        attempt.retried = true;
+                    }
+                    self.status = "Retrying playback..";
+                }
+            }
+            Recovery::Skip => {
+                log::warn!("Cannot play music {}. Skipping it..", music_id);
+                attempt.failed_at = Some(Instant::now());
+                self.report_unplayable(&music_id);
+            }
+            Recovery::GiveUp => {
+                log::warn!("Cannot play music {}. Giving up..", music_id);
+                self.loading = None;
+                self.bottom.playing = None;
+                self.report_unplayable(&music_id);
+            }
+        }
+        true
+    }
+
+    // Tell which music could not be played. Toast lasts for longer than SKIP_DELAY so that it is
+    // still seen once next music starts
+    pub fn report_unplayable(&mut self, music_id: &str) {
+        let text = format!("Couldn't play {}", self.music_title(music_id));
+        self.show_toast(ui::ToastLevel::Error, text);
+    }
+
+    // Play the music after the one that could not be played followed by rest of the list it was
+    // played from. Playback is stopped if there is nothing after it
+    fn skip_failed(&mut self, failed: &str) {
+        let played_from = self.played_from.clone();
+        let queued = ui::queue::next_queued(&self.queue);
+        let next = match next_after(failed, queued, &played_from) {
+            Some(next) => next,
+            None => {
+                self.bottom.playing = None;
+                return;
+            }
+        };
+        if queued.map(|music| music.id.as_str()) == Some(next.as_str()) {
+            self.queued_played(&next);
+            self.queued_next = None;
+        }
+        if self.load_music(&next).is_err() {
+            return;
+        }
+
+        // Music from queue is followed by the rest of list after the failed one
+        let after = if played_from.iter().any(|music| music.id == next) {
+            next.as_str()
+        } else {
+            failed
+        };
+        for music in played_from
+            .iter()
+            .skip_while(|music| music.id != after)
+            .skip(1)
+        {
+            self.append_music(&music.id);
+        }
+        self.played_from = played_from;
+        self.status = "Skipped unplayable..";
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ui::test_music as music;
+
+    // Player that never starts the music it is given
+    struct FailingPlayer {
+        idle: bool,
+    }
+
+    impl LoadProbe for FailingPlayer {
+        fn has_started(&self) -> bool {
+            false
+        }
+
+        fn is_idle(&self) -> bool {
+            self.idle
+        }
+    }
+
+    fn failed_attempt(retried: bool) -> LoadAttempt {
+        LoadAttempt {
+            retried,
+            started_at: Instant::now()
+                .checked_sub(FAILURE_GRACE)
+                .unwrap_or_else(Instant::now),
+            ..LoadAttempt::new("a")
+        }
+    }
+
+    #[test]
+    fn recover_as_per_policy() {
+        let failing = FailingPlayer { idle: true };
+        let check = |retried, policy| check_load(&failed_attempt(retried), &failing, policy);
+        assert_eq!(
+            check(false, OnPlayError::SkipNext),
+            LoadState::Failed(Recovery::Skip)
+        );
+        assert_eq!(
+            check(false, OnPlayError::Stop),
+            LoadState::Failed(Recovery::GiveUp)
+        );
+        assert_eq!(
+            check(false, OnPlayError::Retry),
+            LoadState::Failed(Recovery::Retry)
+        );
+        assert_eq!(
+            check(true, OnPlayError::Retry),
+            LoadState::Failed(Recovery::GiveUp)
+        );
+
+        // Mpv is still loading or has only just been asked to load
+        let loading = FailingPlayer { idle: false };
+        let attempt = failed_attempt(false);
+        assert_eq!(
+            check_load(&attempt, &loading, OnPlayError::SkipNext),
+            LoadState::Waiting
+        );
+        let fresh = LoadAttempt::new("a");
+        assert_eq!(
+            check_load(&fresh, &failing, OnPlayError::SkipNext),
+            LoadState::Waiting
+        );
+
+        let policy = config::PlayErrorPolicy::default();
+        assert_eq!(policy.for_context(true), OnPlayError::SkipNext);
+        assert_eq!(policy.for_context(false), OnPlayError::Stop);
+    }
+
+    #[test]
+    fn music_after_failed() {
+        let list = vec![music("a"), music("b"), music("c")];
+        assert_eq!(next_after("a", None, &list).as_deref(), Some("b"));
+        assert_eq!(next_after("c", None, &list), None);
+        // Failed music that is not in list has nothing after it
+        assert_eq!(next_after("x", None, &list), None);
+
+        let queued = music("q");
+        assert_eq!(next_after("c", Some(&queued), &list).as_deref(), Some("q"));
+    }
+}
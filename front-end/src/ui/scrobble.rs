@@ -0,0 +1,465 @@
+use crate::ui::{self, shared_import::*};
+use config::initilize::{CONFIG, STORAGE, TB_SCROBBLES};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+// Most scrobbles last.fm accepts in a single request
+const BATCH_LIMIT: usize = 50;
+// Scrobbles that could not be sent are tried again this often
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+// Last.fm only takes music longer than this
+const MIN_LENGTH: Duration = Duration::from_secs(30);
+// Session got from last.fm is kept in settings table so that password is only sent once
+const SESSION_SETTING: &str = "lastfm_session";
+
+// Artist and title of music as expected by last.fm. Music on youtube is often titled as
+// "Artist - Title" and auto generated channels are named as "Artist - Topic"
+pub fn track_of(music: &fetcher::MusicUnit) -> (String, String) {
+    match music.name.split_once(" - ") {
+        Some((artist, title)) if !artist.trim().is_empty() && !title.trim().is_empty() => {
+            (artist.trim().to_string(), title.trim().to_string())
+        }
+        _ => {
+            let artist = music.artist.trim_end_matches(" - Topic");
+            (artist.to_string(), music.name.clone())
+        }
+    }
+}
+
+// Returns true if music of given length is to be scrobbled. Length of live stream is not known
+// so it is never scrobbled
+pub fn is_scrobbled(duration: Duration) -> bool {
+    duration > MIN_LENGTH
+}
+
+// Signature of call with given parameters. Parameters are concatenated as name and value in
+// order of their name followed by the api secret. Last.fm takes md5 of that in lowercase hex
+pub fn signature(params: &[(String, String)], secret: &str) -> String {
+    let mut sorted: Vec<_> = params.iter().collect();
+    sorted.sort();
+    let joined: String = sorted
+        .into_iter()
+        .map(|(name, value)| format!("{}{}", name, value))
+        .collect();
+    format!("{:x}", md5::compute(format!("{}{}", joined, secret)))
+}
+
+// Music to tell last.fm about. Time are in seconds and played_at is unix time music started at
+struct Track {
+    artist: String,
+    title: String,
+    duration: u64,
+    played_at: u64,
+}
+
+enum Submit {
+    NowPlaying(Track),
+    Scrobble(Track),
+}
+
+enum Failure {
+    // Could not reach last.fm or last.fm is down for now. Scrobbles are kept to be sent later
+    Offline(String),
+    // Saved session is no longer valid. New one is asked for
+    Session,
+    // Wrong credentials or api account. Nothing is sent again in this run
+    Auth(String),
+    // Last.fm refused the call itself. Sending it again would not help
+    Rejected(String),
+}
+
+impl Failure {
+    // Error codes from https://www.last.fm/api/errorcodes
+    fn from_code(code: i64, message: String) -> Self {
+        match code {
+            9 => Failure::Session,
+            4 | 10 | 13 | 26 => Failure::Auth(message),
+            11 | 16 | 29 => Failure::Offline(message),
+            _ => Failure::Rejected(message),
+        }
+    }
+}
+
+// Talks to last.fm in a thread of its own so that ui never waits for network
+struct Worker {
+    runtime: tokio::runtime::Runtime,
+    client: reqwest::Client,
+    session: Option<String>,
+    // Set on authentication failure. Scrobbles are still queued for next run
+    disabled: bool,
+    status: mpsc::Sender<&'static str>,
+}
+
+impl Worker {
+    fn run(mut self, receiver: mpsc::Receiver<Submit>) {
+        // Scrobbles left from last run
+        self.flush();
+        loop {
+            match receiver.recv_timeout(RETRY_INTERVAL) {
+                Ok(Submit::NowPlaying(track)) => self.now_playing(&track),
+                Ok(Submit::Scrobble(track)) => {
+                    Self::enqueue(&track);
+                    self.flush();
+                }
+                Err(RecvTimeoutError::Timeout) => self.flush(),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    fn call(&self, mut params: Vec<(String, String)>) -> Result<serde_json::Value, Failure> {
+        let lastfm = &CONFIG.lastfm;
+        params.push(("api_key".to_string(), lastfm.api_key.clone()));
+        let sig = signature(&params, &lastfm.api_secret);
+        params.push(("api_sig".to_string(), sig));
+        params.push(("format".to_string(), "json".to_string()));
+
+        let request = async {
+            let response = self.client.post(API_URL).form(&params).send().await?;
+            response.json::<serde_json::Value>().await
+        };
+        let reply = self
+            .runtime
+            .block_on(request)
+            .map_err(|err| Failure::Offline(err.to_string()))?;
+        match reply["error"].as_i64() {
+            Some(code) => {
+                let message = reply["message"].as_str().unwrap_or_default().to_string();
+                Err(Failure::from_code(code, message))
+            }
+            None => Ok(reply),
+        }
+    }
+
+    fn session(&mut self) -> Result<String, Failure> {
+        if let Some(session) = &self.session {
+            return Ok(session.clone());
+        }
+        if let Some(saved) = ui::State::saved_setting(SESSION_SETTING).filter(|s| !s.is_empty()) {
+            self.session = Some(saved.clone());
+            return Ok(saved);
+        }
+
+        let lastfm = &CONFIG.lastfm;
+        let reply = self.call(vec![
+            ("method".to_string(), "auth.getMobileSession".to_string()),
+            ("username".to_string(), lastfm.username.clone()),
+            ("password".to_string(), lastfm.password.clone()),
+        ])?;
+        let session = match reply["session"]["key"].as_str() {
+            Some(key) => key.to_string(),
+            None => return Err(Failure::Auth("No session in reply".to_string())),
+        };
+        ui::State::save_setting(SESSION_SETTING, &session);
+        self.session = Some(session.clone());
+        Ok(session)
+    }
+
+    // Call method that needs the session. Expired session is replaced once
+    fn authorized(&mut self, params: Vec<(String, String)>) -> Result<(), Failure> {
+        for _ in 0..2 {
+            let mut params = params.clone();
+            params.push(("sk".to_string(), self.session()?));
+            match self.call(params) {
+                Err(Failure::Session) => {
+                    self.session = None;
+                    ui::State::save_setting(SESSION_SETTING, "");
+                }
+                result => return result.map(|_| ()),
+            }
+        }
+        Err(Failure::Auth("Session is refused".to_string()))
+    }
+
+    fn now_playing(&mut self, track: &Track) {
+        if self.disabled {
+            return;
+        }
+        let params = vec![
+            ("method".to_string(), "track.updateNowPlaying".to_string()),
+            ("artist".to_string(), track.artist.clone()),
+            ("track".to_string(), track.title.clone()),
+            ("duration".to_string(), track.duration.to_string()),
+        ];
+        if let Err(failure) = self.authorized(params) {
+            // Now playing is only meaningful right now so it is not kept for later
+            self.report(failure, "now playing");
+        }
+    }
+
+    // Send the queued scrobbles in batches. Scrobbles are removed from queue once last.fm
+    // answers for them, and are kept when last.fm could not be reached
+    fn flush(&mut self) {
+        while !self.disabled {
+            let batch = Self::queued();
+            if batch.is_empty() {
+                return;
+            }
+            let settled = match self.scrobble(&batch) {
+                // Whole batch is refused even if only some of them are wrong. They are then sent
+                // one at a time so that only the refused ones are dropped
+                Err(Failure::Rejected(_)) if batch.len() > 1 => batch.chunks(1).all(|single| {
+                    let result = self.scrobble(single);
+                    self.settle(single, result)
+                }),
+                result => self.settle(&batch, result),
+            };
+            if !settled {
+                return;
+            }
+        }
+    }
+
+    fn scrobble(&mut self, batch: &[(i64, Track)]) -> Result<(), Failure> {
+        let mut params = vec![("method".to_string(), "track.scrobble".to_string())];
+        for (index, (_, track)) in batch.iter().enumerate() {
+            params.extend([
+                (format!("artist[{}]", index), track.artist.clone()),
+                (format!("track[{}]", index), track.title.clone()),
+                (format!("timestamp[{}]", index), track.played_at.to_string()),
+                (format!("duration[{}]", index), track.duration.to_string()),
+            ]);
+        }
+        self.authorized(params)
+    }
+
+    // Remove scrobbles from queue once last.fm has answered for them. Returns false if they are
+    // kept to be sent later
+    fn settle(&mut self, batch: &[(i64, Track)], result: Result<(), Failure>) -> bool {
+        let rows: Vec<i64> = batch.iter().map(|(row, _)| *row).collect();
+        match result {
+            Ok(()) => {
+                log::info!("Scrobbled {} music to last.fm", rows.len());
+                Self::dequeue(&rows);
+                true
+            }
+            Err(Failure::Rejected(message)) => {
+                self.report(Failure::Rejected(message), "scrobble");
+                Self::dequeue(&rows);
+                true
+            }
+            Err(failure) => {
+                self.report(failure, "scrobble");
+                false
+            }
+        }
+    }
+
+    fn report(&mut self, failure: Failure, action: &str) {
+        let status = match failure {
+            Failure::Offline(err) => {
+                log::warn!("Cannot reach last.fm for {}. Error: {}", action, err);
+                return;
+            }
+            Failure::Auth(err) => {
+                log::error!(
+                    "Last.fm login failed. Scrobbling is stopped. Error: {}",
+                    err
+                );
+                self.disabled = true;
+                "Last.fm login failed.."
+            }
+            Failure::Session => {
+                log::error!("Last.fm refused the new session for {}", action);
+                "Scrobble failed.."
+            }
+            Failure::Rejected(err) => {
+                log::error!("Last.fm refused {}. Error: {}", action, err);
+                "Scrobble failed.."
+            }
+        };
+        self.status.send(status).ok();
+    }
+
+    fn enqueue(track: &Track) {
+        let query = format!(
+            "
+            INSERT INTO {tb_name}
+            (artist, title, duration, played_at)
+            VALUES (:artist, :title, :duration, :played_at)
+        ",
+            tb_name = TB_SCROBBLES
+        );
+        let (duration, played_at) = (track.duration.to_string(), track.played_at.to_string());
+        let args = [
+            (":artist", &track.artist),
+            (":title", &track.title),
+            (":duration", &duration),
+            (":played_at", &played_at),
+        ];
+        if let Err(err) = STORAGE.lock().unwrap().execute(&query, &args) {
+            log::error!("Error queueing scrobble. Error: {err}", err = err);
+        }
+    }
+
+    // Earliest queued scrobbles along with their rowid
+    fn queued() -> Vec<(i64, Track)> {
+        let query = format!(
+            "
+            SELECT rowid, artist, title, duration, played_at FROM {tb_name}
+            ORDER BY rowid LIMIT {limit}
+        ",
+            tb_name = TB_SCROBBLES,
+            limit = BATCH_LIMIT
+        );
+        let storage = STORAGE.lock().unwrap();
+        let mut statement = match storage.prepare(&query) {
+            Ok(statement) => statement,
+            Err(err) => {
+                log::error!("Error reading queued scrobbles. Error: {err}", err = err);
+                return Vec::new();
+            }
+        };
+        let rows = statement.query_map([], |row| {
+            let track = Track {
+                artist: row.get(1)?,
+                title: row.get(2)?,
+                duration: row.get::<_, i64>(3)? as u64,
+                played_at: row.get::<_, i64>(4)? as u64,
+            };
+            Ok((row.get(0)?, track))
+        });
+        match rows {
+            Ok(rows) => rows.flatten().collect(),
+            Err(err) => {
+                log::error!("Error reading queued scrobbles. Error: {err}", err = err);
+                Vec::new()
+            }
+        }
+    }
+
+    fn dequeue(rows: &[i64]) {
+        let ids: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+        let query = format!(
+            "DELETE FROM {tb_name} WHERE rowid IN ({ids})",
+            tb_name = TB_SCROBBLES,
+            ids = ids.join(",")
+        );
+        if let Err(err) = STORAGE.lock().unwrap().execute_batch(&query) {
+            log::error!("Error removing sent scrobbles. Error: {err}", err = err);
+        }
+    }
+}
+
+// Handle kept in ui state to send plays to last.fm
+pub struct Scrobbler {
+    sender: mpsc::Sender<Submit>,
+    status: mpsc::Receiver<&'static str>,
+    // Start of music whose now playing is already sent
+    announced: Option<Instant>,
+}
+
+impl Scrobbler {
+    // None if last.fm account is not given in config
+    pub fn new() -> Option<Self> {
+        if !CONFIG.lastfm.is_configured() {
+            return None;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let (status_sender, status) = mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build();
+            let client = fetcher::utils::with_proxy(reqwest::ClientBuilder::new()).build();
+            match (runtime, client) {
+                (Ok(runtime), Ok(client)) => Worker {
+                    runtime,
+                    client,
+                    session: None,
+                    disabled: false,
+                    status: status_sender,
+                }
+                .run(receiver),
+                _ => log::error!("Cannot start scrobbler"),
+            }
+        });
+        Some(Scrobbler {
+            sender,
+            status,
+            announced: None,
+        })
+    }
+
+    // Tell last.fm about music that started at `started`. This is sent once for every start
+    pub fn now_playing(
+        &mut self,
+        music: &fetcher::MusicUnit,
+        started: Option<Instant>,
+        duration: Duration,
+    ) {
+        if started.is_none() || self.announced == started {
+            return;
+        }
+        self.announced = started;
+        let track = Self::track(music, Duration::ZERO, duration);
+        self.sender.send(Submit::NowPlaying(track)).ok();
+    }
+
+    // Scrobble music that has played for `elapsed`. Scrobble is queued until last.fm takes it
+    pub fn scrobble(&self, music: &fetcher::MusicUnit, elapsed: Duration, duration: Duration) {
+        let track = Self::track(music, elapsed, duration);
+        self.sender.send(Submit::Scrobble(track)).ok();
+    }
+
+    // Status of last failure to be shown in statusbar, if any
+    pub fn take_status(&self) -> Option<&'static str> {
+        self.status.try_iter().last()
+    }
+
+    fn track(music: &fetcher::MusicUnit, elapsed: Duration, duration: Duration) -> Track {
+        let (artist, title) = track_of(music);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Track {
+            artist,
+            title,
+            duration: duration.as_secs(),
+            played_at: now.saturating_sub(elapsed).as_secs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_of_sorted_params() {
+        let params = vec![
+            ("method".to_string(), "auth.getSession".to_string()),
+            ("api_key".to_string(), "key".to_string()),
+        ];
+        // md5 of "api_keykeymethodauth.getSessionsecret"
+        assert_eq!(
+            signature(&params, "secret"),
+            "22c8184cc52cd5d7a67f5a8b092ff8b6"
+        );
+    }
+
+    #[test]
+    fn short_music_not_scrobbled() {
+        assert!(is_scrobbled(Duration::from_secs(31)));
+        assert!(!is_scrobbled(Duration::from_secs(30)));
+        assert!(!is_scrobbled(Duration::ZERO));
+    }
+
+    #[test]
+    fn artist_from_title() {
+        let music = |name: &str, artist: &str| fetcher::MusicUnit {
+            name: name.to_string(),
+            artist: artist.to_string(),
+            ..ui::test_music("id")
+        };
+        assert_eq!(
+            track_of(&music("Queen - Bohemian Rhapsody", "Queen Official")),
+            ("Queen".to_string(), "Bohemian Rhapsody".to_string())
+        );
+        assert_eq!(
+            track_of(&music("Bohemian Rhapsody", "Queen - Topic")),
+            ("Queen".to_string(), "Bohemian Rhapsody".to_string())
+        );
+    }
+}
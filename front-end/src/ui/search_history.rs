@@ -0,0 +1,105 @@
+use crate::ui;
+use config::initilize::{CONFIG, STORAGE, TB_SEARCHES};
+
+// Previously searched queries with the latest one at first. Only search_history_limit of them
+// are kept
+pub fn load() -> Vec<String> {
+    let query = format!(
+        "
+        SELECT query FROM {tb_name}
+        ORDER BY searched_at DESC, rowid DESC
+        LIMIT {limit}
+    ",
+        tb_name = TB_SEARCHES,
+        limit = CONFIG.constants.search_history_limit
+    );
+    let conn = STORAGE.lock().unwrap();
+    let mut stmt = match conn.prepare(&query) {
+        Ok(stmt) => stmt,
+        Err(err) => {
+            log::error!("Cannot read search history. Error: {err}", err = err);
+            return Vec::new();
+        }
+    };
+    let rows = stmt.query_map([], |row| row.get(0));
+    match rows {
+        Ok(rows) => rows.filter_map(Result::ok).collect(),
+        Err(err) => {
+            log::error!("Cannot read search history. Error: {err}", err = err);
+            Vec::new()
+        }
+    }
+}
+
+impl ui::State<'_> {
+    // Remember the query as latest searched one. Same query searched before is moved to the top
+    pub fn save_search(&mut self, query: &str) {
+        self.forget_recall();
+        if query.is_empty() || CONFIG.constants.search_history_limit == 0 {
+            return;
+        }
+        self.search_history.retain(|searched| searched != query);
+        self.search_history.insert(0, query.to_string());
+        self.search_history
+            .truncate(CONFIG.constants.search_history_limit);
+
+        let insert_query = format!(
+            "
+            INSERT OR REPLACE INTO {tb_name}
+            (query, searched_at)
+            VALUES (:query, strftime('%s', 'now'))
+        ",
+            tb_name = TB_SEARCHES
+        );
+        let evict_query = format!(
+            "
+            DELETE FROM {tb_name}
+            WHERE query NOT IN (
+                SELECT query FROM {tb_name}
+                ORDER BY searched_at DESC, rowid DESC
+                LIMIT {limit}
+            )
+        ",
+            tb_name = TB_SEARCHES,
+            limit = CONFIG.constants.search_history_limit
+        );
+
+        let conn = STORAGE.lock().unwrap();
+        let res = conn
+            .execute(&insert_query, &[(":query", query)])
+            .and_then(|_| conn.execute(&evict_query, []));
+        if let Err(err) = res {
+            log::error!("Error saving search history. Error: {err}", err = err);
+        }
+    }
+
+    // Put older (or newer) searched query in searchbar. What user was typing before recalling is
+    // kept aside and put back when moving newer than the latest query.
+    // This only changes the searchbar. Query is searched when user press ENTER
+    pub fn recall_search(&mut self, older: bool) {
+        let index = match (self.search_recalled, older) {
+            (None, true) if !self.search_history.is_empty() => {
+                self.search_draft = std::mem::take(&mut self.search.0);
+                0
+            }
+            (Some(index), true) => (index + 1).min(self.search_history.len() - 1),
+            (Some(0), false) => {
+                self.search.0 = std::mem::take(&mut self.search_draft);
+                self.search_recalled = None;
+                return;
+            }
+            (Some(index), false) => index - 1,
+            (None, _) => return,
+        };
+        self.search_recalled = Some(index);
+        self.search.0 = self.search_history[index].clone();
+        // Recalled query is not typed so live search should not pick it up
+        self.search_typed_at = None;
+    }
+
+    // Take whatever is in searchbar as what user is typing. Next recall starts from latest query
+    pub fn forget_recall(&mut self) {
+        self.search_recalled = None;
+        self.search_draft.clear();
+    }
+}
@@ -0,0 +1,132 @@
+use crate::ui::{self, shared_import::*};
+use config::initilize::{CONFIG, STORAGE, TB_SETTINGS};
+
+// What user was doing when app was last quit. This is saved in settings table of storage as json
+#[derive(Serialize, Deserialize)]
+struct Session {
+    filled_source: (
+        ui::MusicbarSource,
+        ui::PlaylistbarSource,
+        ui::ArtistbarSource,
+    ),
+    fetched_page: [Option<usize>; 3],
+    // One of sidebar/music/playlist/artist. Other windows are not restored
+    active: String,
+    sidebar: Option<usize>,
+    // Last searched query
+    search: String,
+    // Id of music being played and position in seconds
+    playing: Option<(String, f64)>,
+    // True if music being played was paused. Missing in session saved by older version
+    #[serde(default)]
+    paused: bool,
+}
+
+impl ui::State<'_> {
+    // Save the session so that it can be restored in next run. Failure is only logged as this is
+    // done while quitting
+    pub fn save_session(&self) {
+        let active = match self.active {
+            ui::Window::Musicbar => "music",
+            ui::Window::Playlistbar => "playlist",
+            ui::Window::Artistbar => "artist",
+            _ => "sidebar",
+        };
+        let playing = self.playing_id().map(|id| {
+            let position = self
+                .player
+                .get_property::<f64>("time-pos")
+                .unwrap_or_default();
+            (id, position)
+        });
+        let session = Session {
+            filled_source: self.filled_source.clone(),
+            fetched_page: self.fetched_page,
+            active: active.to_string(),
+            sidebar: self.sidebar.selected(),
+            search: self.search.1.clone(),
+            playing,
+            paused: matches!(self.bottom.playing, Some((_, false))),
+        };
+
+        let value = match serde_json::to_string(&session) {
+            Ok(value) => value,
+            Err(err) => {
+                log::error!("Error serializing session. Error: {err}", err = err);
+                return;
+            }
+        };
+        let query = format!(
+            "
+            INSERT OR REPLACE INTO {tb_name}
+            (name, value)
+            VALUES ('session', :value)
+        ",
+            tb_name = TB_SETTINGS
+        );
+        if let Err(err) = STORAGE
+            .lock()
+            .unwrap()
+            .execute(&query, &[(":value", &value)])
+        {
+            log::error!("Error saving session. Error: {err}", err = err);
+        }
+    }
+
+    // Fill the lists from same source as when app was last quit. Last played music is also
+    // played again from same position if resume_playback is set in config.
+    // Session that cannot be read is ignored and app starts as usual
+    pub fn restore_session(&mut self) {
+        let query = format!(
+            "SELECT value FROM {tb_name} WHERE name = 'session'",
+            tb_name = TB_SETTINGS
+        );
+        let session = STORAGE
+            .lock()
+            .unwrap()
+            .query_row(&query, [], |row| row.get::<_, String>(0))
+            .ok()
+            .and_then(|value| serde_json::from_str::<Session>(&value).ok());
+        let session = match session {
+            Some(session) => session,
+            None => return,
+        };
+
+        self.filled_source = session.filled_source;
+        self.fetched_page = session.fetched_page;
+        self.active = match session.active.as_str() {
+            "music" => ui::Window::Musicbar,
+            "playlist" => ui::Window::Playlistbar,
+            "artist" => ui::Window::Artistbar,
+            _ => ui::Window::Sidebar,
+        };
+        if let Some(selected) = session.sidebar {
+            if selected < ui::utils::SIDEBAR_LIST_COUNT {
+                self.sidebar.select(Some(selected));
+            }
+        }
+        self.search.1 = session.search;
+
+        if let (true, Some((music_id, position))) =
+            (CONFIG.constants.resume_playback, session.playing)
+        {
+            self.play_music(&music_id).ok();
+            // Position can only be sought once mpv has loaded the music
+            self.resume_position = Some(position);
+            if session.paused {
+                self.player.pause().ok();
+                self.sync_pause();
+            }
+        }
+    }
+
+    // Seek to position of music saved in session once it is loaded. See `restore_session`
+    pub fn seek_resume_position(&mut self) {
+        if self.bottom.music_duration.is_zero() {
+            return;
+        }
+        if let Some(position) = self.resume_position.take() {
+            self.player.seek_absolute(position).ok();
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::ui::{self, shared_import::*};
+use config::initilize::CONFIG;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+// Random generator for the session. Same shuffle_seed gives same order every run while 0 gives
+// another one each time
+pub fn seeded_rng(seed: u64) -> StdRng {
+    if seed == 0 {
+        StdRng::from_entropy()
+    } else {
+        StdRng::seed_from_u64(seed)
+    }
+}
+
+// Order of playlist entry after shuffling entries from `first` to `count`. Entries before
+// `first` (played music, the one being played and queued music after it) keep their place. So
+// the music that was just played is never next when there is anything else to play
+pub fn shuffled_order(count: usize, first: usize, rng: &mut StdRng) -> Vec<usize> {
+    let mut order = (0..count).collect::<Vec<_>>();
+    if first < count {
+        order[first..].shuffle(rng);
+    }
+    order
+}
+
+// Order of playlist entry that puts `current` entries back as they were in `original`. Entries
+// that were not there before shuffling (eg: queued later) go after them as they are now
+pub fn restored_order(current: &[String], original: &[String]) -> Vec<usize> {
+    let mut order = (0..current.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| {
+        original
+            .iter()
+            .position(|path| *path == current[index])
+            .unwrap_or(original.len())
+    });
+    order
+}
+
+// Moves needed in mpv playlist to put entries in `order` where `order[i]` is the entry that
+// should end up at `i`. Each move is from and to index as expected by `playlist-move`
+pub fn moves_for(order: &[usize]) -> Vec<(usize, usize)> {
+    let mut playlist = (0..order.len()).collect::<Vec<_>>();
+    let mut moves = Vec::new();
+    for (to, entry) in order.iter().enumerate() {
+        let from = playlist.iter().position(|e| e == entry).unwrap_or(to);
+        if from != to {
+            let entry = playlist.remove(from);
+            playlist.insert(to, entry);
+            moves.push((from, to));
+        }
+    }
+    moves
+}
+
+impl ui::State<'_> {
+    pub fn toggle_shuffle(&mut self) {
+        let shuffle = !self.playback_behaviour.shuffle;
+        if shuffle {
+            self.shuffle_playlist();
+            self.status = "Shuffled..";
+        } else {
+            self.unshuffle_playlist();
+            self.status = "Unshuffled..";
+        }
+        self.playback_behaviour.shuffle = shuffle;
+    }
+
+    // Shuffle the playlist entries that are yet to be played. Order before shuffling is kept
+    // to be restored by `State::unshuffle_playlist`. Mpv's own playlist-shuffle is not used as
+    // it also moves the entry being played and cannot be seeded
+    pub fn shuffle_playlist(&mut self) {
+        let paths = self.playlist_paths();
+        let position = self
+            .player
+            .get_property::<i64>("playlist-pos")
+            .unwrap_or(-1);
+        // Queued music right after the playing one stays there. See `State::play_queue`
+        let queued = usize::from(self.queued_next.is_some());
+        let first = usize::try_from(position + 1).unwrap_or_default() + queued;
+        let order = shuffled_order(paths.len(), first, &mut self.shuffle.rng);
+        self.reorder_playlist(&order);
+        self.shuffle.original = paths;
+    }
+
+    pub fn unshuffle_playlist(&mut self) {
+        let original = std::mem::take(&mut self.shuffle.original);
+        let order = restored_order(&self.playlist_paths(), &original);
+        self.reorder_playlist(&order);
+    }
+
+    fn playlist_paths(&self) -> Vec<String> {
+        let count = self
+            .player
+            .get_property::<i64>("playlist-count")
+            .unwrap_or_default();
+        (0..count)
+            .filter_map(|index| {
+                self.player
+                    .get_property::<String>(&format!("playlist/{}/filename", index))
+                    .ok()
+            })
+            .collect()
+    }
+
+    fn reorder_playlist(&self, order: &[usize]) {
+        for (from, to) in moves_for(order) {
+            let (from, to) = (from.to_string(), to.to_string());
+            self.player
+                .command("playlist-move", &[from.as_str(), to.as_str()])
+                .ok();
+        }
+    }
+
+    pub fn new_shuffle() -> ui::ShuffleOrder {
+        ui::ShuffleOrder {
+            rng: seeded_rng(CONFIG.constants.shuffle_seed),
+            original: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(order: &[usize]) -> Vec<usize> {
+        let mut playlist = (0..order.len()).collect::<Vec<_>>();
+        for (from, to) in moves_for(order) {
+            let entry = playlist.remove(from);
+            playlist.insert(to, entry);
+        }
+        playlist
+    }
+
+    #[test]
+    fn shuffle_keeps_played_entries() {
+        let mut rng = seeded_rng(7);
+        let order = shuffled_order(20, 3, &mut rng);
+        assert_eq!(order[..3], [0, 1, 2]);
+        let mut rest = order[3..].to_vec();
+        rest.sort_unstable();
+        assert_eq!(rest, (3..20).collect::<Vec<_>>());
+        // Same seed gives same order
+        assert_eq!(shuffled_order(20, 3, &mut seeded_rng(7)), order);
+        assert_eq!(shuffled_order(2, 5, &mut rng), [0, 1]);
+    }
+
+    #[test]
+    fn moves_reach_order() {
+        let order = shuffled_order(10, 0, &mut seeded_rng(3));
+        assert_eq!(apply(&order), order);
+        assert!(moves_for(&[0, 1, 2]).is_empty());
+    }
+
+    #[test]
+    fn restore_original_order() {
+        let path = |p: &str| p.to_string();
+        let original = [path("a"), path("b"), path("c")];
+        let current = [path("c"), path("x"), path("a"), path("b")];
+        assert_eq!(restored_order(&current, &original), [2, 3, 0, 1]);
+    }
+}
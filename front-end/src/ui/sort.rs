@@ -0,0 +1,273 @@
+use crate::ui::{
+    self, shared_import::*, MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX, MIDDLE_PLAYLIST_INDEX,
+};
+use fetcher::ExtendDuration;
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortKey {
+    // Title of music or name of playlist/artist
+    Name,
+    Duration,
+    Views,
+}
+
+// Order in which list is sorted. List without sort is shown in the order it was fetched
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl Sort {
+    // Shown in title of sorted list
+    pub fn label(&self) -> String {
+        let key = match self.key {
+            SortKey::Name => "Name",
+            SortKey::Duration => "Length",
+            SortKey::Views => "Views",
+        };
+        format!("{} {}", key, if self.descending { "↓" } else { "↑" })
+    }
+}
+
+// Sort that comes after given one when sort key is pressed. Each key is sorted ascending then
+// descending and list goes back to fetched order after the last key
+pub fn next_sort(current: Option<Sort>, keys: &[SortKey]) -> Option<Sort> {
+    let position = match current {
+        None => 0,
+        Some(sort) => match keys.iter().position(|key| *key == sort.key) {
+            Some(index) => index * 2 + 1 + sort.descending as usize,
+            None => 0,
+        },
+    };
+    keys.get(position / 2).map(|key| Sort {
+        key: *key,
+        descending: position % 2 == 1,
+    })
+}
+
+// Item of list that can be sorted
+pub trait Sortable {
+    // Keys this type of item can be sorted by
+    const KEYS: &'static [SortKey];
+    fn compare(&self, other: &Self, key: SortKey) -> Ordering;
+    fn sort_id(&self) -> &str;
+}
+
+impl Sortable for fetcher::MusicUnit {
+    const KEYS: &'static [SortKey] = &[SortKey::Name, SortKey::Duration, SortKey::Views];
+
+    fn compare(&self, other: &Self, key: SortKey) -> Ordering {
+        match key {
+            SortKey::Name => self.name.to_lowercase().cmp(&other.name.to_lowercase()),
+            SortKey::Duration => {
+                Duration::from_string(&self.duration).cmp(&Duration::from_string(&other.duration))
+            }
+            SortKey::Views => {
+                fetcher::count_from_str(&self.views).cmp(&fetcher::count_from_str(&other.views))
+            }
+        }
+    }
+
+    fn sort_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Sortable for fetcher::PlaylistUnit {
+    const KEYS: &'static [SortKey] = &[SortKey::Name];
+
+    fn compare(&self, other: &Self, _: SortKey) -> Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+
+    fn sort_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Sortable for fetcher::ArtistUnit {
+    const KEYS: &'static [SortKey] = &[SortKey::Name];
+
+    fn compare(&self, other: &Self, _: SortKey) -> Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+
+    fn sort_id(&self) -> &str {
+        &self.id
+    }
+}
+
+// Items in given sort order. Items that compare equal keep their fetched order
+pub fn apply<T: Sortable + Clone>(items: &[T], sort: Sort) -> Vec<T> {
+    let mut sorted = items.to_vec();
+    sorted.sort_by(|a, b| {
+        let order = a.compare(b, sort.key);
+        if sort.descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+    sorted
+}
+
+// Change the sort of one list. Original is the list in fetched order which is kept while list is
+// sorted. Returns the new sort and selection that points to previously selected item
+fn cycle<T: Sortable + Clone>(
+    list: &mut (Vec<T>, TableState),
+    original: &mut Vec<T>,
+    current: Option<Sort>,
+) -> Option<Sort> {
+    let selected = list
+        .1
+        .selected()
+        .and_then(|index| list.0.get(index))
+        .map(|item| item.sort_id().to_string());
+    if current.is_none() {
+        *original = list.0.clone();
+    }
+
+    let sort = next_sort(current, T::KEYS);
+    list.0 = match sort {
+        Some(sort) => apply(original, sort),
+        None => std::mem::take(original),
+    };
+    if let Some(id) = selected {
+        list.1
+            .select(list.0.iter().position(|item| item.sort_id() == id));
+    }
+    sort
+}
+
+impl ui::State<'_> {
+    // Sort the list of active window by next sort order. See `next_sort`
+    pub fn cycle_sort(&mut self) {
+        let index = match self.active {
+            ui::Window::Musicbar => MIDDLE_MUSIC_INDEX,
+            ui::Window::Playlistbar => MIDDLE_PLAYLIST_INDEX,
+            ui::Window::Artistbar => MIDDLE_ARTIST_INDEX,
+            _ => {
+                self.status = "Focus a list to sort..";
+                return;
+            }
+        };
+        // Filtered list is built from unfiltered one so it is not sorted on its own
+        if matches!(&self.filter, Some(filter) if filter.window == self.active) {
+            self.status = "Clear filter to sort..";
+            return;
+        }
+
+        let current = self.sort[index];
+        self.sort[index] = match index {
+            MIDDLE_MUSIC_INDEX => cycle(&mut self.musicbar, &mut self.unsorted.0, current),
+            MIDDLE_PLAYLIST_INDEX => cycle(&mut self.playlistbar, &mut self.unsorted.1, current),
+            _ => cycle(&mut self.artistbar, &mut self.unsorted.2, current),
+        };
+        let window = self.active.clone();
+        self.clear_marks(&window);
+        self.list_dirty[index] = true;
+        self.status = if self.sort[index].is_some() {
+            "List sorted.."
+        } else {
+            "Sort removed.."
+        };
+    }
+
+    // Remove the sort of given window when list is about to be filled with new content. List is
+    // brought back in fetched order so that it is still shown that way if new content cannot be
+    // fetched
+    pub fn discard_sort(&mut self, window: &ui::Window) {
+        let index = match window {
+            ui::Window::Musicbar => MIDDLE_MUSIC_INDEX,
+            ui::Window::Playlistbar => MIDDLE_PLAYLIST_INDEX,
+            ui::Window::Artistbar => MIDDLE_ARTIST_INDEX,
+            _ => return,
+        };
+        if self.sort[index].take().is_none() {
+            return;
+        }
+        match index {
+            MIDDLE_MUSIC_INDEX => self.musicbar.0 = std::mem::take(&mut self.unsorted.0),
+            MIDDLE_PLAYLIST_INDEX => self.playlistbar.0 = std::mem::take(&mut self.unsorted.1),
+            _ => self.artistbar.0 = std::mem::take(&mut self.unsorted.2),
+        }
+    }
+
+    // Title of list with its sort order if it is sorted
+    pub fn sorted_title(&self, title: &str, window: &ui::Window) -> String {
+        let sort = match window {
+            ui::Window::Musicbar => self.sort[MIDDLE_MUSIC_INDEX],
+            ui::Window::Playlistbar => self.sort[MIDDLE_PLAYLIST_INDEX],
+            ui::Window::Artistbar => self.sort[MIDDLE_ARTIST_INDEX],
+            _ => None,
+        };
+        match sort {
+            Some(sort) => format!("{}({}) ", title, sort.label()),
+            None => title.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn music(id: &str, duration: &str, views: &str) -> fetcher::MusicUnit {
+        fetcher::MusicUnit {
+            duration: duration.to_string(),
+            views: views.to_string(),
+            ..ui::test_music(id)
+        }
+    }
+
+    #[test]
+    fn cycle_and_restore_order() {
+        let keys = fetcher::MusicUnit::KEYS;
+        let mut sort = next_sort(None, keys);
+        let mut labels = Vec::new();
+        while let Some(current) = sort {
+            labels.push(current.label());
+            sort = next_sort(sort, keys);
+        }
+        assert_eq!(
+            labels,
+            [
+                "Name ↑",
+                "Name ↓",
+                "Length ↑",
+                "Length ↓",
+                "Views ↑",
+                "Views ↓"
+            ]
+        );
+
+        let fetched = vec![
+            music("b", "1:02:00", "1.2M"),
+            music("c", "3:10", "900"),
+            music("a", "3:10", "25K"),
+        ];
+        let mut list = (fetched.clone(), TableState::default());
+        list.1.select(Some(1));
+        let mut original = Vec::new();
+
+        let sort = cycle(&mut list, &mut original, None);
+        let ids: Vec<&str> = list.0.iter().map(|music| music.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        // Selection follows the item that was selected
+        assert_eq!(list.1.selected(), Some(2));
+
+        let by_length = apply(&fetched, next_sort(next_sort(sort, keys), keys).unwrap());
+        let ids: Vec<&str> = by_length.iter().map(|music| music.id.as_str()).collect();
+        assert_eq!(ids, ["c", "a", "b"]);
+
+        // Last sort goes back to fetched order
+        let views_desc = Some(Sort {
+            key: SortKey::Views,
+            descending: true,
+        });
+        assert_eq!(cycle(&mut list, &mut original, views_desc), None);
+        assert_eq!(list.0, fetched);
+    }
+}
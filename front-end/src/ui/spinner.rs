@@ -0,0 +1,53 @@
+use crate::ui::{self, shared_import::*};
+
+// Frames of spinner shown before status while any list is being fetched
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+// How long each frame is shown. Screen is only drawn this often while something is fetched
+pub const FRAME_DURATION: Duration = Duration::from_millis(120);
+
+// Frame shown after spinner has been spinning for elapsed
+pub fn frame_at(elapsed: Duration) -> char {
+    let index = elapsed.as_millis() / FRAME_DURATION.as_millis();
+    FRAMES[index as usize % FRAMES.len()]
+}
+
+impl ui::State<'_> {
+    // Spinner starts with the first list being fetched and keeps spinning from the same frame
+    // when another list is fetched meanwhile
+    pub fn start_fetching(&mut self, index: usize) {
+        self.fetching[index] = true;
+        self.spinning_since.get_or_insert_with(Instant::now);
+    }
+
+    // Spinner stops once none of the lists are being fetched
+    pub fn stop_fetching(&mut self, index: usize) {
+        self.fetching[index] = false;
+        if !self.fetching.contains(&true) {
+            self.spinning_since = None;
+        }
+    }
+
+    // Frame to draw now. None when nothing is being fetched
+    pub fn spinner(&self) -> Option<char> {
+        self.spinning_since.map(|since| frame_at(since.elapsed()))
+    }
+
+    // Time left until spinner moves to next frame. None when nothing is being fetched
+    pub fn next_frame_in(&self) -> Option<Duration> {
+        let elapsed = self.spinning_since?.elapsed().as_millis();
+        let frame = FRAME_DURATION.as_millis();
+        Some(Duration::from_millis((frame - elapsed % frame) as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_cycle() {
+        assert_eq!(frame_at(Duration::ZERO), FRAMES[0]);
+        assert_eq!(frame_at(FRAME_DURATION * 3), FRAMES[3]);
+        assert_eq!(frame_at(FRAME_DURATION * 10), FRAMES[0]);
+    }
+}
@@ -0,0 +1,347 @@
+use crate::ui::shared_import::*;
+use crossterm::{cursor::MoveTo, queue};
+use std::io::Write;
+use std::sync::{mpsc, Condvar};
+
+// Thumbnail of youtube video. mqdefault is 320x180 which is enough for the small area in sidebar
+const THUMBNAIL_URL: &str = "https://i.ytimg.com/vi/{id}/mqdefault.jpg";
+// Size of one cell of terminal in pixels. Terminal is not asked for it so common size is assumed
+const CELL_WIDTH: u32 = 8;
+const CELL_HEIGHT: u32 = 16;
+// Maximum number of downloaded thumbnails kept in memory. Earliest one is removed first
+const CACHE_LIMIT: usize = 50;
+// Id of image placed with kitty graphics protocol so that only this image is replaced/deleted
+const KITTY_IMAGE_ID: u32 = 533;
+// Kitty protocol accepts payload in chunks of at most this many bytes
+const KITTY_CHUNK: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+lazy_static::lazy_static! {
+    // Graphics protocol supported by terminal. This is known from environment at startup as
+    // asking the terminal itself would mix its reply with input read by event listener
+    pub static ref PROTOCOL: Option<Protocol> = detect();
+}
+
+fn detect() -> Option<Protocol> {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+    if std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || program == "WezTerm"
+    {
+        Some(Protocol::Kitty)
+    } else if ["foot", "mlterm", "sixel", "contour"]
+        .iter()
+        .any(|name| term.contains(name))
+        || program == "iTerm.app"
+    {
+        Some(Protocol::Sixel)
+    } else {
+        None
+    }
+}
+
+// Height of area reserved for thumbnail in sidebar of given width including the border. Nothing
+// is reserved when terminal cannot show images or sidebar list would become too small
+pub fn reserved_height(width: u16, list_height: u16) -> u16 {
+    if PROTOCOL.is_none() {
+        return 0;
+    }
+    // Thumbnail is 16:9 and cell is twice as high as it is wide
+    let height = (width.saturating_sub(2) as u32 * 9 / 32) as u16 + 2;
+    if height * 2 > list_height {
+        0
+    } else {
+        height
+    }
+}
+
+// Size in pixels of image of given size scaled to fit in given number of columns and rows.
+// Aspect ratio is kept
+pub fn fit(image: (u32, u32), cells: (u16, u16)) -> (u32, u32) {
+    let (width, height) = (image.0.max(1), image.1.max(1));
+    let max_width = cells.0 as u32 * CELL_WIDTH;
+    let max_height = cells.1 as u32 * CELL_HEIGHT;
+    if max_width * height <= max_height * width {
+        (max_width, height * max_width / width)
+    } else {
+        (width * max_height / height, max_height)
+    }
+}
+
+// Image encoded with kitty graphics protocol. Image is placed at cursor and scaled by terminal to
+// given number of columns and rows
+fn kitty(image: &image::RgbImage, cells: (u32, u32)) -> String {
+    let payload = base64::encode(image.as_raw());
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut encoded = String::with_capacity(payload.len() + chunks.len() * 32);
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = (index + 1 < chunks.len()) as u8;
+        if index == 0 {
+            encoded.push_str(&format!(
+                "\x1b_Ga=T,f=24,i={id},s={w},v={h},c={c},r={r},C=1,q=2,m={m};",
+                id = KITTY_IMAGE_ID,
+                w = image.width(),
+                h = image.height(),
+                c = cells.0,
+                r = cells.1,
+                m = more
+            ));
+        } else {
+            encoded.push_str(&format!("\x1b_Gm={};", more));
+        }
+        encoded.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        encoded.push_str("\x1b\\");
+    }
+    encoded
+}
+
+// Image encoded as sixel. Colors are reduced to 6 levels of each red, green and blue
+fn sixel(image: &image::RgbImage) -> String {
+    let level = |value: u8| value as usize * 6 / 256;
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let colors: Vec<usize> = image
+        .pixels()
+        .map(|pixel| level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+        .collect();
+
+    let mut encoded = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for color in 0..216 {
+        let percent = |level: usize| level * 100 / 5;
+        encoded.push_str(&format!(
+            "#{};2;{};{};{}",
+            color,
+            percent(color / 36),
+            percent(color / 6 % 6),
+            percent(color % 6)
+        ));
+    }
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut used: Vec<usize> = rows
+            .clone()
+            .flat_map(|y| colors[y * width..(y + 1) * width].iter().copied())
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+        for color in used {
+            encoded.push_str(&format!("#{}", color));
+            let columns = (0..width).map(|x| {
+                let bits = rows
+                    .clone()
+                    .filter(|y| colors[y * width + x] == color)
+                    .fold(0, |bits, y| bits | 1 << (y - band));
+                (63 + bits) as u8 as char
+            });
+            push_run_length(&mut encoded, columns);
+            encoded.push('$');
+        }
+        encoded.push('-');
+    }
+    encoded.push_str("\x1b\\");
+    encoded
+}
+
+// Push sixel characters with repeated ones written as !<count><char>
+fn push_run_length(encoded: &mut String, chars: impl Iterator<Item = char>) {
+    let mut chars = chars.peekable();
+    while let Some(ch) = chars.next() {
+        let mut count = 1;
+        while chars.peek() == Some(&ch) {
+            chars.next();
+            count += 1;
+        }
+        if count > 3 {
+            encoded.push_str(&format!("!{}{}", count, ch));
+        } else {
+            (0..count).for_each(|_| encoded.push(ch));
+        }
+    }
+}
+
+// Thumbnails downloaded in this session and the one drawn on screen
+pub struct Thumbnails {
+    // Jpeg of thumbnail by music id. None while it is being downloaded or if it could not be
+    cache: HashMap<String, Option<Vec<u8>>>,
+    order: VecDeque<String>,
+    sender: mpsc::Sender<(String, Option<Vec<u8>>)>,
+    receiver: mpsc::Receiver<(String, Option<Vec<u8>>)>,
+    // Ui is drawn again once download completes
+    notifier: Arc<Condvar>,
+    // Music whose thumbnail is drawn and area it is drawn in including the border
+    shown: Option<(String, Rect)>,
+}
+
+impl Thumbnails {
+    pub fn new(notifier: Arc<Condvar>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Thumbnails {
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+            sender,
+            receiver,
+            notifier,
+            shown: None,
+        }
+    }
+
+    // Draw thumbnail of given music in given area after the frame is drawn. Thumbnail is only
+    // drawn again when music or area changes, when terminal was resized or after something else
+    // was drawn over it. Nothing is drawn until thumbnail is downloaded
+    pub fn draw(&mut self, music_id: Option<&str>, area: Rect, covered: bool, redraw: bool) {
+        let protocol = match *PROTOCOL {
+            Some(protocol) => protocol,
+            None => return,
+        };
+        while let Ok((id, jpeg)) = self.receiver.try_recv() {
+            self.cache.insert(id, jpeg);
+        }
+
+        let wanted = match music_id {
+            Some(id) if !covered && area.width > 2 && area.height > 2 => Some((id, area)),
+            _ => None,
+        };
+        let unchanged = match (&self.shown, wanted) {
+            (Some((shown, shown_area)), Some((id, area))) => shown == id && *shown_area == area,
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged && !redraw {
+            return;
+        }
+        self.clear(protocol, redraw || covered);
+
+        let (id, area) = match wanted {
+            Some(wanted) => wanted,
+            None => return,
+        };
+        match self.cache.get(id) {
+            Some(Some(jpeg)) => {
+                let jpeg = jpeg.clone();
+                if let Err(err) = Self::render(protocol, &jpeg, area) {
+                    log::error!(
+                        "Cannot show thumbnail of {id}. Error: {err}",
+                        id = id,
+                        err = err
+                    );
+                }
+                self.shown = Some((id.to_string(), area));
+            }
+            Some(None) => {}
+            None => self.download(id),
+        }
+    }
+
+    // Remove the thumbnail drawn before. Sixel is removed by writing over it. That is skipped after
+    // resize as whole screen is already cleared and when something is drawn over it, as writing
+    // over would also erase that
+    fn clear(&mut self, protocol: Protocol, drawn_over: bool) {
+        let area = match self.shown.take() {
+            Some((_, area)) => area,
+            None => return,
+        };
+        let mut stdout = std::io::stdout();
+        let cleared = match protocol {
+            Protocol::Kitty => write!(stdout, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID),
+            Protocol::Sixel if !drawn_over => {
+                let inner = area.inner(&layout::Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                });
+                let blank = " ".repeat(inner.width as usize);
+                (inner.top()..inner.bottom()).try_for_each(|y| {
+                    queue!(stdout, MoveTo(inner.x, y)).map_err(std::io::Error::other)?;
+                    stdout.write_all(blank.as_bytes())
+                })
+            }
+            Protocol::Sixel => Ok(()),
+        };
+        cleared.and_then(|_| stdout.flush()).ok();
+    }
+
+    fn render(protocol: Protocol, jpeg: &[u8], area: Rect) -> Result<(), String> {
+        let image = image::load_from_memory(jpeg)
+            .map_err(|err| err.to_string())?
+            .to_rgb8();
+        let inner = area.inner(&layout::Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let (width, height) = fit(image.dimensions(), (inner.width, inner.height));
+        let encoded = match protocol {
+            Protocol::Kitty => {
+                let cells = (width.div_ceil(CELL_WIDTH), height.div_ceil(CELL_HEIGHT));
+                kitty(&image, cells)
+            }
+            Protocol::Sixel => sixel(&image::imageops::resize(
+                &image,
+                width,
+                height,
+                image::imageops::FilterType::Triangle,
+            )),
+        };
+
+        let mut stdout = std::io::stdout();
+        queue!(stdout, MoveTo(inner.x, inner.y)).map_err(|err| err.to_string())?;
+        stdout
+            .write_all(encoded.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(|err| err.to_string())
+    }
+
+    // Download thumbnail in another thread so that ui do not wait for it
+    fn download(&mut self, id: &str) {
+        if self.order.len() >= CACHE_LIMIT {
+            if let Some(oldest) = self.order.pop_front() {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.cache.insert(id.to_string(), None);
+        self.order.push_back(id.to_string());
+
+        let (id, sender, notifier) = (id.to_string(), self.sender.clone(), self.notifier.clone());
+        std::thread::spawn(move || {
+            let url = THUMBNAIL_URL.replace("{id}", &id);
+            let fetch = async {
+                let client = fetcher::utils::with_proxy(reqwest::ClientBuilder::new())
+                    .build()
+                    .ok()?;
+                let response = client.get(&url).send().await.ok()?;
+                if !response.status().is_success() {
+                    return None;
+                }
+                response.bytes().await.ok().map(|bytes| bytes.to_vec())
+            };
+            let jpeg = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .ok()
+                .and_then(|runtime| runtime.block_on(fetch));
+            sender.send((id, jpeg)).ok();
+            notifier.notify_all();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_and_encode() {
+        // 320x180 thumbnail in 20 columns and 5 rows is limited by height
+        assert_eq!(fit((320, 180), (20, 5)), (142, 80));
+        assert_eq!(fit((320, 180), (10, 10)), (80, 45));
+
+        let red = image::RgbImage::from_pixel(5, 1, image::Rgb([255, 0, 0]));
+        let encoded = sixel(&red);
+        assert!(encoded.starts_with("\x1bP0;1;0q\"1;1;5;1"));
+        assert!(encoded.ends_with("#180!5@$-\x1b\\"));
+    }
+}
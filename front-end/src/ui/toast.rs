@@ -0,0 +1,59 @@
+use crate::ui::{
+    self,
+    shared_import::*,
+    utils::{ExtendBlock, ExtendStyle},
+};
+
+// How long toast of each level stays in statusbox. Errors are left for longer so that they are
+// not missed
+pub fn toast_duration(level: &ui::ToastLevel) -> Duration {
+    match level {
+        ui::ToastLevel::Info => Duration::from_secs(2),
+        ui::ToastLevel::Warning => Duration::from_secs(4),
+        ui::ToastLevel::Error => Duration::from_secs(6),
+    }
+}
+
+// Toast is drawn in place of statusbox with level as title of the box
+pub fn get_toast<'a>(text: &'a str, level: &ui::ToastLevel) -> Paragraph<'a> {
+    let (title, style) = match level {
+        ui::ToastLevel::Info => ("info", Style::list_title()),
+        ui::ToastLevel::Warning => ("warning", Style::default().fg(Color::Yellow)),
+        ui::ToastLevel::Error => ("error", Style::default().fg(Color::Red)),
+    };
+    Paragraph::new(Span::styled(text, style.add_modifier(Modifier::BOLD)))
+        .block(Block::new(title.to_owned()))
+}
+
+impl ui::State<'_> {
+    // Show message in statusbox until it expires. Status is kept as it is and is shown again
+    // after that. Toast shown before is replaced
+    pub fn show_toast(&mut self, level: ui::ToastLevel, text: String) {
+        self.toast = Some((text, level, Instant::now()));
+    }
+
+    // Remove the toast once it has been shown for its duration. Returns true if it was removed
+    // now
+    pub fn expire_toast(&mut self) -> bool {
+        match &self.toast {
+            Some((_, level, shown_at)) if shown_at.elapsed() >= toast_duration(level) => {
+                self.toast = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_stay_longer() {
+        let info = toast_duration(&ui::ToastLevel::Info);
+        let warning = toast_duration(&ui::ToastLevel::Warning);
+        assert!(info < warning);
+        assert!(warning < toast_duration(&ui::ToastLevel::Error));
+    }
+}
@@ -1,21 +1,32 @@
-use crate::ui;
+use crate::ui::{
+    self,
+    columns::{music_header, music_widths, COLUMN_SPACING},
+    mark::{marked_cell, marked_style, marked_title},
+};
 use fetcher::ExtendDuration;
 use std::borrow::Cow;
 use tui;
 use ui::shared_import::*;
 
-pub const SIDEBAR_LIST_COUNT: usize = 6;
+pub const SIDEBAR_LIST_COUNT: usize = 8;
+pub const MIN_SPEED: f32 = 0.5;
+pub const MAX_SPEED: f32 = 3.0;
+pub const SPEED_STEP: f32 = 0.1;
 pub const SIDEBAR_LIST_ITEMS: [&str; SIDEBAR_LIST_COUNT] = [
     "Trending",
     "Youtube Communinty",
     "Liked songs",
     "My playlist",
     "Following",
+    "Recently played",
+    "Most played",
     "Search",
 ];
 use config::initilize::{
-    CONFIG, STORAGE, TB_FAVOURATES_ARTIST, TB_FAVOURATES_MUSIC, TB_FAVOURATES_PLAYLIST,
+    CONFIG, STORAGE, TB_FAVOURATES_ARTIST, TB_FAVOURATES_MUSIC, TB_FAVOURATES_PLAYLIST, TB_HISTORY,
+    TB_QUEUE, TB_RECENTS, TB_SETTINGS,
 };
+use config::keymap::Action;
 
 pub fn show_pupop_text<'a, B>(frame: &mut tui::terminal::Frame<B>, text: [&'a str; 2], area: &Rect)
 where
@@ -32,6 +43,52 @@ where
     frame.render_widget(paragraph, *area);
 }
 
+// Wait for lock longer than this is logged as it may make the ui feel slow
+const SLOW_LOCK: Duration = Duration::from_millis(50);
+
+// Lock the mutex even if another thread panicked while holding it. State is still usable in that
+// case so one failing thread should not bring down the others too
+pub trait LockRecover<T> {
+    fn lock_recover(&self) -> std::sync::MutexGuard<'_, T>;
+}
+
+impl<T> LockRecover<T> for Mutex<T> {
+    fn lock_recover(&self) -> std::sync::MutexGuard<'_, T> {
+        fn recover<G>(err: std::sync::PoisonError<G>) -> G {
+            log::warn!("Recovering state lock poisoned by panicked thread");
+            err.into_inner()
+        }
+        match self.try_lock() {
+            Ok(guard) => guard,
+            Err(std::sync::TryLockError::Poisoned(err)) => recover(err),
+            // Only time the wait when lock is contended so that common case stays cheap
+            Err(std::sync::TryLockError::WouldBlock) => {
+                let started = Instant::now();
+                let guard = self.lock().unwrap_or_else(recover);
+                if started.elapsed() >= SLOW_LOCK {
+                    log::debug!("Waited {:?} for state lock", started.elapsed());
+                }
+                guard
+            }
+        }
+    }
+}
+
+// Widget that copies the content of its area from previously drawn frame. This is used to draw
+// the list which has not changed since last frame without building the whole list again
+pub struct CachedRegion<'a>(pub &'a tui::buffer::Buffer);
+
+impl Widget for CachedRegion<'_> {
+    fn render(self, area: Rect, buf: &mut tui::buffer::Buffer) {
+        let area = area.intersection(self.0.area).intersection(buf.area);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                *buf.get_mut(x, y) = self.0.get(x, y).clone();
+            }
+        }
+    }
+}
+
 // A helper macro to decode the tuple with three memebers to tui::style::Color::Rgb value
 // enum Example {
 //  First(i32, i32, i32) => accepts 3 individual value
@@ -57,8 +114,28 @@ impl<'parent> ui::TopLayout {
     }
 
     pub fn get_statusbox(state: &'parent ui::State) -> Paragraph<'parent> {
+        // Motion being typed in list is shown in place of status until it is used
+        let status = match (state.motion.describe(), state.retrying) {
+            (Some(motion), _) => motion,
+            (None, Some((attempt, total))) => format!("Retrying ({}/{})..", attempt, total),
+            (None, None) => match state.marked_count() {
+                0 => state.status.to_string(),
+                count => format!("{} marked - {}", count, state.status),
+            },
+        };
+        // Tell that list is still being fetched and the app is not stuck
+        let status = match state.spinner() {
+            Some(frame) => format!("{} {}", frame, status),
+            None => status,
+        };
+        // Tell weather loaded music is playing or paused
+        let status = match state.bottom.playing {
+            Some((_, true)) => format!("▶ {}", status),
+            Some((_, false)) => format!("⏸ {}", status),
+            None => status,
+        };
         Paragraph::new(Span::styled(
-            state.status,
+            status,
             Style::default()
                 .add_modifier(Modifier::BOLD | Modifier::ITALIC)
                 .fg(rgb!(CONFIG.theme.color_secondary)),
@@ -69,15 +146,22 @@ impl<'parent> ui::TopLayout {
 
     pub fn get_searchbox(state: &'parent ui::State) -> Paragraph<'parent> {
         let mut cursor_style = Style::default().fg(rgb!(CONFIG.theme.color_secondary));
+        let title = match state.search_scope {
+            ui::SearchScope::All => "Search ".to_owned(),
+            ui::SearchScope::Music => "Search music ".to_owned(),
+            ui::SearchScope::Playlist => "Search playlist ".to_owned(),
+            ui::SearchScope::Artist => "Search artist ".to_owned(),
+            ui::SearchScope::Focused => "Search focused list ".to_owned(),
+        };
 
         let block = match state.active {
             ui::Window::Searchbar => {
                 cursor_style = cursor_style.add_modifier(Modifier::BOLD | Modifier::RAPID_BLINK);
-                Block::active("Search ".to_owned())
+                Block::active(title)
             }
             _ => {
                 cursor_style = cursor_style.add_modifier(Modifier::HIDDEN);
-                Block::new("Search ".to_owned())
+                Block::new(title)
             }
         };
         let text = text::Spans::from(vec![
@@ -94,7 +178,7 @@ impl<'parent> ui::TopLayout {
 }
 
 impl<'parent> ui::MainLayout {
-    pub fn new(parent: Rect) -> Self {
+    pub fn new(parent: Rect, hidden: [bool; 3], ratio: [u16; 3]) -> Self {
         let layout = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
@@ -102,55 +186,83 @@ impl<'parent> ui::MainLayout {
 
         ui::MainLayout {
             sidebar: ui::SideBar::new(layout[0]),
-            middle_section: ui::MiddleLayout::new(layout[1]),
+            middle_section: ui::MiddleLayout::new(layout[1], hidden, ratio),
         }
     }
 }
 
 impl<'parent> ui::MiddleLayout {
-    pub fn new(parent: Rect) -> Self {
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(parent);
+    // Hidden section gets no area and the other one takes the whole parent. Bottom is only
+    // hidden when both playlistbar and artistbar are hidden. Otherwise musicbar takes the height
+    // in ratio of `Constants::pane_ratio`
+    pub fn new(parent: Rect, hidden: [bool; 3], ratio: [u16; 3]) -> Self {
+        let bottom_hidden = hidden[ui::MIDDLE_PLAYLIST_INDEX] && hidden[ui::MIDDLE_ARTIST_INDEX];
+        let (music, bottom) = if hidden[ui::MIDDLE_MUSIC_INDEX] {
+            (Rect::default(), parent)
+        } else if bottom_hidden {
+            (parent, Rect::default())
+        } else {
+            let music_height = ui::panes::split_length(
+                parent.height,
+                ratio[ui::MIDDLE_MUSIC_INDEX],
+                ui::panes::MIN_PANE_HEIGHT,
+            );
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(music_height), Constraint::Min(0)])
+                .split(parent);
+            (layout[0], layout[1])
+        };
 
         ui::MiddleLayout {
-            layout: layout[0],
-            bottom: ui::MiddleBottom::new(layout[1]),
+            layout: music,
+            bottom: ui::MiddleBottom::new(bottom, hidden, ratio),
         }
     }
 
     pub fn get_music_container(state: &'parent mut ui::State) -> Table<'parent> {
         let block = match state.active {
-            ui::Window::Musicbar => Block::active("Music ".to_owned()),
+            ui::Window::Musicbar => {
+                Block::active(state.list_title("Music ", &ui::Window::Musicbar))
+            }
             _ => {
                 state.musicbar.1.select(None);
-                Block::new("Music ".to_owned())
+                Block::new(state.list_title("Music ", &ui::Window::Musicbar))
             }
         };
 
+        let show_ids = state.show_ids;
+        let detailed = CONFIG.constants.music_details;
+        let widths = music_widths(state.layout.music.width, detailed);
+        // Table only borrows the widths so they are kept in state
+        state.music_columns = widths.iter().copied().map(Constraint::Length).collect();
+        let state: &'parent ui::State = state;
         let data_list = &state.musicbar.0;
         let items: Vec<Row> = data_list
             .iter()
-            .map(|music| {
-                Row::new(vec![
-                    music.name.as_str(),
-                    music.artist.as_str(),
-                    music.duration.as_str(),
-                ])
+            .enumerate()
+            .map(|(index, music)| {
+                let marked = state.is_marked(&ui::Window::Musicbar, index);
+                let title = if show_ids {
+                    music.id.as_str()
+                } else {
+                    music.name.as_str()
+                };
+                let mut cells = vec![marked_title(title, marked, widths[0] as usize)];
+                if detailed {
+                    cells.push(Cell::from(music.artist.as_str()));
+                }
+                cells.push(Cell::from(music.duration.as_str()));
+                if detailed {
+                    cells.push(Cell::from(music.views.as_str()));
+                }
+                Row::new(cells).style(marked_style(marked))
             })
             .collect();
         let table = Table::new(items)
-            .header(Row::new(vec!["Title", "Artist", "Length"]).style(Style::list_title()))
-            .widths(
-                [
-                    Constraint::Percentage(55),
-                    Constraint::Percentage(30),
-                    Constraint::Percentage(15),
-                ]
-                .as_ref(),
-            )
-            .column_spacing(2)
+            .header(Row::new(music_header(detailed)).style(Style::list_title()))
+            .widths(&state.music_columns)
+            .column_spacing(COLUMN_SPACING)
             .style(Style::list_idle())
             .highlight_style(Style::list_highlight())
             .block(block);
@@ -160,34 +272,54 @@ impl<'parent> ui::MiddleLayout {
 }
 
 impl<'parent> ui::MiddleBottom {
-    pub fn new(parent: Rect) -> Self {
-        let layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
-            .split(parent);
+    // Same as MiddleLayout, shown list takes the whole parent when other one is hidden
+    pub fn new(parent: Rect, hidden: [bool; 3], ratio: [u16; 3]) -> Self {
+        let layout = if hidden[ui::MIDDLE_PLAYLIST_INDEX] {
+            [Rect::default(), parent]
+        } else if hidden[ui::MIDDLE_ARTIST_INDEX] {
+            [parent, Rect::default()]
+        } else {
+            let playlist = ratio[ui::MIDDLE_PLAYLIST_INDEX];
+            let percent = playlist * 100 / (playlist + ratio[ui::MIDDLE_ARTIST_INDEX]).max(1);
+            let playlist_width =
+                ui::panes::split_length(parent.width, percent, ui::panes::MIN_PANE_WIDTH);
+            let layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(playlist_width), Constraint::Min(0)])
+                .split(parent);
+            [layout[0], layout[1]]
+        };
 
-        ui::MiddleBottom {
-            layout: [layout[0], layout[1]],
-        }
+        ui::MiddleBottom { layout }
     }
 
     pub fn get_playlist_container(state: &'parent mut ui::State) -> Table<'parent> {
         let block = match state.active {
-            ui::Window::Playlistbar => Block::active("Playlist ".to_owned()),
+            ui::Window::Playlistbar => {
+                Block::active(state.list_title("Playlist ", &ui::Window::Playlistbar))
+            }
             _ => {
                 state.playlistbar.1.select(None);
-                Block::new("Playlist ".to_owned())
+                Block::new(state.list_title("Playlist ", &ui::Window::Playlistbar))
             }
         };
+        let show_ids = state.show_ids;
         let data_list = &state.playlistbar.0;
         let items: Vec<Row> = data_list
             .iter()
-            .map(|playlist| {
+            .enumerate()
+            .map(|(index, playlist)| {
+                let marked = state.is_marked(&ui::Window::Playlistbar, index);
                 Row::new(vec![
-                    playlist.video_count.as_str(),
-                    playlist.name.as_str(),
-                    playlist.author.as_str(),
+                    marked_cell(playlist.video_count.as_str(), marked),
+                    Cell::from(if show_ids {
+                        playlist.id.as_str()
+                    } else {
+                        playlist.name.as_str()
+                    }),
+                    Cell::from(playlist.author.as_str()),
                 ])
+                .style(marked_style(marked))
             })
             .collect();
         let table = Table::new(items)
@@ -210,18 +342,46 @@ impl<'parent> ui::MiddleBottom {
 
     pub fn get_artist_container(state: &'parent mut ui::State) -> Table<'parent> {
         let block;
+        let title = state.list_title("Artist ", &ui::Window::Artistbar);
         if state.active == ui::Window::Artistbar {
-            block = Block::active("Artist ".to_string());
+            block = Block::active(title);
         } else {
-            block = Block::new("Artist ".to_string());
+            block = Block::new(title);
             state.artistbar.1.select(None);
         }
+        let show_ids = state.show_ids;
         let data_list = &state.artistbar;
-        let items: Vec<Row> = data_list
+        let mut items: Vec<Row> = data_list
             .0
             .iter()
-            .map(|artist| Row::new(vec![artist.video_count.as_str(), artist.name.as_str()]))
+            .enumerate()
+            .map(|(index, artist)| {
+                let name = if show_ids {
+                    artist.id.as_str()
+                } else {
+                    artist.name.as_str()
+                };
+                let marked = state.is_marked(&ui::Window::Artistbar, index);
+                Row::new(vec![
+                    marked_cell(artist.video_count.as_str(), marked),
+                    Cell::from(name),
+                ])
+                .style(marked_style(marked))
+            })
             .collect();
+        // Tell how to follow an artist instead of showing blank list
+        if items.is_empty() && state.filled_source.2 == ui::ArtistbarSource::Favourates {
+            let follow_key = CONFIG
+                .key_map(state.key_profile)
+                .describe(Action::FavouratesAdd);
+            items.push(Row::new(vec![
+                Cell::from(""),
+                Cell::from(format!(
+                    "No followed artists. Press {} on an artist to follow",
+                    follow_key
+                )),
+            ]));
+        }
         let table = Table::new(items)
             .header(Row::new(vec!["#", "Name"]).style(Style::list_title()))
             .widths([Constraint::Percentage(10), Constraint::Percentage(90)].as_ref())
@@ -241,28 +401,41 @@ impl<'parent> ui::SideBar {
         // | <playing | paused>
         // | R-1
         // | S-1
+        // | A-1
+        // | C-1
         // ----------------
-        // Total height: 6
-        let status_height: u16 = 6;
+        // Total height: 8
+        let status_height: u16 = 8;
         let list_height = parent.height.checked_sub(status_height).unwrap_or_default();
+        // Thumbnail of music being played takes the bottom of list area
+        #[cfg(feature = "thumbnail")]
+        let thumbnail_height = ui::thumbnail::reserved_height(parent.width, list_height);
+        #[cfg(not(feature = "thumbnail"))]
+        let thumbnail_height = 0;
 
         let layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(list_height),
+                Constraint::Length(list_height - thumbnail_height),
+                Constraint::Length(thumbnail_height),
                 Constraint::Length(status_height),
             ])
             .split(parent);
 
         ui::SideBar {
-            layout: [layout[0], layout[1]],
+            layout: [layout[0], layout[1], layout[2]],
         }
     }
 
     pub fn get_shortcuts(state: &ui::State) -> List<'parent> {
+        // Show the name of keybinding profile in title if it is not the default one
+        let title = match state.key_profile {
+            0 => "Shortcut ".to_owned(),
+            index => format!("Shortcut [{}] ", CONFIG.key_profile(index).0),
+        };
         let block = match state.active {
-            ui::Window::Sidebar => Block::active("Shortcut ".to_owned()),
-            _ => Block::new("Shortcut ".to_owned()),
+            ui::Window::Sidebar => Block::active(title),
+            _ => Block::new(title),
         };
         List::new(
             SIDEBAR_LIST_ITEMS
@@ -290,19 +463,27 @@ impl<'parent> ui::BottomLayout {
         ui::BottomLayout { layout: layout[0] }
     }
 
-    pub fn get_status_bar(state: &'parent ui::State) -> Gauge<'parent> {
-        let content;
-        if let Some((name, _)) = &state.bottom.playing {
-            content = name.as_str()
-        } else {
-            content = ">> Play some Music <<"
-        };
+    pub fn get_status_bar(state: &'parent ui::State, width: u16) -> Gauge<'parent> {
+        // 2 for the border
+        let content = state.now_playing_line(width.saturating_sub(2) as usize);
 
-        let heading = format!(
-            "{} / {}",
-            state.bottom.music_elapse.to_string(),
+        // mpv do not know the duration of live stream. Only elapsed time is known in such case
+        let is_live = state.bottom.music_duration.is_zero() && !state.bottom.music_elapse.is_zero();
+        let total = if is_live {
+            String::from("live")
+        } else {
             state.bottom.music_duration.to_string()
-        );
+        };
+        let mut heading = format!("{} / {}", state.bottom.music_elapse.to_string(), total);
+        if (state.speed() - 1.0).abs() > f32::EPSILON {
+            heading += &format!(" - {}x", state.speed());
+        }
+        if let Some(remaining) = state.sleep_remaining() {
+            heading += &format!(" - sleep in {}", remaining.to_string());
+        }
+        if let Some(downloads) = state.downloads_line() {
+            heading += &format!(" - {}", downloads);
+        }
 
         let mut block;
         if state.active == ui::Window::BottomControl {
@@ -314,7 +495,13 @@ impl<'parent> ui::BottomLayout {
 
         let mut ratio =
             state.bottom.music_elapse.as_secs_f64() / state.bottom.music_duration.as_secs_f64();
-        if ratio > 1.0 {
+        if is_live {
+            // There is no progress to show. Keep filling the bar every few seconds just to
+            // indicate that stream is being played
+            const LIVE_SWEEP_SECS: u64 = 10;
+            ratio = (state.bottom.music_elapse.as_secs() % LIVE_SWEEP_SECS) as f64
+                / LIVE_SWEEP_SECS as f64;
+        } else if ratio > 1.0 {
             ratio = 1.0
         } else if ratio.is_nan() || ratio < 0.0 {
             ratio = 0.0
@@ -333,7 +520,9 @@ impl<'parent> ui::BottomLayout {
     // Desired layout:
     // | Vol: <volume_level>
     // | suffle | <strikethrough>suffle<strikethrough>
-    // | (no-)repeat
+    // | repeat-all | repeat-one | <strikethrough>repeat<strikethrough>
+    // | auto-next | <strikethrough>auto-next<strikethrough>
+    // | consume | <strikethrough>consume<strikethrough>
     // | playing | paused (blinked)
     pub fn get_icons_set(state: &'parent ui::State) -> Paragraph<'parent> {
         let block = Block::active(String::new());
@@ -348,8 +537,13 @@ impl<'parent> ui::BottomLayout {
         }
 
         let mut repeat = Span::styled("repeat-all", Style::list_highlight());
-        if !state.playback_behaviour.repeat {
-            repeat.content = Cow::Borrowed("repeat-one");
+        match state.playback_behaviour.repeat {
+            ui::RepeatMode::All => {}
+            ui::RepeatMode::One => repeat.content = Cow::Borrowed("repeat-one"),
+            ui::RepeatMode::Nothing => {
+                repeat.content = Cow::Borrowed("repeat");
+                repeat.style = repeat.style.add_modifier(Modifier::CROSSED_OUT);
+            }
         }
 
         let mut suffle = Span::styled("suffle", Style::list_highlight());
@@ -357,16 +551,32 @@ impl<'parent> ui::BottomLayout {
             suffle.style = suffle.style.add_modifier(Modifier::CROSSED_OUT);
         }
 
-        let volume = Span::styled(
-            format!("Vol: {}", state.playback_behaviour.volume),
-            Style::list_highlight(),
-        );
+        let mut auto_next = Span::styled("auto-next", Style::list_highlight());
+        if !state.playback_behaviour.auto_advance {
+            auto_next.style = auto_next.style.add_modifier(Modifier::CROSSED_OUT);
+        }
+
+        // Consume asked for while repeating all is not in effect so it is shown as off
+        let mut consume = Span::styled("consume", Style::list_highlight());
+        if !state.consumes_queue() {
+            consume.style = consume.style.add_modifier(Modifier::CROSSED_OUT);
+        }
+
+        let volume = match state.playback_behaviour.muted_volume {
+            Some(_) => Span::styled("Muted", Style::list_idle()),
+            None => Span::styled(
+                format!("Vol: {}", state.playback_behaviour.volume),
+                Style::list_highlight(),
+            ),
+        };
 
         let content = Text {
             lines: [
                 Spans([volume].to_vec()),
                 Spans([repeat].to_vec()),
                 Spans([suffle].to_vec()),
+                Spans([auto_next].to_vec()),
+                Spans([consume].to_vec()),
                 Spans([paused_status].to_vec()),
             ]
             .to_vec(),
@@ -387,6 +597,7 @@ pub trait ExtendStyle {
     fn block_title() -> Style;
     fn list_idle() -> Style;
     fn list_title() -> Style;
+    fn list_marked() -> Style;
 }
 
 impl ExtendStyle for Style {
@@ -419,6 +630,16 @@ impl ExtendStyle for Style {
             sub_modifier: Modifier::empty(),
         }
     }
+
+    #[inline(always)]
+    fn list_marked() -> Style {
+        Style {
+            fg: Some(rgb!(CONFIG.theme.color_primary)),
+            bg: None,
+            add_modifier: Modifier::BOLD | Modifier::ITALIC,
+            sub_modifier: Modifier::empty(),
+        }
+    }
 }
 
 impl<'a> ExtendBlock<'a> for Block<'_> {
@@ -442,7 +663,12 @@ impl<'a> ExtendBlock<'a> for Block<'_> {
 }
 
 impl ui::Position {
-    pub fn caclulate(screen_rect: &Rect) -> Self {
+    pub fn caclulate(
+        screen_rect: &Rect,
+        layout_mode: ui::LayoutMode,
+        hidden: [bool; 3],
+        ratio: [u16; 3],
+    ) -> Self {
         // 3 line for each bottom and top bar (1 for content and 2 for border)
         // remaining height for middlebar
         let for_middle = screen_rect.height.checked_sub(3 + 3).unwrap_or_default();
@@ -460,7 +686,7 @@ impl ui::Position {
             .split(*screen_rect);
 
         let top_section = ui::TopLayout::new(main_layout[0]);
-        let main_section = ui::MainLayout::new(main_layout[1]);
+        let main_section = ui::MainLayout::new(main_layout[1], hidden, ratio);
         let bottom_section = ui::BottomLayout::new(main_layout[2]);
         let sidebar = main_section.sidebar;
         let middle_section = main_section.middle_section;
@@ -476,17 +702,44 @@ impl ui::Position {
             height,
             width,
         };
+        // Help and queue window have many lines so they take most of the screen
+        let overlay_height = screen_rect.height * 4 / 5;
+        let overlay_width = std::cmp::min(screen_rect.width, 70);
+        let overlay_pos = Rect {
+            x: center_x - (overlay_width / 2),
+            y: center_y - (overlay_height / 2),
+            height: overlay_height,
+            width: overlay_width,
+        };
 
-        ui::Position {
-            search: top_section.layout[0],
-            status: top_section.layout[1],
-            shortcut: sidebar.layout[0],
-            music: middle_section.layout,
-            playlist: middle_bottom.layout[0],
-            artist: middle_bottom.layout[1],
-            music_info: bottom_section.layout,
-            bottom_icons: sidebar.layout[1],
-            popup: popup_pos,
+        match layout_mode {
+            ui::LayoutMode::Normal => ui::Position {
+                search: top_section.layout[0],
+                status: top_section.layout[1],
+                shortcut: sidebar.layout[0],
+                music: middle_section.layout,
+                playlist: middle_bottom.layout[0],
+                artist: middle_bottom.layout[1],
+                music_info: bottom_section.layout,
+                bottom_icons: sidebar.layout[2],
+                thumbnail: sidebar.layout[1],
+                popup: popup_pos,
+                overlay: overlay_pos,
+            },
+            // Only one of these section is rendered at a time. See State::is_visible
+            ui::LayoutMode::Compact => ui::Position {
+                search: top_section.layout[0],
+                status: top_section.layout[1],
+                shortcut: main_layout[1],
+                music: main_layout[1],
+                playlist: main_layout[1],
+                artist: main_layout[1],
+                music_info: bottom_section.layout,
+                bottom_icons: Rect::default(),
+                thumbnail: Rect::default(),
+                popup: popup_pos,
+                overlay: overlay_pos,
+            },
         }
     }
 }
@@ -499,8 +752,17 @@ impl Default for ui::State<'_> {
         // By default repeat the playlist. Set playlist to repeat
         mpv.repeat_playlist();
 
-        // At first have maximum volume
-        mpv.change_volume(100);
+        // Start with the volume of last run. Maximum volume if there was none
+        let volume = ui::State::saved_volume().unwrap_or(100);
+        mpv.set_volume(volume);
+        let speed = ui::State::saved_speed()
+            .filter(|_| CONFIG.constants.remember_speed)
+            .unwrap_or(1.0);
+        mpv.set_speed(speed);
+
+        mpv.auto_advance(CONFIG.constants.auto_advance);
+        mpv.prefer_audio_language(&CONFIG.constants.audio_language);
+        mpv.set_output_device(&CONFIG.constants.audio_device);
 
         let mut sidebar_list_state = ListState::default();
         sidebar_list_state.select(Some(0));
@@ -511,8 +773,16 @@ impl Default for ui::State<'_> {
             playlistbar: (Vec::new(), TableState::default()),
             artistbar: (Vec::new(), TableState::default()),
             search: (String::new(), String::new()),
+            search_scope: Self::saved_search_scope(),
+            last_pane: ui::Window::Musicbar,
             active: ui::Window::Sidebar,
             fetched_page: [None; 3],
+            last_page: [false; 3],
+            fetch_failed: [false; 3],
+            retrying: None,
+            fetching: [false; 3],
+            spinning_since: None,
+            downloaders: Arc::new(Mutex::new(ui::Downloaders::default())),
             filled_source: (
                 ui::MusicbarSource::RecentlyPlayed,
                 ui::PlaylistbarSource::RecentlyPlayed,
@@ -526,9 +796,82 @@ impl Default for ui::State<'_> {
             player: mpv,
             playback_behaviour: ui::PlaybackBehaviour {
                 shuffle: false,
-                repeat: true,
-                volume: 100,
+                repeat: ui::RepeatMode::All,
+                volume,
+                muted_volume: None,
+                speed,
+                auto_advance: CONFIG.constants.auto_advance,
+                consume_queue: CONFIG.constants.consume_queue,
+            },
+            key_profile: 0,
+            show_ids: false,
+            layout_mode: ui::LayoutMode::Normal,
+            hidden: ui::State::saved_hidden(),
+            pane_ratio: ui::State::saved_pane_ratio(),
+            music_columns: Vec::new(),
+            queue: VecDeque::new(),
+            queue_version: None,
+            filter: None,
+            filter_mode: if CONFIG.constants.fuzzy_filter {
+                ui::filter::FilterMode::Fuzzy
+            } else {
+                ui::filter::FilterMode::Substring
             },
+            unfiltered: (Vec::new(), Vec::new(), Vec::new()),
+            sort: [None; 3],
+            unsorted: (Vec::new(), Vec::new(), Vec::new()),
+            cache_size: (0, CONFIG.constants.result_cache_limit),
+            clear_cache: false,
+            refresh: [false; 3],
+            bookmark: None,
+            // Nothing is fetched yet so communicator should be woken up in first refresh cycle
+            dirty: true,
+            play_on_select: CONFIG.constants.play_on_select,
+            pending_selection: None,
+            search_typed_at: None,
+            live_searched: false,
+            search_history: ui::search_history::load(),
+            search_recalled: None,
+            search_draft: String::new(),
+            play_first_result: false,
+            quit_armed: false,
+            motion: ui::motion::Motion::default(),
+            downloads: Vec::new(),
+            sleep_timer: None,
+            now_playing: None,
+            played_from: Vec::new(),
+            track_changed_at: None,
+            track_started_at: None,
+            play_counted: false,
+            #[cfg(feature = "scrobble")]
+            scrobbler: ui::scrobble::Scrobbler::new(),
+            resume_position: None,
+            prefetched: None,
+            fade_volume: None,
+            fader: None,
+            list_dirty: [true; 3],
+            shuffle: ui::State::new_shuffle(),
+            marked: Default::default(),
+            radio: ui::RadioMode::default(),
+            stay_on_source: CONFIG.constants.stay_on_source,
+            before_overlay: ui::Window::Sidebar,
+            overlay_scroll: 0,
+            lyrics: HashMap::new(),
+            lyrics_of: None,
+            lyrics_request: None,
+            queue_selected: 0,
+            menu: Vec::new(),
+            menu_selected: 0,
+            queued_next: None,
+            loading: None,
+            toast: None,
+            osd: None,
+            browser: None,
+            last_input: Instant::now(),
+            export: None,
+            layout: ui::Position::default(),
+            sidebar_offset: 0,
+            list_offset: [0; 3],
         }
     }
 }
@@ -538,13 +881,40 @@ pub trait ExtendMpv {
     fn repeat_playlist(&self);
     fn repeat_one(&self);
     fn repeat_nothing(&self);
-    fn shuffle(&self);
-    fn unshuffle(&self);
     fn cache_for(&self, time: i64);
     fn play_next(&self);
     fn play_prev(&self);
     fn change_volume(&self, step: i8) -> Option<u8>;
+    fn set_volume(&self, volume: u8) -> bool;
+    fn set_speed(&self, rate: f32) -> bool;
     fn get_volume(&self) -> Option<f64>;
+    fn auto_advance(&self, enable: bool);
+    fn prefer_audio_language(&self, language: &str);
+    fn audio_tracks(&self) -> Vec<(i64, String)>;
+    fn audio_devices(&self) -> Vec<(String, String)>;
+    fn output_device(&self) -> String;
+    fn set_output_device(&self, name: &str) -> bool;
+}
+
+// Each choice of youtube-dl format is first tried with stream in given language before falling
+// back to the format as it is. Choice merging more than one stream is left as it is since filter
+// would only apply to its last part. eg: worst -> worst[language^=en]/worst
+fn language_format(format: &str, language: &str) -> String {
+    let format = if format.is_empty() {
+        "bestaudio/best"
+    } else {
+        format
+    };
+    let in_language = format
+        .split('/')
+        .filter(|choice| !choice.contains('+'))
+        .map(|choice| format!("{}[language^={}]", choice, language))
+        .collect::<Vec<_>>();
+    if in_language.is_empty() {
+        format.to_string()
+    } else {
+        format!("{}/{}", in_language.join("/"), format)
+    }
 }
 
 impl ExtendMpv for libmpv::Mpv {
@@ -559,16 +929,16 @@ impl ExtendMpv for libmpv::Mpv {
 
         // Video is always hidden. Override config file
         self.set_property("video", "no").unwrap();
-    }
-
-    #[inline(always)]
-    fn shuffle(&self) {
-        self.command("playlist-shuffle", &[]).ok();
-    }
 
-    #[inline(always)]
-    fn unshuffle(&self) {
-        self.command("playlist-unshuffle", &[]).ok();
+        // Stream is resolved by youtube-dl and then fetched by mpv itself so both are told
+        // about the proxy. Proxy is added to raw options of mpv.conf instead of replacing them
+        let proxy = &CONFIG.constants.proxy_url;
+        if !proxy.is_empty() {
+            self.set_property("http-proxy", proxy.as_str()).ok();
+            let option = format!("proxy=[{}]", proxy);
+            self.command("change-list", &["ytdl-raw-options", "append", &option])
+                .ok();
+        }
     }
 
     #[inline(always)]
@@ -604,6 +974,19 @@ impl ExtendMpv for libmpv::Mpv {
         }
     }
 
+    // Volume above 100 is clamped to 100. Returns false if mpv refused to change the volume
+    #[inline(always)]
+    fn set_volume(&self, volume: u8) -> bool {
+        self.set_property("volume", volume.min(100) as f64).is_ok()
+    }
+
+    // Pitch correction while playing faster or slower is left to mpv. Returns false if mpv refused
+    // the speed
+    #[inline(always)]
+    fn set_speed(&self, rate: f32) -> bool {
+        self.set_property("speed", rate as f64).is_ok()
+    }
+
     #[inline(always)]
     fn repeat_playlist(&self) {
         self.set_property("loop-playlist", "inf").ok();
@@ -628,10 +1011,130 @@ impl ExtendMpv for libmpv::Mpv {
     fn play_prev(&self) {
         self.playlist_previous_weak().ok();
     }
+
+    // Mpv itself moves to next entry of playlist when a file ends. With keep-open set to `always`
+    // mpv will pause at the end of every file instead and never advance automatically
+    #[inline(always)]
+    fn auto_advance(&self, enable: bool) {
+        let keep_open = if enable { "no" } else { "always" };
+        self.set_property("keep-open", keep_open).ok();
+    }
+
+    // alang only select among the audio tracks that are already loaded. youtube-dl is also asked
+    // to prefer stream in given language so that such track is loaded in first place. Format set
+    // in mpv.conf is kept and only narrowed to the language, with the format itself as fallback
+    fn prefer_audio_language(&self, language: &str) {
+        if language.is_empty() {
+            return;
+        }
+        self.set_property("alang", language).ok();
+        let format = self
+            .get_property::<String>("ytdl-format")
+            .unwrap_or_default();
+        self.set_property("ytdl-format", language_format(&format, language).as_str())
+            .ok();
+    }
+
+    // Returns the id and language of every audio track of currently playing file
+    fn audio_tracks(&self) -> Vec<(i64, String)> {
+        let count = self
+            .get_property::<i64>("track-list/count")
+            .unwrap_or_default();
+        (0..count)
+            .filter(|index| {
+                self.get_property::<String>(&format!("track-list/{}/type", index))
+                    .map(|track_type| track_type == "audio")
+                    .unwrap_or(false)
+            })
+            .filter_map(|index| {
+                let id = self
+                    .get_property::<i64>(&format!("track-list/{}/id", index))
+                    .ok()?;
+                let language = self
+                    .get_property::<String>(&format!("track-list/{}/lang", index))
+                    .unwrap_or_else(|_| "unknown".to_string());
+                Some((id, language))
+            })
+            .collect()
+    }
+
+    // Returns the name and description of every audio output device mpv can play to. Devices
+    // plugged in or removed while running are reflected here by mpv itself
+    fn audio_devices(&self) -> Vec<(String, String)> {
+        let count = self
+            .get_property::<i64>("audio-device-list/count")
+            .unwrap_or_default();
+        (0..count)
+            .filter_map(|index| {
+                let name = self
+                    .get_property::<String>(&format!("audio-device-list/{}/name", index))
+                    .ok()?;
+                let description = self
+                    .get_property::<String>(&format!("audio-device-list/{}/description", index))
+                    .unwrap_or_else(|_| name.clone());
+                Some((name, description))
+            })
+            .collect()
+    }
+
+    #[inline(always)]
+    fn output_device(&self) -> String {
+        self.get_property("audio-device")
+            .unwrap_or_else(|_| ui::output::DEFAULT_DEVICE.to_string())
+    }
+
+    // Returns false if mpv refused the device. Device that does not exist is only noticed by mpv
+    // when it opens the device to play
+    #[inline(always)]
+    fn set_output_device(&self, name: &str) -> bool {
+        self.set_property("audio-device", name).is_ok()
+    }
 }
 
 impl ui::State<'_> {
-    pub fn play_music(&mut self, music_id: &str) {
+    // Returns error if mpv could not play the music. Music could still fail to load afterwards
+    // which is handled by `State::check_playback`
+    pub fn play_music(&mut self, music_id: &str) -> Result<(), libmpv::Error> {
+        self.load_music(music_id)?;
+        // Now as the selection is being played. Add remaining item from musicbar to the play
+        // queue.
+        let rest = self
+            .musicbar
+            .0
+            .iter()
+            // If this is the currently payed song donot add it to prevent having
+            // currently played song two time in queue
+            .filter(|music| music.id != *music_id)
+            .map(|music| music.id.clone())
+            .collect::<Vec<_>>();
+        for music_id in rest {
+            self.append_music(&music_id);
+        }
+        if self.playback_behaviour.shuffle {
+            self.shuffle_playlist();
+        }
+        Ok(())
+    }
+
+    // Add the music at end of mpv playlist
+    pub fn append_music(&self, music_id: &str) {
+        self.player
+            .command(
+                "loadfile",
+                [
+                    format!("https://www.youtube.com/watch?v={}", music_id).as_str(),
+                    "append",
+                ]
+                .as_ref(),
+            )
+            .ok();
+    }
+
+    // Play only the given music replacing everything in mpv playlist. Returns error if mpv
+    // could not play it
+    pub fn load_music(&mut self, music_id: &str) -> Result<(), libmpv::Error> {
+        log::info!("Loading music {}", music_id);
+        self.skip_crossfade();
         self.player.unpause().ok();
         match self.player.command(
             "loadfile",
@@ -645,28 +1148,21 @@ impl ui::State<'_> {
                 self.status = "Playing...";
                 // set currently playing (unpaused) to ture. no need to set real title as it will
                 // be done by refresh_mpv_status() later on
-                self.bottom.playing = Some((String::new(), true))
+                self.bottom.playing = Some((String::new(), true));
+
+                // Play is counted in history by `State::count_play` once it is heard
+                if let Some(music) = self.musicbar.0.iter().find(|music| music.id == *music_id) {
+                    Self::add_to_recents(music);
+                }
+                self.set_now_playing(music_id);
+                self.loading = Some(ui::recovery::LoadAttempt::new(music_id));
+                Ok(())
+            }
+            Err(err) => {
+                log::warn!("Cannot load music {}. Error: {}", music_id, err);
+                self.report_unplayable(music_id);
+                Err(err)
             }
-            Err(_) => self.status = "Playback error..",
-        }
-        // Now as the selection is being played. Add remaining item from musicbar to the play
-        // queue.
-        for music in self.musicbar.0.iter() {
-            // If this is the currently payed song donot add it to prevent having
-            // currently played song two time in queue
-            if music.id == *music_id {
-                continue;
-            }
-            self.player
-                .command(
-                    "loadfile",
-                    [
-                        format!("https://www.youtube.com/watch?v={}", music.id).as_str(),
-                        "append",
-                    ]
-                    .as_ref(),
-                )
-                .ok();
         }
     }
 
@@ -688,8 +1184,15 @@ impl ui::State<'_> {
                 // set currently playing (unpaused) to ture. no need to set real title as it will
                 // be done by refresh_mpv_status() later on
                 self.bottom.playing = Some((String::new(), true));
+                // Music of playlist is only known after mpv starts playing it
+                self.now_playing = None;
+                self.played_from.clear();
+                self.loading = None;
+            }
+            Err(err) => {
+                log::warn!("Cannot load playlist {}. Error: {}", playlist_id, err);
+                self.show_toast(ui::ToastLevel::Error, "Cannot play playlist".to_string());
             }
-            Err(_) => self.status = "Playback error..",
         }
     }
 
@@ -700,6 +1203,7 @@ impl ui::State<'_> {
         // but for that we need tp spawn seperate thread/task
         // and also we are updating the ui anway so it may also be affordable to just query mpv in
         // ui updating loop
+        self.sync_pause();
         if let Some((_, true)) = self.bottom.playing {
             match self.player.get_property::<i64>("audio-pts") {
                 Ok(time) => {
@@ -728,28 +1232,342 @@ impl ui::State<'_> {
                 .get_property::<i64>("duration")
                 .unwrap_or_default();
 
-            self.bottom.playing = Some((title, true)); // at this scope of match playing status is always true
             self.bottom.music_duration =
                 Duration::from_secs(estimated_duration_reply.try_into().unwrap_or_default());
+            self.update_now_playing(&title, self.bottom.music_duration);
+            self.count_play();
+            #[cfg(feature = "scrobble")]
+            if let Some(status) = self.scrobbler.as_ref().and_then(|s| s.take_status()) {
+                self.show_toast(ui::ToastLevel::Warning, status.to_string());
+            }
+            self.seek_resume_position();
+            self.prefetch_next();
+            self.crossfade();
+            self.bottom.playing = Some((title, true)); // at this scope of match playing status is always true
+        }
+    }
+
+    // Returns true if given section of middle area is to be rendered in current layout
+    // In compact layout, only the active section is visible and musicbar is visible when
+    // active window is not one of middle area section. Panes hidden by user are only left out in
+    // normal layout
+    pub fn is_visible(&self, window: &ui::Window) -> bool {
+        if self.layout_mode == ui::LayoutMode::Normal {
+            return !self.is_hidden(window);
+        }
+        match self.active {
+            ui::Window::Sidebar
+            | ui::Window::Musicbar
+            | ui::Window::Playlistbar
+            | ui::Window::Artistbar => self.active == *window,
+            _ => *window == ui::Window::Musicbar,
+        }
+    }
+
+    // Make every list in middle area to be drawn again in next frame
+    pub fn mark_lists_dirty(&mut self) {
+        self.list_dirty = [true; 3];
+    }
+
+    // Make given window active. As the active list is drawn differently, all lists are marked to
+    // be drawn again when active window changes
+    pub fn focus(&mut self, window: ui::Window) {
+        if self.active != window && !self.is_hidden(&window) {
+            self.mark_lists_dirty();
+            self.active = window;
+        }
+    }
+
+    // Focus the list that was just filled by communicator. Searchbar is kept active when the
+    // list was fetched for live search
+    pub fn focus_fetched(&mut self, window: ui::Window) {
+        if !(self.live_searched && self.active == ui::Window::Searchbar) {
+            self.focus(window);
+        }
+    }
+
+    // Returns true if threads waiting for state should be notified in this refresh cycle and
+    // reset the dirty flag. While music is playing, progress in bottombar changes every refresh so
+    // it is always true in that case
+    pub fn take_dirty(&mut self) -> bool {
+        let is_playing = matches!(self.bottom.playing, Some((_, true)));
+        std::mem::take(&mut self.dirty) || is_playing
+    }
+
+    // How long event listener should wait for input before doing periodic work. While music is
+    // playing this is refresh_rate so that progress is updated, or idle_refresh_rate once input
+    // has been idle for input_idle_secs. Otherwise it only wakes up when something waiting for
+    // time is due, or after idle_poll_rate when nothing is
+    pub fn poll_timeout(&self) -> Duration {
+        let millis = Duration::from_millis;
+        let mut timeout = millis(CONFIG.constants.idle_poll_rate);
+        let mut due = |after: Duration| timeout = timeout.min(after);
+
+        // Downloads are also checked often so that finished ones are reported soon
+        if matches!(self.bottom.playing, Some((_, true))) || !self.downloads.is_empty() {
+            if self.is_input_idle() {
+                due(millis(CONFIG.constants.idle_refresh_rate));
+            } else {
+                due(millis(CONFIG.constants.refresh_rate));
+            }
+        }
+        if let Some(typed_at) = self.search_typed_at {
+            due(millis(CONFIG.constants.live_search_delay).saturating_sub(typed_at.elapsed()));
+        }
+        if let Some((_, selected_at)) = self.pending_selection {
+            due(millis(CONFIG.constants.play_on_select_delay).saturating_sub(selected_at.elapsed()));
+        }
+        if let Some(changed_at) = self.track_changed_at {
+            due(ui::now_playing::NOTIFY_DELAY.saturating_sub(changed_at.elapsed()));
+        }
+        if let Some(remaining) = self.sleep_remaining() {
+            due(remaining);
+        }
+        if let Some((_, shown_at)) = self.osd {
+            due(ui::osd::OSD_DURATION.saturating_sub(shown_at.elapsed()));
+        }
+        if let Some(remaining) = self.motion.remaining() {
+            due(remaining);
+        }
+        if let Some((_, level, shown_at)) = &self.toast {
+            due(ui::toast::toast_duration(level).saturating_sub(shown_at.elapsed()));
+        }
+        if let Some(remaining) = self.next_frame_in() {
+            due(remaining);
+        }
+        timeout
+    }
+
+    // Nobody is using the app for a while, probably because terminal is in background. Volume is
+    // stepped along with refresh while crossfading so fade keeps using refresh_rate
+    fn is_input_idle(&self) -> bool {
+        let idle_after = CONFIG.constants.input_idle_secs;
+        idle_after != 0
+            && CONFIG.constants.crossfade_secs == 0
+            && self.last_input.elapsed() >= Duration::from_secs(idle_after)
+    }
+
+    // Any key or mouse input brings back the refresh_rate
+    pub fn saw_input(&mut self) {
+        self.last_input = Instant::now();
+    }
+
+    // Remember the highlighted music of musicbar to be played later if play on select is enabled
+    pub fn select_for_playing(&mut self) {
+        if !self.play_on_select {
+            return;
+        }
+        if let Some(selected_index) = self.musicbar.1.selected() {
+            if let Some(music) = self.musicbar.0.get(selected_index) {
+                self.pending_selection = Some((music.id.clone(), Instant::now()));
+            }
+        }
+    }
+
+    // Play the music remembered by `select_for_playing` once it have stayed selected for
+    // play_on_select_delay. Returns true if music was played
+    pub fn play_pending_selection(&mut self) -> bool {
+        let delay = Duration::from_millis(CONFIG.constants.play_on_select_delay);
+        match self.pending_selection {
+            Some((_, selected_at)) if selected_at.elapsed() >= delay => {}
+            _ => return false,
+        }
+
+        if let Some((music_id, _)) = self.pending_selection.take() {
+            self.play_music(&music_id).ok();
+        }
+        true
+    }
+
+    // Remember that query in searchbar was changed so that it is searched after live_search_delay.
+    // Nothing is done when live search is disabled
+    pub fn typed_search(&mut self) {
+        if CONFIG.constants.live_search {
+            self.search_typed_at = Some(Instant::now());
+        }
+    }
+
+    // Returns true if query typed in searchbar have not changed for live_search_delay and is to be
+    // searched now. Query that is same as last searched one is not searched again. Youtube link
+    // is only opened with enter so it is never searched
+    pub fn take_pending_search(&mut self) -> bool {
+        let delay = Duration::from_millis(CONFIG.constants.live_search_delay);
+        match self.search_typed_at {
+            Some(typed_at) if typed_at.elapsed() >= delay => {}
+            _ => return false,
+        }
+
+        self.search_typed_at = None;
+        self.active == ui::Window::Searchbar
+            && self.search.0.trim() != self.search.1
+            && ui::link::parse_link(&self.search.0).is_none()
+    }
+
+    // Search the query given in command line as soon as app starts. Music, playlist and artist
+    // are all searched and musicbar is focused. If play_first is true, first music found is also
+    // played
+    pub fn search_on_start(&mut self, query: &str, play_first: bool) {
+        let query = query.trim().to_string();
+        self.search.0 = query.clone();
+        self.search.1 = query.clone();
+        self.fetched_page = [Some(0); 3];
+        self.filled_source.0 = ui::MusicbarSource::Search(query.clone());
+        self.filled_source.1 = ui::PlaylistbarSource::Search(query.clone());
+        self.filled_source.2 = ui::ArtistbarSource::Search(query);
+        self.active = ui::Window::Musicbar;
+        self.play_first_result = play_first;
+    }
+
+    // Play first music in musicbar if it was asked from command line. This is only done once after
+    // musicbar is first filled. See `State::search_on_start`
+    pub fn play_first_result(&mut self) {
+        if !std::mem::take(&mut self.play_first_result) {
+            return;
+        }
+        if let Some(music_id) = self.musicbar.0.first().map(|music| music.id.clone()) {
+            self.musicbar.1.select(Some(0));
+            self.play_music(&music_id).ok();
+        }
+    }
+
+    // Fill musicbar and playlistbar from the artist who published the music selected in musicbar,
+    // same as selecting the artist in artistbar. Returns false if there is no music selected or if
+    // the artist of it is not known
+    pub fn goto_artist(&mut self) -> bool {
+        let selected = self.musicbar.1.selected();
+        let artist_id = match selected.and_then(|index| self.musicbar.0.get(index)) {
+            Some(music) if !music.artist_id.is_empty() => music.artist_id.clone(),
+            Some(_) => {
+                self.status = "Artist not known..";
+                return false;
+            }
+            None => {
+                self.status = "Nothing selected..";
+                return false;
+            }
+        };
+        self.filled_source.0 = ui::MusicbarSource::Artist(artist_id.clone());
+        self.filled_source.1 = ui::PlaylistbarSource::Artist(artist_id);
+        true
+    }
+
+    // Play next entry of mpv playlist, or previous one if next is false. Music being left is
+    // not faded out
+    pub fn change_track(&mut self, next: bool) {
+        self.skip_crossfade();
+        if next {
+            self.player.play_next();
+        } else {
+            self.player.play_prev();
+        }
+    }
+
+    // Seek the playing track by offset seconds, backward if offset is negative. Target position is
+    // kept in between start and end of track so seeking before start restarts the track and
+    // seeking past the end simply stops at end
+    pub fn seek(&mut self, offset: f64) {
+        if self.bottom.playing.is_none() {
+            return;
+        }
+
+        let position = self
+            .player
+            .get_property::<f64>("time-pos")
+            .unwrap_or_default();
+        let mut target = (position + offset).max(0.0);
+        if let Ok(duration) = self.player.get_property::<f64>("duration") {
+            target = target.min(duration);
+        }
+
+        if self.player.seek_absolute(target).is_ok() {
+            self.status = "Seeking..";
+            // Show the new position right away instead of waiting for mpv to report it
+            self.bottom.music_elapse = Duration::from_secs(target as u64);
+            self.show_osd(format!(
+                "{} / {}",
+                self.bottom.music_elapse.to_string(),
+                self.bottom.music_duration.to_string()
+            ));
+        }
+    }
+
+    // Seconds to seek when seek key is pressed with CTRL. See seek_coarse_percent in config
+    pub fn coarse_seek_step(&self) -> f64 {
+        let percent = CONFIG.constants.seek_coarse_percent;
+        match self.player.get_property::<f64>("duration") {
+            Ok(duration) if percent > 0 && duration > 0.0 => duration * percent as f64 / 100.0,
+            _ => CONFIG.constants.seek_coarse_secs as f64,
+        }
+    }
+
+    // Switch to the audio track next to currently selected one. Switching track in mpv keeps
+    // the playback position so nothing needs to be done for that
+    pub fn cycle_audio_track(&mut self) {
+        let tracks = self.player.audio_tracks();
+        if tracks.len() < 2 {
+            self.status = "Single audio..";
+            return;
+        }
+
+        let current = self.player.get_property::<i64>("aid").ok();
+        let next = tracks
+            .iter()
+            .position(|(id, _)| Some(*id) == current)
+            .map(|index| (index + 1) % tracks.len())
+            .unwrap_or_default();
+
+        if self.player.set_property("aid", tracks[next].0).is_ok() {
+            self.status = "Audio changed..";
+        } else {
+            self.show_toast(ui::ToastLevel::Error, "Cannot switch audio".to_string());
         }
     }
 
+    // Nothing is done if nothing is loaded in mpv. Otherwise paused state shown in ui is read back
+    // from mpv so that it never shows something mpv is not doing
     pub fn toggle_pause(&mut self) {
+        let is_playing = match self.bottom.playing {
+            Some((_, is_playing)) => is_playing,
+            None => {
+                self.status = "Nothing playing..";
+                return;
+            }
+        };
+        let toggled = if is_playing {
+            self.player.pause()
+        } else {
+            self.player.unpause()
+        };
+        if let Err(err) = toggled {
+            log::warn!("Cannot toggle pause. Error: {}", err);
+            self.show_toast(ui::ToastLevel::Error, "Cannot pause or resume".to_string());
+            return;
+        }
+        log::debug!("Toggled pause. Was playing: {}", is_playing);
+
+        self.sync_pause();
+        self.status = match self.bottom.playing {
+            Some((_, false)) => "Paused..",
+            _ => "Playing..",
+        };
+    }
+
+    // Update the paused state of loaded music from mpv. Pause may also be changed outside of
+    // toggle_pause, eg: by restoring a paused session
+    pub fn sync_pause(&mut self) {
         if let Some((_, ref mut is_playing)) = self.bottom.playing {
-            if *is_playing {
-                self.status = "Paused..";
-                self.player.pause().unwrap();
-            } else {
-                self.status = "Playing..";
-                self.player.unpause().unwrap();
+            if let Ok(paused) = self.player.get_property::<bool>("pause") {
+                *is_playing = !paused;
+                if let Some(fader) = &self.fader {
+                    fader.follow_pause(paused);
+                }
             }
-            *is_playing = !*is_playing;
         }
     }
 }
 
 impl ui::State<'_> {
-    pub fn remove_music_from_favourates(&mut self, music: &fetcher::MusicUnit) {
+    pub fn remove_music_from_favourates(&mut self, music: &fetcher::MusicUnit) -> bool {
         let query = format!(
             "
             DELETE FROM
@@ -767,9 +1585,10 @@ impl ui::State<'_> {
         } else {
             self.status = "Err removing..";
         }
+        res.is_ok()
     }
 
-    pub fn remove_playlist_from_favourates(&mut self, playlist: &fetcher::PlaylistUnit) {
+    pub fn remove_playlist_from_favourates(&mut self, playlist: &fetcher::PlaylistUnit) -> bool {
         let query = format!(
             "
             DELETE FROM
@@ -786,9 +1605,10 @@ impl ui::State<'_> {
         } else {
             self.status = "Err removing..";
         }
+        res.is_ok()
     }
 
-    pub fn remove_artist_from_favourates(&mut self, artist: &fetcher::ArtistUnit) {
+    pub fn remove_artist_from_favourates(&mut self, artist: &fetcher::ArtistUnit) -> bool {
         let query = format!(
             "
             DELETE FROM
@@ -806,6 +1626,7 @@ impl ui::State<'_> {
         } else {
             self.status = "Err removing..";
         }
+        res.is_ok()
     }
 
     pub fn add_artist_to_favourates(&mut self, artist: &fetcher::ArtistUnit) {
@@ -861,20 +1682,113 @@ impl ui::State<'_> {
         }
     }
 
-    pub fn add_playlist_to_favourates(&mut self, playlist: &fetcher::PlaylistUnit) {
+    // Record the play of music in history. This is only used to compute listening stats and most
+    // played list so failure is not reported in status
+    pub fn add_to_history(music: &fetcher::MusicUnit) {
         let query = format!(
             "
-            INSERT OR REPLACE INTO {tb_name}
-            (id, name, author, count)
-            VALUES (:id, :name, :author, :count);
+            INSERT INTO {tb_name}
+            (id, title, author, duration, played_at)
+            VALUES (:id, :title, :author, :duration, strftime('%s', 'now'))
         ",
-            tb_name = TB_FAVOURATES_PLAYLIST
+            tb_name = TB_HISTORY
         );
 
         let args = [
-            (":id", &playlist.id),
-            (":name", &playlist.name),
-            (":author", &playlist.author),
+            (":id", &music.id),
+            (":title", &music.name),
+            (":author", &music.artist),
+            (":duration", &music.duration),
+        ];
+
+        if let Err(err) = STORAGE.lock().unwrap().execute(&query, &args) {
+            log::error!("Error adding music to history. Error: {err}", err = err);
+        }
+    }
+
+    // Move music to the top of recently played list. Played music is kept only once in this list
+    // and only latest `recents_limit` of them are kept
+    pub fn add_to_recents(music: &fetcher::MusicUnit) {
+        let insert_query = format!(
+            "
+            INSERT OR REPLACE INTO {tb_name}
+            (id, title, author, duration, played_at)
+            VALUES (:id, :title, :author, :duration, strftime('%s', 'now'))
+        ",
+            tb_name = TB_RECENTS
+        );
+        // Many music can be played in same second so rowid is used to find the latest among them
+        let evict_query = format!(
+            "
+            DELETE FROM {tb_name}
+            WHERE id NOT IN (
+                SELECT id FROM {tb_name}
+                ORDER BY played_at DESC, rowid DESC
+                LIMIT {limit}
+            )
+        ",
+            tb_name = TB_RECENTS,
+            limit = CONFIG.constants.recents_limit
+        );
+
+        let args = [
+            (":id", &music.id),
+            (":title", &music.name),
+            (":author", &music.artist),
+            (":duration", &music.duration),
+        ];
+
+        let conn = STORAGE.lock().unwrap();
+        let res = conn
+            .execute(&insert_query, &args)
+            .and_then(|_| conn.execute(&evict_query, []));
+        if let Err(err) = res {
+            log::error!("Error adding music to recents. Error: {err}", err = err);
+        }
+    }
+
+    // Forget every recently played music along with the history of plays. Musicbar showing the
+    // recently played music is emptied too
+    pub fn clear_history(&mut self) {
+        let query = format!(
+            "
+            DELETE FROM {tb_recents};
+            DELETE FROM {tb_history};
+        ",
+            tb_recents = TB_RECENTS,
+            tb_history = TB_HISTORY
+        );
+        if let Err(err) = STORAGE.lock().unwrap().execute_batch(&query) {
+            log::error!("Error clearing history. Error: {err}", err = err);
+            self.status = "Err clearing..";
+            return;
+        }
+        if matches!(
+            self.filled_source.0,
+            ui::MusicbarSource::RecentlyPlayed | ui::MusicbarSource::MostPlayed
+        ) {
+            self.discard_sort(&ui::Window::Musicbar);
+            self.musicbar.0.clear();
+            self.musicbar.1.select(None);
+            self.list_dirty[ui::MIDDLE_MUSIC_INDEX] = true;
+        }
+        self.status = "History cleared..";
+    }
+
+    pub fn add_playlist_to_favourates(&mut self, playlist: &fetcher::PlaylistUnit) {
+        let query = format!(
+            "
+            INSERT OR REPLACE INTO {tb_name}
+            (id, name, author, count)
+            VALUES (:id, :name, :author, :count);
+        ",
+            tb_name = TB_FAVOURATES_PLAYLIST
+        );
+
+        let args = [
+            (":id", &playlist.id),
+            (":name", &playlist.name),
+            (":author", &playlist.author),
             (":count", &playlist.video_count),
         ];
 
@@ -885,9 +1799,519 @@ impl ui::State<'_> {
             self.status = "Err adding..";
         }
     }
+
+    // Add the playlist whose content is shown in musicbar to favourates. Only the playlist itself
+    // is saved so opening it again later fetches content it has by then. Name and author are
+    // taken from playlistbar as musicbar only knows the id
+    pub fn save_viewed_playlist(&mut self) {
+        let playlist_id = match &self.filled_source.0 {
+            ui::MusicbarSource::Playlist(playlist_id) => playlist_id,
+            _ => {
+                self.status = "No playlist shown..";
+                return;
+            }
+        };
+        let playlist = self
+            .playlistbar
+            .0
+            .iter()
+            .chain(&self.unfiltered.1)
+            .chain(&self.unsorted.1)
+            .find(|playlist| playlist.id == *playlist_id)
+            .cloned();
+        match playlist {
+            Some(playlist) => self.add_playlist_to_favourates(&playlist),
+            None => self.status = "Playlist info not found..",
+        }
+    }
+}
+
+impl ui::State<'_> {
+    // Add given music to the end of play queue. Queue stored by other instance is read first so
+    // that music queued there is kept
+    pub fn enqueue_music(&mut self, music: &fetcher::MusicUnit) {
+        self.sync_queue();
+        let serial = self.queue.iter().map(|(added, ..)| added + 1).max();
+        self.queue
+            .push_back((serial.unwrap_or_default(), music.clone(), false));
+        if self.flush_queue() {
+            self.status = "Queued..";
+        } else {
+            self.status = "Err queueing..";
+        }
+    }
+
+    // Play every music shown in musicbar in the same order. First one is played now and the rest
+    // replace whatever was in play queue so they are played one after another by `play_queue`
+    pub fn play_all(&mut self) {
+        let first = match self.musicbar.0.first() {
+            Some(music) => music.id.clone(),
+            None => {
+                self.status = "Nothing to play..";
+                return;
+            }
+        };
+        let rest = self.musicbar.0.iter().skip(1).cloned().enumerate();
+        self.queue = rest.map(|(added, music)| (added, music, false)).collect();
+        self.queued_next = None;
+        self.queue_selected = 0;
+        let playing = self.load_music(&first).is_ok();
+        if !self.flush_queue() {
+            self.status = "Err queueing..";
+        } else if playing {
+            self.status = "Playing all..";
+        }
+    }
+
+    // Write the whole queue to storage if queue is shared. Previous content of queue in storage
+    // is replaced within single transaction so that other instance never reads half written
+    // queue. Sqlite itself takes care of locking between multiple instance. Queue should be
+    // synced by `State::sync_queue` before it is changed, otherwise change made by other instance
+    // is overwritten. Returns false if queue could not be written
+    pub fn flush_queue(&self) -> bool {
+        if !CONFIG.constants.shared_queue {
+            return true;
+        }
+
+        let query = format!(
+            "
+            INSERT INTO {tb_name}
+            (position, added, id, title, author, duration, played)
+            VALUES
+            (:position, :added, :id, :title, :author, :duration, :played)
+        ",
+            tb_name = TB_QUEUE
+        );
+
+        let mut conn = STORAGE.lock().unwrap();
+        let res = conn.transaction().and_then(|transaction| {
+            transaction.execute(&format!("DELETE FROM {}", TB_QUEUE), [])?;
+            for (position, (added, music, played)) in self.queue.iter().enumerate() {
+                // position, added and played column are decleared as integer so sqlite converts
+                // these to integer
+                let position = position.to_string();
+                let added = added.to_string();
+                let played = u8::from(*played).to_string();
+                let args = [
+                    (":position", &position),
+                    (":added", &added),
+                    (":id", &music.id),
+                    (":title", &music.name),
+                    (":author", &music.artist),
+                    (":duration", &music.duration),
+                    (":played", &played),
+                ];
+                transaction.execute(&query, &args)?;
+            }
+            transaction.commit()
+        });
+
+        res.is_ok()
+    }
+
+    // Bring back the queue in the order items were added to it
+    pub fn restore_queue_order(&mut self) {
+        self.sync_queue();
+        self.queue
+            .make_contiguous()
+            .sort_by_key(|(added, ..)| *added);
+        if self.flush_queue() {
+            self.status = "Queue restored..";
+        } else {
+            self.status = "Err queueing..";
+        }
+    }
+
+    // Read the queue from storage if it was changed by another instance after last read.
+    // Sqlite changes the data_version only when another connection commits the change so our own
+    // change from flush_queue is not read back. Returns true if queue was changed
+    pub fn sync_queue(&mut self) -> bool {
+        if !CONFIG.constants.shared_queue {
+            return false;
+        }
+
+        let conn = STORAGE.lock().unwrap();
+        let version = conn
+            .query_row("PRAGMA data_version", [], |row| row.get::<_, i64>(0))
+            .ok();
+        if version.is_none() || version == self.queue_version {
+            return false;
+        }
+        self.queue_version = version;
+
+        let query = format!(
+            "
+            SELECT
+            added, id, title, author, duration, played
+            FROM {tb_name}
+            ORDER BY position
+        ",
+            tb_name = TB_QUEUE
+        );
+        let mut stmt = match conn.prepare(&query) {
+            Ok(stmt) => stmt,
+            Err(_) => return false,
+        };
+        let results = stmt.query_map([], |row| {
+            let added = row.get::<_, i64>(0).unwrap_or_default();
+            let music = fetcher::MusicUnit {
+                id: row.get(1).unwrap_or_default(),
+                name: row.get(2).unwrap_or_else(|_| "SQL_ERROR".into()),
+                artist: row.get(3).unwrap_or_else(|_| "SQL_ERROR".into()),
+                artist_id: String::new(),
+                duration: row.get(4).unwrap_or_else(|_| "0:0".into()),
+                views: String::new(),
+            };
+            let played = row.get::<_, i64>(5).unwrap_or_default() != 0;
+            Ok((added as usize, music, played))
+        });
+
+        match results {
+            Ok(results) => {
+                let queue = results.filter_map(Result::ok).collect::<VecDeque<_>>();
+                let changed = queue != self.queue;
+                self.queue = queue;
+                changed
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl ui::State<'_> {
+    // Change volume by given step. Changing volume while muted unmutes the playback
+    pub fn change_volume(&mut self, step: i8) {
+        // Step is applied to volume set by user rather than the one lowered by fade
+        self.stop_fade();
+        if let Some(volume) = self.playback_behaviour.muted_volume.take() {
+            self.player.set_volume(volume);
+        }
+        match self.player.change_volume(step) {
+            Some(volume) => {
+                self.playback_behaviour.volume = volume;
+                Self::save_volume(volume);
+                self.show_osd(format!("Vol {}%", volume));
+            }
+            None => self.show_toast(ui::ToastLevel::Error, "Cannot change volume".to_string()),
+        }
+    }
+
+    // Mute the playback if not muted and restore the volume from before muting otherwise.
+    // Volume is kept as it is in playback_behaviour so that it is shown again after unmuting
+    pub fn toggle_mute(&mut self) {
+        self.stop_fade();
+        let (target, muted_volume) = match self.playback_behaviour.muted_volume {
+            Some(volume) => (volume, None),
+            None => (0, Some(self.playback_behaviour.volume)),
+        };
+        if self.player.set_volume(target) {
+            self.playback_behaviour.muted_volume = muted_volume;
+            self.status = if muted_volume.is_some() {
+                "Muted.."
+            } else {
+                "Unmuted.."
+            };
+        } else {
+            self.show_toast(ui::ToastLevel::Error, "Cannot change volume".to_string());
+        }
+    }
+
+    // Play at given speed. Speed is clamped between MIN_SPEED and MAX_SPEED and rounded to
+    // single decimal so that repeated steps of 0.1 do not drift away from 1.0.
+    // mpv applies the speed right away even in middle of track and keeps it for next tracks
+    pub fn set_speed(&mut self, rate: f32) {
+        let rate = ((rate * 10.0).round() / 10.0).clamp(MIN_SPEED, MAX_SPEED);
+        if self.player.set_speed(rate) {
+            self.playback_behaviour.speed = rate;
+            if CONFIG.constants.remember_speed {
+                Self::save_setting("speed", &rate.to_string());
+            }
+        } else {
+            self.show_toast(ui::ToastLevel::Error, "Cannot change speed".to_string());
+        }
+    }
+
+    // Speed at which music is played. 1.0 is the normal speed
+    pub fn speed(&self) -> f32 {
+        self.playback_behaviour.speed
+    }
+
+    // Volume is saved in storage so that next run starts with same volume. Failure is not
+    // reported as this is not something user asked for
+    fn save_volume(volume: u8) {
+        Self::save_setting("volume", &volume.to_string());
+    }
+
+    fn saved_volume() -> Option<u8> {
+        Self::saved_setting("volume")?.parse().ok()
+    }
+
+    fn saved_speed() -> Option<f32> {
+        Self::saved_setting("speed")?.parse().ok()
+    }
+
+    // Save a value in settings table of storage under given name. Value from previous save is
+    // replaced
+    pub fn save_setting(name: &str, value: &str) {
+        let query = format!(
+            "
+            INSERT OR REPLACE INTO {tb_name}
+            (name, value)
+            VALUES (:name, :value)
+        ",
+            tb_name = TB_SETTINGS
+        );
+        if let Err(err) = STORAGE
+            .lock()
+            .unwrap()
+            .execute(&query, &[(":name", name), (":value", value)])
+        {
+            log::error!("Error saving {name}. Error: {err}", name = name, err = err);
+        }
+    }
+
+    pub fn saved_setting(name: &str) -> Option<String> {
+        let query = format!(
+            "SELECT value FROM {tb_name} WHERE name = :name",
+            tb_name = TB_SETTINGS
+        );
+        STORAGE
+            .lock()
+            .unwrap()
+            .query_row(&query, &[(":name", name)], |row| row.get::<_, String>(0))
+            .ok()
+    }
+}
+
+impl ui::State<'_> {
+    // Start filtering the list of active window. Returns false if active window has no list to
+    // filter. If the list is already filtered, continue editing the same filter
+    pub fn start_filter(&mut self) -> bool {
+        if let Some(filter) = &mut self.filter {
+            if filter.window == self.active {
+                filter.editing = true;
+                return true;
+            }
+        }
+
+        match self.active {
+            ui::Window::Musicbar => self.unfiltered.0 = self.musicbar.0.clone(),
+            ui::Window::Playlistbar => self.unfiltered.1 = self.playlistbar.0.clone(),
+            ui::Window::Artistbar => self.unfiltered.2 = self.artistbar.0.clone(),
+            _ => return false,
+        }
+        // Only single list is filtered at a time so bring back the previously filtered list
+        self.clear_filter();
+        self.filter = Some(ui::filter::ListFilter {
+            window: self.active.clone(),
+            query: String::new(),
+            editing: true,
+        });
+        true
+    }
+
+    // Rebuild the filtered list from original list with current filter query
+    pub fn apply_filter(&mut self) {
+        let filter = match &self.filter {
+            Some(filter) => filter,
+            None => return,
+        };
+
+        let (query, mode) = (filter.query.as_str(), self.filter_mode);
+        let is_empty = match filter.window {
+            ui::Window::Musicbar => {
+                self.musicbar.0 = ui::filter::apply(&self.unfiltered.0, query, mode);
+                self.musicbar.0.is_empty()
+            }
+            ui::Window::Playlistbar => {
+                self.playlistbar.0 = ui::filter::apply(&self.unfiltered.1, query, mode);
+                self.playlistbar.0.is_empty()
+            }
+            ui::Window::Artistbar => {
+                self.artistbar.0 = ui::filter::apply(&self.unfiltered.2, query, mode);
+                self.artistbar.0.is_empty()
+            }
+            _ => return,
+        };
+
+        // Previously selected index may not exist anymore so always select the best match
+        let selection = if is_empty { None } else { Some(0) };
+        match filter.window {
+            ui::Window::Musicbar => self.musicbar.1.select(selection),
+            ui::Window::Playlistbar => self.playlistbar.1.select(selection),
+            _ => self.artistbar.1.select(selection),
+        }
+        let window = filter.window.clone();
+        self.clear_marks(&window);
+    }
+
+    // Remove the filter and bring back all the item in filtered list
+    pub fn clear_filter(&mut self) {
+        if let Some(filter) = self.filter.take() {
+            self.clear_marks(&filter.window);
+            match filter.window {
+                ui::Window::Musicbar => {
+                    self.musicbar.0 = std::mem::take(&mut self.unfiltered.0);
+                }
+                ui::Window::Playlistbar => {
+                    self.playlistbar.0 = std::mem::take(&mut self.unfiltered.1);
+                }
+                ui::Window::Artistbar => {
+                    self.artistbar.0 = std::mem::take(&mut self.unfiltered.2);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Remove the filter of given window when list is about to be filled with new content.
+    // Unfiltered list is brought back so that it is still shown if new content cannot be fetched
+    pub fn discard_filter(&mut self, window: &ui::Window) {
+        if matches!(&self.filter, Some(filter) if filter.window == *window) {
+            self.clear_filter();
+        }
+    }
+
+    // Returns true if character keys should be taken as input to the filter query
+    pub fn is_filtering(&self) -> bool {
+        match &self.filter {
+            Some(filter) => filter.editing && filter.window == self.active,
+            None => false,
+        }
+    }
+
+    // Title of list block. When the list is filtered, filter query is also shown in title
+    pub fn list_title(&self, title: &str, window: &ui::Window) -> String {
+        let title = self.sorted_title(title, window);
+        let title = title.as_str();
+        match &self.filter {
+            Some(filter) if filter.window == *window => {
+                let (matched, total) = match window {
+                    ui::Window::Musicbar => (self.musicbar.0.len(), self.unfiltered.0.len()),
+                    ui::Window::Playlistbar => (self.playlistbar.0.len(), self.unfiltered.1.len()),
+                    _ => (self.artistbar.0.len(), self.unfiltered.2.len()),
+                };
+                format!("{}[\\{}] {}/{} ", title, filter.query, matched, total)
+            }
+            _ if self.has_fetch_failed(window) => format!("{}[Fetch error] ", title),
+            _ => match self.page_range(window) {
+                Some((page, first, last)) => {
+                    let end = if self.is_last_page(window) {
+                        " (end)"
+                    } else {
+                        ""
+                    };
+                    format!("{}[Page {}{}: {}-{}] ", title, page, end, first, last)
+                }
+                None => title.to_string(),
+            },
+        }
+    }
+
+    // Returns true if last fetch of list of given window has failed
+    pub fn has_fetch_failed(&self, window: &ui::Window) -> bool {
+        match window {
+            ui::Window::Musicbar => self.fetch_failed[ui::MIDDLE_MUSIC_INDEX],
+            ui::Window::Playlistbar => self.fetch_failed[ui::MIDDLE_PLAYLIST_INDEX],
+            ui::Window::Artistbar => self.fetch_failed[ui::MIDDLE_ARTIST_INDEX],
+            _ => false,
+        }
+    }
+
+    // Returns true if list of given window is showing the last page of its source
+    pub fn is_last_page(&self, window: &ui::Window) -> bool {
+        match window {
+            ui::Window::Musicbar => self.last_page[ui::MIDDLE_MUSIC_INDEX],
+            ui::Window::Playlistbar => self.last_page[ui::MIDDLE_PLAYLIST_INDEX],
+            ui::Window::Artistbar => self.last_page[ui::MIDDLE_ARTIST_INDEX],
+            _ => false,
+        }
+    }
+
+    // Empty every list showing result of search. Searched query is kept so that it can still be
+    // edited with edit_search key
+    pub fn clear_search_results(&mut self) {
+        let (music, playlist, artist) = (
+            matches!(self.filled_source.0, ui::MusicbarSource::Search(_)),
+            matches!(self.filled_source.1, ui::PlaylistbarSource::Search(_)),
+            matches!(self.filled_source.2, ui::ArtistbarSource::Search(_)),
+        );
+        if !(music || playlist || artist) {
+            self.status = "No search results..";
+            return;
+        }
+        if music {
+            self.discard_sort(&ui::Window::Musicbar);
+            self.musicbar.0.clear();
+            self.musicbar.1.select(None);
+        }
+        if playlist {
+            self.discard_sort(&ui::Window::Playlistbar);
+            self.playlistbar.0.clear();
+            self.playlistbar.1.select(None);
+        }
+        if artist {
+            self.discard_sort(&ui::Window::Artistbar);
+            self.artistbar.0.clear();
+            self.artistbar.1.select(None);
+        }
+        self.mark_lists_dirty();
+        self.status = "Results cleared..";
+    }
+
+    // Ask communicator to fetch list of active window again without changing its source or page
+    pub fn refresh_active(&mut self) {
+        let index = match self.active {
+            ui::Window::Musicbar => ui::MIDDLE_MUSIC_INDEX,
+            ui::Window::Playlistbar => ui::MIDDLE_PLAYLIST_INDEX,
+            ui::Window::Artistbar => ui::MIDDLE_ARTIST_INDEX,
+            _ => {
+                self.status = "Nothing to refresh..";
+                return;
+            }
+        };
+        // Page is forgotten once end of result is reached. First page is fetched in such case
+        self.fetched_page[index].get_or_insert(0);
+        self.refresh[index] = true;
+        self.status = "Refreshing..";
+    }
+
+    // Select item at given position of list in window again after the list is refreshed.
+    // Position is clamped to the end of new list
+    pub fn reselect(&mut self, window: &ui::Window, position: usize) {
+        let (len, list_state) = match window {
+            ui::Window::Musicbar => (self.musicbar.0.len(), &mut self.musicbar.1),
+            ui::Window::Playlistbar => (self.playlistbar.0.len(), &mut self.playlistbar.1),
+            ui::Window::Artistbar => (self.artistbar.0.len(), &mut self.artistbar.1),
+            _ => return,
+        };
+        list_state.select(len.checked_sub(1).map(|last| position.min(last)));
+    }
+
+    // Page number and the position of first and last item shown in list of given window, all
+    // starting from 1. Position counts the items in previous pages too. None if list is empty
+    fn page_range(&self, window: &ui::Window) -> Option<(usize, usize, usize)> {
+        let (index, len) = match window {
+            ui::Window::Musicbar => (ui::MIDDLE_MUSIC_INDEX, self.musicbar.0.len()),
+            ui::Window::Playlistbar => (ui::MIDDLE_PLAYLIST_INDEX, self.playlistbar.0.len()),
+            ui::Window::Artistbar => (ui::MIDDLE_ARTIST_INDEX, self.artistbar.0.len()),
+            _ => return None,
+        };
+        let page = self.fetched_page[index]?;
+        if len == 0 {
+            return None;
+        }
+        let first = page * CONFIG.constants.page_size[index] + 1;
+        Some((page + 1, first, first + len - 1))
+    }
 }
 
 impl ui::Window {
+    // Window that has to be answered or closed before focus can move to another one
+    pub fn is_modal(&self) -> bool {
+        matches!(self, ui::Window::Confirm(_) | ui::Window::Menu)
+    }
+
     /* Any components of top bar and bottombar are not focusable instead directly controlled by the shortcut keys */
     pub fn next(&self) -> ui::Window {
         match self {
@@ -897,7 +2321,12 @@ impl ui::Window {
             ui::Window::Searchbar
             | ui::Window::Artistbar
             | ui::Window::BottomControl
-            | ui::Window::Popup(..) => ui::Window::Sidebar,
+            | ui::Window::Popup(..)
+            | ui::Window::Helpbar
+            | ui::Window::Queuebar
+            | ui::Window::Lyrics
+            | ui::Window::Confirm(_)
+            | ui::Window::Menu => ui::Window::Sidebar,
             ui::Window::None => unreachable!(),
         }
     }
@@ -910,10 +2339,178 @@ impl ui::Window {
             ui::Window::Searchbar
             | ui::Window::Sidebar
             | ui::Window::BottomControl
-            | ui::Window::Popup(..) => ui::Window::Artistbar,
+            | ui::Window::Popup(..)
+            | ui::Window::Helpbar
+            | ui::Window::Queuebar
+            | ui::Window::Lyrics
+            | ui::Window::Confirm(_)
+            | ui::Window::Menu => ui::Window::Artistbar,
             ui::Window::None => unreachable!(),
         }
     }
+
+    // Returns true if this window is drawn over everything else
+    pub fn is_overlay(&self) -> bool {
+        matches!(
+            self,
+            ui::Window::Popup(..)
+                | ui::Window::Helpbar
+                | ui::Window::Queuebar
+                | ui::Window::Lyrics
+                | ui::Window::Confirm(_)
+                | ui::Window::Menu
+        )
+    }
+}
+
+impl ui::RepeatMode {
+    // Repeat mode to switch to when repeat key is pressed
+    pub fn next(&self) -> Self {
+        match self {
+            ui::RepeatMode::All => ui::RepeatMode::One,
+            ui::RepeatMode::One => ui::RepeatMode::Nothing,
+            ui::RepeatMode::Nothing => ui::RepeatMode::All,
+        }
+    }
+}
+
+// Minutes of sleep timer and the status shown when it is chosen. Sleep timer key cycles through
+// these and then turns the timer off
+const SLEEP_TIMER_STEPS: [(u64, &str); 3] = [
+    (15, "Sleep in 15 min.."),
+    (30, "Sleep in 30 min.."),
+    (60, "Sleep in 60 min.."),
+];
+
+impl ui::State<'_> {
+    // Switch sleep timer to next duration or off. Timer always starts again from now
+    pub fn cycle_sleep_timer(&mut self) {
+        let next = match self.sleep_timer {
+            None => 0,
+            Some((index, _)) => index + 1,
+        };
+        match SLEEP_TIMER_STEPS.get(next) {
+            Some((minutes, status)) => {
+                let deadline = Instant::now() + Duration::from_secs(minutes * 60);
+                self.sleep_timer = Some((next, deadline));
+                self.status = status;
+            }
+            None => {
+                self.sleep_timer = None;
+                self.status = "Sleep timer off..";
+            }
+        }
+    }
+
+    // Time left before sleep timer ends. None if timer is off
+    pub fn sleep_remaining(&self) -> Option<Duration> {
+        self.sleep_timer
+            .map(|(_, deadline)| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    // Returns true once the sleep timer has ended. Timer is turned off at the same time so this
+    // is true only once for every timer. Playback is paused here and caller should quit the app
+    // if sleep_timer_quit is set
+    pub fn take_sleep_timer(&mut self) -> bool {
+        match self.sleep_remaining() {
+            Some(remaining) if remaining.is_zero() => {}
+            _ => return false,
+        }
+
+        self.sleep_timer = None;
+        if let Some((_, true)) = self.bottom.playing {
+            self.toggle_pause();
+        }
+        self.status = "Good night..";
+        true
+    }
+}
+
+impl ui::DownloadJob {
+    pub fn new(url: String) -> Self {
+        ui::DownloadJob {
+            url,
+            state: Arc::new(Mutex::new(ui::DownloadState::Queued)),
+        }
+    }
+
+    pub fn state(&self) -> ui::DownloadState {
+        *self.state.lock().unwrap()
+    }
+}
+
+impl ui::State<'_> {
+    // True if any download is yet to be finished
+    pub fn has_running_downloads(&self) -> bool {
+        self.downloads.iter().any(|job| {
+            matches!(
+                job.state(),
+                ui::DownloadState::Queued
+                    | ui::DownloadState::Downloading
+                    | ui::DownloadState::Paused
+            )
+        })
+    }
+
+    // Show toast if any download finished since last call and forget about those downloads.
+    // Returns true if toast was shown
+    pub fn report_downloads(&mut self) -> bool {
+        let (mut done, mut failed) = (0, 0);
+        self.downloads.retain(|job| match job.state() {
+            ui::DownloadState::Done => {
+                done += 1;
+                false
+            }
+            ui::DownloadState::Failed => {
+                log::warn!("Download of {} failed", job.url);
+                failed += 1;
+                false
+            }
+            ui::DownloadState::Queued
+            | ui::DownloadState::Downloading
+            | ui::DownloadState::Paused => true,
+        });
+
+        let (level, text) = match (done, failed) {
+            (0, 0) => return false,
+            (_, 0) => (ui::ToastLevel::Info, format!("{} download finished", done)),
+            (0, _) => (ui::ToastLevel::Error, format!("{} download failed", failed)),
+            (_, _) => (
+                ui::ToastLevel::Warning,
+                format!("{} of {} download failed", failed, done + failed),
+            ),
+        };
+        self.show_toast(level, text);
+        true
+    }
+}
+
+impl ui::SearchScope {
+    // Scope to switch to when search_scope key is pressed
+    pub fn next(&self) -> Self {
+        match self {
+            ui::SearchScope::All => ui::SearchScope::Music,
+            ui::SearchScope::Music => ui::SearchScope::Playlist,
+            ui::SearchScope::Playlist => ui::SearchScope::Artist,
+            ui::SearchScope::Artist | ui::SearchScope::Focused => ui::SearchScope::All,
+        }
+    }
+
+    // Find the scope from type prefix of query. Returns the scope and query without the prefix.
+    // Query without any prefix in search_by_type is returned as it is with None
+    pub fn from_query(query: &str) -> (Option<Self>, &str) {
+        let scopes = [
+            ui::SearchScope::Music,
+            ui::SearchScope::Playlist,
+            ui::SearchScope::Artist,
+        ];
+        for (prefix, scope) in CONFIG.constants.search_by_type.iter().zip(scopes) {
+            if let Some(query) = query.strip_prefix(prefix.as_str()) {
+                return (Some(scope), query.trim());
+            }
+        }
+        (None, query)
+    }
 }
 
 impl std::convert::TryFrom<usize> for ui::SidebarOption {
@@ -925,8 +2522,87 @@ impl std::convert::TryFrom<usize> for ui::SidebarOption {
             2 => Ok(ui::SidebarOption::Liked),
             3 => Ok(ui::SidebarOption::Saved),
             4 => Ok(ui::SidebarOption::Following),
-            5 => Ok(ui::SidebarOption::Search),
+            5 => Ok(ui::SidebarOption::RecentlyPlayed),
+            6 => Ok(ui::SidebarOption::MostPlayed),
+            7 => Ok(ui::SidebarOption::Search),
             _ => Err("No sidebar option found corresponding to this usize"),
         }
     }
 }
+
+// Suspend (when pause is true) or continue the downloader leading given process group. Every
+// process of group is sent SIGSTOP/SIGCONT signal. Returns true on success
+#[cfg(unix)]
+fn signal_download(group: u32, pause: bool) -> bool {
+    let signal = if pause { libc::SIGSTOP } else { libc::SIGCONT };
+    // Negative process id stands for the process group. Sending signal has no other effect
+    unsafe { libc::kill(-(group as libc::pid_t), signal) == 0 }
+}
+
+// There is no such signal on other platform so downloads cannot be paused
+#[cfg(not(unix))]
+fn signal_download(_group: u32, _pause: bool) -> bool {
+    false
+}
+
+impl ui::Downloaders {
+    // Keep track of downloader that just started as leader of given process group. It is paused
+    // right away if downloads are paused. Returns true if downloader is paused
+    pub fn started(&mut self, group: u32) -> bool {
+        self.groups.push(group);
+        self.paused && signal_download(group, true)
+    }
+
+    pub fn finished(&mut self, group: u32) {
+        self.groups.retain(|running| *running != group);
+    }
+}
+
+impl ui::State<'_> {
+    // Pause all the running downloads if not paused or else resume all of them. Download that
+    // starts while paused is paused as soon as it starts
+    pub fn toggle_pause_downloads(&mut self) {
+        let mut downloaders = self.downloaders.lock().unwrap();
+        let pause = !downloaders.paused;
+        let mut sucess = true;
+        for group in downloaders.groups.iter() {
+            sucess = signal_download(*group, pause) && sucess;
+        }
+        downloaders.paused = pause;
+
+        let (from, to) = if pause {
+            (ui::DownloadState::Downloading, ui::DownloadState::Paused)
+        } else {
+            (ui::DownloadState::Paused, ui::DownloadState::Downloading)
+        };
+        for job in self.downloads.iter() {
+            let mut job_state = job.state.lock().unwrap();
+            if *job_state == from {
+                *job_state = to;
+            }
+        }
+        std::mem::drop(downloaders);
+
+        self.status = match (sucess, pause) {
+            (false, _) => "Signal error..",
+            (true, true) => "Downloads paused..",
+            (true, false) => "Downloads resumed..",
+        };
+    }
+
+    // Downloads yet to finish as shown in status bar. eg: 2 downloading, 1 paused
+    pub fn downloads_line(&self) -> Option<String> {
+        let count = |wanted: &[ui::DownloadState]| {
+            let states = self.downloads.iter().map(ui::DownloadJob::state);
+            states.filter(|state| wanted.contains(state)).count()
+        };
+        let running = count(&[ui::DownloadState::Queued, ui::DownloadState::Downloading]);
+        let paused = count(&[ui::DownloadState::Paused]);
+        match (running, paused) {
+            (0, 0) => None,
+            (_, 0) => Some(format!("{} downloading", running)),
+            (0, _) => Some(format!("{} download paused", paused)),
+            (_, _) => Some(format!("{} downloading, {} paused", running, paused)),
+        }
+    }
+}